@@ -0,0 +1,32 @@
+use std::time::Instant;
+
+use sqlengine::database::Database;
+
+const ITERATIONS: u32 = 200;
+
+// queries that scan every row of the table and evaluate expressions over whole chunks
+const QUERIES: [&str; 8] = [
+    "select * from 'tests/resources/data/userdata1.parquet'",
+    "select count(*), sum(salary), max(registration_dttm) from 'tests/resources/data/userdata1.parquet'",
+    "select id, first_name from 'tests/resources/data/userdata1.parquet' where salary > 100000",
+    "select upper(first_name), length(last_name), abs(salary) from 'tests/resources/data/userdata1.parquet'",
+    "select cast(id as varchar), cast(salary as bigint) from 'tests/resources/data/userdata1.parquet'",
+    "select nullif(gender, 'Male'), nullif(id, 7) from 'tests/resources/data/userdata1.parquet'",
+    "select count(*) from 'tests/resources/data/userdata1.parquet' where first_name like '%an%' or email ilike 'a%'",
+    "select count(b.id) from 'tests/resources/data/userdata1.parquet' as a left join 'tests/resources/data/userdata1.parquet' as b on a.id = b.id + 500",
+];
+
+fn main() {
+    let db = Database::new().unwrap();
+
+    for query in QUERIES {
+        db.execute(query).unwrap();
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            db.execute(query).unwrap();
+        }
+        let millis = start.elapsed().as_secs_f64() * 1000.0 / ITERATIONS as f64;
+        println!("{:>9.3} ms  {}", millis, query);
+    }
+}
@@ -3,6 +3,7 @@ use crate::optimizer;
 use crate::parser;
 use crate::planner;
 use crate::types::error::Error;
+use crate::types::field::Field;
 use crate::types::ResultSet;
 
 pub struct Database {
@@ -28,8 +29,39 @@ impl Database {
     }
 
     pub fn execute(&self, sql: &str) -> Result<ResultSet, Error> {
+        self.execute_with_params(sql, &[])
+    }
+
+    pub fn optimizer_rules(&self) -> Vec<&'static str> {
+        self.optimizer.rule_names()
+    }
+
+    pub fn set_optimizer_rule_enabled(&mut self, name: &str, enabled: bool) -> Result<(), Error> {
+        self.optimizer.set_rule_enabled(name, enabled)
+    }
+
+    // common table expressions are planned again at every reference unless they are materialized
+    pub fn set_materialize_ctes(&mut self, materialize: bool) {
+        self.planner.set_materialize_ctes(materialize);
+    }
+
+    // plans and optimizes a statement without executing it and returns the optimizer rules that fired
+    pub fn optimizer_trace(&self, sql: &str) -> Result<Vec<String>, Error> {
+        let ast = self.parser.parse(sql)?;
+        let plan = self.planner.build_statements(&ast, &[])?;
+        let (_, fired_rules) = self.optimizer.optimize_with_trace(plan)?;
+        Ok(fired_rules)
+    }
+
+    // the number of parquet row groups that were skipped by all the queries run so far
+    pub fn row_groups_pruned(&self) -> u64 {
+        self.executor.metrics().row_groups_pruned()
+    }
+
+    // executes a statement with values bound to its numbered parameters ($1, $2, ...)
+    pub fn execute_with_params(&self, sql: &str, params: &[Field]) -> Result<ResultSet, Error> {
         let ast = self.parser.parse(sql)?;
-        let plan = self.planner.build_statements(&ast)?;
+        let plan = self.planner.build_statements(&ast, params)?;
         let optimized_plan = self.optimizer.optimize(plan)?;
         let result_set = self.executor.execute(optimized_plan)?;
         Ok(result_set)
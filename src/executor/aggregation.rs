@@ -1,22 +1,30 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use parquet::record::Field;
 use sqlparser::ast::{Expr, Function, SelectItem};
 
 use crate::executor::Executor;
 use crate::planner::OutputSchema;
+use crate::types::coercion;
+use crate::types::data_type::DataType;
 use crate::types::error::Error;
-use crate::types::{Chunk, Row, TupleValue};
+use crate::types::field::Field;
+use crate::types::vector::{Bitmap, Vector, VectorData};
+use crate::types::{Chunk, KeyValue, Row, TupleValue};
 
-use super::expression::{Caster, ExprEvaluator};
-use super::{Buffer, VECTOR_SIZE_THRESHOLD};
+use super::expression::{Caster, ExprEvaluator, PhysicalExpr};
+use super::{row_key, Buffer, RowKey, VECTOR_SIZE_THRESHOLD};
 
-type GroupByKey = Vec<String>;
 type AggregationColumns = Vec<Box<dyn Accumulator>>;
 type NonAggregationColumns = Vec<Field>;
 
 pub trait Accumulator {
     fn accumulate(&mut self, field: &Field) -> Result<(), Error>;
+    fn accumulate_vector(&mut self, vector: &Vector) -> Result<(), Error> {
+        for i in 0..vector.len() {
+            self.accumulate(&vector.get(i))?;
+        }
+        Ok(())
+    }
     fn aggregate(&self) -> Result<Field, Error>;
 }
 
@@ -24,8 +32,10 @@ pub struct Aggregation {
     output_schema: OutputSchema,
     child: Box<dyn Executor>,
     aggregates: Vec<Function>,
-    non_aggregates: Vec<SelectItem>,
-    group_by: Vec<Expr>,
+    // the compiled argument of each aggregate function
+    arguments: Vec<PhysicalExpr>,
+    non_aggregates: Vec<PhysicalExpr>,
+    group_by: Vec<PhysicalExpr>,
 
     buffer: Option<Buffer>,
 }
@@ -38,12 +48,31 @@ impl Aggregation {
         group_by: Vec<Expr>,
         output_schema: OutputSchema,
     ) -> Result<Box<Aggregation>, Error> {
+        let child_output_schema = child.get_output_schema();
+
+        // unsupported functions are reported before their arguments are checked
+        for function in aggregates.iter() {
+            Self::new_accumulator(function)?;
+        }
+        let arguments = aggregates
+            .iter()
+            .map(|function| PhysicalExpr::compile(&Self::get_expr(function)?, &child_output_schema))
+            .collect::<Result<Vec<PhysicalExpr>, Error>>()?;
+        let group_by = group_by
+            .iter()
+            .map(|expr| PhysicalExpr::compile(expr, &child_output_schema))
+            .collect::<Result<Vec<PhysicalExpr>, Error>>()?;
+
         Ok(Box::new(Aggregation {
+            non_aggregates: PhysicalExpr::compile_select_items(
+                &non_aggregates,
+                &child_output_schema,
+            )?,
             child,
             output_schema,
             group_by,
             aggregates,
-            non_aggregates,
+            arguments,
             buffer: None,
         }))
     }
@@ -54,101 +83,68 @@ impl Aggregation {
             return Ok(());
         }
 
-        let mut rows_map: HashMap<GroupByKey, (AggregationColumns, NonAggregationColumns)> =
-            HashMap::new();
+        // the groups are kept in the order they are first seen and the map finds the group of a key
+        let mut group_indexes: HashMap<RowKey, usize> = HashMap::new();
+        let mut groups: Vec<(AggregationColumns, NonAggregationColumns)> = Vec::new();
 
         loop {
             let chunk = self.child.next_chunk()?;
             if chunk.is_empty() {
                 break;
             }
-            for row in chunk.get_rows() {
-                let group_by_values: Vec<Field> = self
-                    .group_by
-                    .iter()
-                    .map(|expr| ExprEvaluator::evaluate(expr, row, &self.child.get_output_schema()))
-                    .collect::<Result<Vec<Field>, Error>>()?;
-
-                // TODO(Dylan): See is there is some other better method to generate key
-                let key = group_by_values
-                    .iter()
-                    .map(|field| field.to_string())
-                    .collect::<GroupByKey>();
-
-                if let std::collections::hash_map::Entry::Vacant(e) = rows_map.entry(key.clone()) {
-                    let mut accumulators: AggregationColumns = self
-                        .aggregates
-                        .iter()
-                        .map(|a| self.new_accumulator(a))
-                        .collect::<Result<AggregationColumns, Error>>()?;
-                    for (i, function) in self.aggregates.iter().enumerate() {
-                        let field = ExprEvaluator::evaluate(
-                            &self.get_expr(function)?,
-                            row,
-                            &self.child.get_output_schema(),
-                        )?;
-                        accumulators[i].accumulate(&field)?;
-                    }
 
-                    let mut non_aggregated_values: NonAggregationColumns = Vec::new();
-                    for expr in self.non_aggregates.iter() {
-                        match expr {
-                            SelectItem::UnnamedExpr(e) => {
-                                let field = ExprEvaluator::evaluate(
-                                    e,
-                                    row,
-                                    &self.child.get_output_schema(),
-                                )?;
-                                non_aggregated_values.push(field);
-                            }
-                            SelectItem::Wildcard(_) => {
-                                for col in row {
-                                    non_aggregated_values.push(col.value.clone());
-                                }
-                            }
-                            _ => {
-                                return Err(Error::Execution(format!(
-                                    "Unsupported select item: {}",
-                                    expr
-                                )));
-                            }
-                        }
-                    }
+            let group_by_vectors = Self::evaluate_all(&self.group_by, &chunk)?;
+            let argument_vectors = Self::evaluate_all(&self.arguments, &chunk)?;
+            let non_aggregate_vectors = Self::evaluate_all(&self.non_aggregates, &chunk)?;
+
+            // without a group by every row is in the same group so whole vectors are accumulated at once
+            if self.group_by.is_empty() {
+                if groups.is_empty() {
+                    groups.push(self.new_group(&non_aggregate_vectors, 0)?);
+                }
+                for (accumulator, vector) in groups[0].0.iter_mut().zip(argument_vectors.iter()) {
+                    accumulator.accumulate_vector(vector)?;
+                }
+                continue;
+            }
+
+            for row in 0..chunk.size() {
+                let key = row_key(&group_by_vectors, row);
 
-                    e.insert((accumulators, non_aggregated_values));
-                } else {
-                    let value = rows_map.get_mut(&key).unwrap();
-
-                    for (i, function) in self.aggregates.iter().enumerate() {
-                        let field = ExprEvaluator::evaluate(
-                            &self.get_expr(function)?,
-                            row,
-                            &self.child.get_output_schema(),
-                        )?;
-                        value.0[i].accumulate(&field)?;
+                let group = match group_indexes.get(&key) {
+                    Some(group) => *group,
+                    None => {
+                        group_indexes.insert(key, groups.len());
+                        groups.push(self.new_group(&non_aggregate_vectors, row)?);
+                        groups.len() - 1
                     }
+                };
+
+                for (accumulator, vector) in groups[group].0.iter_mut().zip(argument_vectors.iter())
+                {
+                    accumulator.accumulate(&vector.get(row))?;
                 }
             }
         }
 
-        //  if there are no rows we need to insert an empty row with empty accumulators
-        if rows_map.keys().len() == 0 {
+        // if there are no rows and no group by we need to insert an empty row with empty accumulators
+        if groups.is_empty() && self.group_by.is_empty() {
             let accumulators: AggregationColumns = self
                 .aggregates
                 .iter()
-                .map(|a| self.new_accumulator(a))
+                .map(Self::new_accumulator)
                 .collect::<Result<AggregationColumns, Error>>()?;
             let mut non_aggregated_values: NonAggregationColumns = Vec::new();
             for _ in 0..self.non_aggregates.len() {
                 non_aggregated_values.push(Field::Null);
             }
-            rows_map.insert(Vec::new(), (accumulators, non_aggregated_values));
+            groups.push((accumulators, non_aggregated_values));
         }
 
         let mut rows = Buffer::new();
 
         // calculate the rows
-        for aggregate_row in rows_map.values() {
+        for aggregate_row in groups.iter() {
             let mut row: Row = Vec::new();
             for (i, _function) in self.aggregates.iter().enumerate() {
                 row.push(TupleValue {
@@ -165,21 +161,51 @@ impl Aggregation {
         Ok(())
     }
 
-    fn new_accumulator(&self, function: &Function) -> Result<Box<dyn Accumulator>, Error> {
-        match function.name.to_string().as_str() {
-            "max" => Ok(Box::new(MaxAccumulator::new())),
-            "min" => Ok(Box::new(MinAccumulator::new())),
-            "sum" => Ok(Box::new(SumAccumulator::new())),
-            "count" => Ok(Box::new(CountAccumulator::new())),
-            "avg" => Ok(Box::new(AvgAccumulator::new())),
-            _ => Err(Error::Execution(format!(
-                "Unsupported function: {}",
-                function.name
-            ))),
+    fn evaluate_all(exprs: &[PhysicalExpr], chunk: &Chunk) -> Result<Vec<Vector>, Error> {
+        exprs.iter().map(|expr| expr.evaluate(chunk)).collect()
+    }
+
+    // creates the accumulators of a group and keeps the non aggregated values of its first row
+    fn new_group(
+        &self,
+        non_aggregate_vectors: &[Vector],
+        row: usize,
+    ) -> Result<(AggregationColumns, NonAggregationColumns), Error> {
+        let accumulators = self
+            .aggregates
+            .iter()
+            .map(Self::new_accumulator)
+            .collect::<Result<AggregationColumns, Error>>()?;
+        let non_aggregated_values = non_aggregate_vectors
+            .iter()
+            .map(|vector| vector.get(row))
+            .collect();
+
+        Ok((accumulators, non_aggregated_values))
+    }
+
+    fn new_accumulator(function: &Function) -> Result<Box<dyn Accumulator>, Error> {
+        let accumulator: Box<dyn Accumulator> = match function.name.to_string().as_str() {
+            "max" => Box::new(MaxAccumulator::new()),
+            "min" => Box::new(MinAccumulator::new()),
+            "sum" => Box::new(SumAccumulator::new()),
+            "count" => Box::new(CountAccumulator::new()),
+            "avg" => Box::new(AvgAccumulator::new()),
+            _ => {
+                return Err(Error::Execution(format!(
+                    "Unsupported function: {}",
+                    function.name
+                )))
+            }
+        };
+
+        if function.distinct {
+            return Ok(Box::new(DistinctAccumulator::new(accumulator)));
         }
+        Ok(accumulator)
     }
 
-    fn get_expr(&self, function: &Function) -> Result<Expr, Error> {
+    fn get_expr(function: &Function) -> Result<Expr, Error> {
         if function.args.len() != 1 {
             return Err(Error::Expression(format!(
                 "Unsupported number of parameteres: {}",
@@ -227,6 +253,34 @@ impl Executor for Aggregation {
     }
 }
 
+// passes each distinct value to the accumulator once, such as for count(distinct x)
+struct DistinctAccumulator {
+    seen: HashSet<KeyValue>,
+    accumulator: Box<dyn Accumulator>,
+}
+
+impl DistinctAccumulator {
+    fn new(accumulator: Box<dyn Accumulator>) -> DistinctAccumulator {
+        DistinctAccumulator {
+            seen: HashSet::new(),
+            accumulator,
+        }
+    }
+}
+
+impl Accumulator for DistinctAccumulator {
+    fn accumulate(&mut self, field: &Field) -> Result<(), Error> {
+        if self.seen.insert(KeyValue::from(field)) {
+            self.accumulator.accumulate(field)?;
+        }
+        Ok(())
+    }
+
+    fn aggregate(&self) -> Result<Field, Error> {
+        self.accumulator.aggregate()
+    }
+}
+
 struct MaxAccumulator {
     max: Option<Field>,
 }
@@ -327,18 +381,64 @@ impl Accumulator for SumAccumulator {
             return Ok(());
         }
 
+        let field = widen_integer(field);
+
         match self.sum {
             Some(ref sum) => {
                 let x = ExprEvaluator::evaluate_binary_op(
-                    field,
+                    &field,
                     &sqlparser::ast::BinaryOperator::Plus,
                     sum,
                 )?;
                 self.sum = Some(x);
             }
             None => {
-                self.sum = Some(field.clone());
+                self.sum = Some(field);
+            }
+        }
+        Ok(())
+    }
+
+    fn accumulate_vector(&mut self, vector: &Vector) -> Result<(), Error> {
+        let validity = vector.validity();
+        let sum = match (vector.data(), &self.sum) {
+            (VectorData::Int(v), Some(Field::Long(s))) => {
+                sum_valid(v, validity, Some(*s), i64::checked_add).map(|s| s.map(Field::Long))
+            }
+            (VectorData::Int(v), None) => {
+                sum_valid(v, validity, None, i64::checked_add).map(|s| s.map(Field::Long))
+            }
+            (VectorData::Long(v), Some(Field::Long(s))) => {
+                sum_valid(v, validity, Some(*s), i64::checked_add).map(|s| s.map(Field::Long))
+            }
+            (VectorData::Long(v), None) => {
+                sum_valid(v, validity, None, i64::checked_add).map(|s| s.map(Field::Long))
+            }
+            (VectorData::Float(v), Some(Field::Float(s))) => {
+                sum_valid(v, validity, Some(*s), |l, r| Some(l + r)).map(|s| s.map(Field::Float))
             }
+            (VectorData::Float(v), None) => {
+                sum_valid(v, validity, None, |l, r| Some(l + r)).map(|s| s.map(Field::Float))
+            }
+            (VectorData::Double(v), Some(Field::Double(s))) => {
+                sum_valid(v, validity, Some(*s), |l, r| Some(l + r)).map(|s| s.map(Field::Double))
+            }
+            (VectorData::Double(v), None) => {
+                sum_valid(v, validity, None, |l, r| Some(l + r)).map(|s| s.map(Field::Double))
+            }
+            _ => None,
+        };
+
+        // the values are added one at a time when there is no kernel or the sum overflows so the error has the values
+        let Some(sum) = sum else {
+            for i in 0..vector.len() {
+                self.accumulate(&vector.get(i))?;
+            }
+            return Ok(());
+        };
+
+        if sum.is_some() {
+            self.sum = sum;
         }
         Ok(())
     }
@@ -351,6 +451,31 @@ impl Accumulator for SumAccumulator {
     }
 }
 
+// adds the values that are not null to the sum, returns none if the sum overflows and none inside if there is still nothing to add
+// integers are summed as bigints so the sum of many ints does not overflow
+fn widen_integer(field: &Field) -> Field {
+    match coercion::cast_numeric(field, DataType::Long) {
+        Some(long) if DataType::of_field(field).is_integer() => long,
+        _ => field.clone(),
+    }
+}
+
+fn sum_valid<T: Copy, S: Copy + From<T>>(
+    values: &[T],
+    validity: &Bitmap,
+    sum: Option<S>,
+    add: fn(S, S) -> Option<S>,
+) -> Option<Option<S>> {
+    values
+        .iter()
+        .zip(validity.iter())
+        .filter(|(_, valid)| *valid)
+        .try_fold(sum, |sum, (value, _)| match sum {
+            Some(sum) => add(S::from(*value), sum).map(Some),
+            None => Some(Some(S::from(*value))),
+        })
+}
+
 struct CountAccumulator {
     count: i32,
 }
@@ -370,6 +495,11 @@ impl Accumulator for CountAccumulator {
         Ok(())
     }
 
+    fn accumulate_vector(&mut self, vector: &Vector) -> Result<(), Error> {
+        self.count += vector.validity().count_set() as i32;
+        Ok(())
+    }
+
     fn aggregate(&self) -> Result<Field, Error> {
         Ok(Field::Int(self.count))
     }
@@ -394,17 +524,18 @@ impl Accumulator for AvgAccumulator {
         if *field == Field::Null {
             return Ok(());
         }
+        let field = widen_integer(field);
         match self.sum {
             Some(ref sum) => {
                 let x = ExprEvaluator::evaluate_binary_op(
-                    field,
+                    &field,
                     &sqlparser::ast::BinaryOperator::Plus,
                     sum,
                 )?;
                 self.sum = Some(x);
             }
             None => {
-                self.sum = Some(field.clone());
+                self.sum = Some(field);
             }
         }
         self.count += 1;
@@ -0,0 +1,188 @@
+use std::collections::HashSet;
+
+use crate::executor::expression::PhysicalExpr;
+use crate::executor::Executor;
+use crate::planner::binder::{common_type, infer_type};
+use crate::planner::{OutputSchema, PlanNode, SubqueryKind};
+use crate::types::data_type::DataType;
+use crate::types::error::Error;
+use crate::types::field::Field;
+use crate::types::vector::Vector;
+use crate::types::{coercion, Chunk, KeyValue};
+
+use super::work_table::WorkTable;
+use super::{collect_chunks, ExecutorBuilder};
+
+enum ApplyKind {
+    Scalar,
+    Exists,
+    // the values of both sides are compared as the type they can both be used as
+    In {
+        expr: PhysicalExpr,
+        data_type: DataType,
+    },
+}
+
+// the rows of a subquery reduced to what the expression needs from them
+enum SubqueryResult {
+    Value(Field),
+    Exists(bool),
+    Values {
+        values: HashSet<KeyValue>,
+        has_null: bool,
+    },
+}
+
+// adds the result of a subquery to every row of the child
+pub struct Apply {
+    output_schema: OutputSchema,
+    kind: ApplyKind,
+    child: Box<dyn Executor>,
+    // executors can only be read once so the subquery is built again from its plan every time it runs
+    subquery_plan: PlanNode,
+    builder: ExecutorBuilder,
+    // the row a correlated subquery reads, none when the subquery does not depend on the row
+    outer_row: Option<WorkTable>,
+    // the result of an uncorrelated subquery once it has run
+    result: Option<SubqueryResult>,
+}
+
+impl Apply {
+    pub fn new(
+        child: Box<dyn Executor>,
+        kind: SubqueryKind,
+        subquery_plan: PlanNode,
+        builder: ExecutorBuilder,
+        outer_row: Option<WorkTable>,
+        output_schema: OutputSchema,
+    ) -> Result<Box<Apply>, Error> {
+        let child_schema = child.get_output_schema();
+
+        let kind = match kind {
+            SubqueryKind::Scalar => ApplyKind::Scalar,
+            SubqueryKind::Exists => ApplyKind::Exists,
+            SubqueryKind::In(expr) => {
+                let (data_type, _) = infer_type(&expr, &child_schema);
+                let subquery_type = subquery_plan.output_schema.columns[0].data_type;
+                ApplyKind::In {
+                    expr: PhysicalExpr::compile(&expr, &child_schema)?,
+                    data_type: common_type(data_type, subquery_type).unwrap_or(data_type),
+                }
+            }
+        };
+
+        Ok(Box::new(Apply {
+            output_schema,
+            kind,
+            child,
+            subquery_plan,
+            builder,
+            outer_row,
+            result: None,
+        }))
+    }
+
+    fn run_subquery(&self) -> Result<SubqueryResult, Error> {
+        let mut subquery = self.builder.clone().build(self.subquery_plan.clone())?;
+
+        match &self.kind {
+            // one row is enough to know that there are rows
+            ApplyKind::Exists => Ok(SubqueryResult::Exists(!subquery.next_chunk()?.is_empty())),
+            ApplyKind::Scalar => {
+                let rows = collect_chunks(subquery.as_mut())?;
+                match rows.size() {
+                    0 => Ok(SubqueryResult::Value(Field::Null)),
+                    1 => Ok(SubqueryResult::Value(rows.column(0).get(0))),
+                    _ => Err(Error::Execution(
+                        "More than one row returned by a subquery used as an expression"
+                            .to_string(),
+                    )),
+                }
+            }
+            ApplyKind::In { data_type, .. } => {
+                let rows = collect_chunks(subquery.as_mut())?;
+                let mut values = HashSet::new();
+                let mut has_null = false;
+                for i in 0..rows.size() {
+                    match rows.column(0).get(i) {
+                        Field::Null => has_null = true,
+                        value => {
+                            values.insert(Self::key(&value, *data_type));
+                        }
+                    }
+                }
+                Ok(SubqueryResult::Values { values, has_null })
+            }
+        }
+    }
+
+    // the value of the expression for a row where the IN expression evaluated to the value
+    fn evaluate(&self, result: &SubqueryResult, value: Field) -> Field {
+        match (result, &self.kind) {
+            (SubqueryResult::Value(value), _) => value.clone(),
+            (SubqueryResult::Exists(exists), _) => Field::Bool(*exists),
+            (SubqueryResult::Values { values, has_null }, ApplyKind::In { data_type, .. }) => {
+                // nothing is in a subquery without rows, otherwise a value that is not found is unknown when a null is involved
+                if values.is_empty() && !has_null {
+                    Field::Bool(false)
+                } else if value == Field::Null {
+                    Field::Null
+                } else if values.contains(&Self::key(&value, *data_type)) {
+                    Field::Bool(true)
+                } else if *has_null {
+                    Field::Null
+                } else {
+                    Field::Bool(false)
+                }
+            }
+            (SubqueryResult::Values { .. }, _) => unreachable!(),
+        }
+    }
+
+    fn key(value: &Field, data_type: DataType) -> KeyValue {
+        KeyValue::from(&coercion::coerce(value, data_type).unwrap_or(value.clone()))
+    }
+}
+
+impl Executor for Apply {
+    fn next_chunk(&mut self) -> Result<Chunk, Error> {
+        let chunk = self.child.next_chunk()?;
+
+        if chunk.is_empty() {
+            return Ok(chunk);
+        }
+
+        let in_values = match &self.kind {
+            ApplyKind::In { expr, .. } => Some(expr.evaluate(&chunk)?),
+            _ => None,
+        };
+        let in_value = |row: usize| in_values.as_ref().map_or(Field::Null, |v| v.get(row));
+
+        let mut values = Vec::with_capacity(chunk.size());
+        match &self.outer_row {
+            Some(outer_row) => {
+                for row in 0..chunk.size() {
+                    *outer_row.borrow_mut() = chunk.slice(row, row + 1);
+                    let result = self.run_subquery()?;
+                    values.push(self.evaluate(&result, in_value(row)));
+                }
+            }
+            None => {
+                if self.result.is_none() {
+                    self.result = Some(self.run_subquery()?);
+                }
+                let result = self.result.as_ref().unwrap();
+                for row in 0..chunk.size() {
+                    values.push(self.evaluate(result, in_value(row)));
+                }
+            }
+        }
+
+        let size = chunk.size();
+        Ok(chunk.concat_columns(Chunk::from_columns(vec![Vector::from_fields(values)], size)))
+    }
+
+    fn get_output_schema(&self) -> OutputSchema {
+        self.output_schema.clone()
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+
+use crate::executor::Executor;
+use crate::planner::{OutputSchema, PlanNode};
+use crate::types::error::Error;
+use crate::types::Chunk;
+
+use super::work_table::WorkTable;
+use super::{
+    coerce_chunk, collect_chunks, row_key, Buffer, ExecutorBuilder, RowKey, VECTOR_SIZE_THRESHOLD,
+};
+
+// fills the work table with every row of the common table expression before the child reads it
+pub struct MaterializedCte {
+    output_schema: OutputSchema,
+    // taken once the work table is filled
+    cte: Option<Box<dyn Executor>>,
+    work_table: WorkTable,
+    child: Box<dyn Executor>,
+}
+
+impl MaterializedCte {
+    pub fn new(
+        cte: Box<dyn Executor>,
+        work_table: WorkTable,
+        child: Box<dyn Executor>,
+        output_schema: OutputSchema,
+    ) -> Result<Box<MaterializedCte>, Error> {
+        Ok(Box::new(MaterializedCte {
+            output_schema,
+            cte: Some(cte),
+            work_table,
+            child,
+        }))
+    }
+}
+
+impl Executor for MaterializedCte {
+    fn next_chunk(&mut self) -> Result<Chunk, Error> {
+        if let Some(mut cte) = self.cte.take() {
+            let rows = collect_chunks(cte.as_mut())?;
+            *self.work_table.borrow_mut() = rows;
+        }
+
+        self.child.next_chunk()
+    }
+
+    fn get_output_schema(&self) -> OutputSchema {
+        self.output_schema.clone()
+    }
+}
+
+// returns the rows of the anchor and then runs the recursive term on the rows returned by the previous iteration until it returns no new rows
+pub struct RecursiveCte {
+    output_schema: OutputSchema,
+    all: bool,
+    // taken once all of its rows are read
+    anchor: Option<Box<dyn Executor>>,
+    // executors can only be read once so the recursive term is built again from its plan for every iteration
+    recursive_plan: PlanNode,
+    builder: ExecutorBuilder,
+    recursive: Option<Box<dyn Executor>>,
+
+    // the rows of the previous iteration that the recursive term reads
+    work_table: WorkTable,
+    // the rows of the current iteration
+    next_work_table: Chunk,
+    // the rows that are already in the output when duplicates are removed
+    seen: HashSet<RowKey>,
+    buffer: Buffer,
+}
+
+impl RecursiveCte {
+    pub fn new(
+        all: bool,
+        anchor: Box<dyn Executor>,
+        recursive_plan: PlanNode,
+        builder: ExecutorBuilder,
+        work_table: WorkTable,
+        output_schema: OutputSchema,
+    ) -> Result<Box<RecursiveCte>, Error> {
+        Ok(Box::new(RecursiveCte {
+            output_schema,
+            all,
+            anchor: Some(anchor),
+            recursive_plan,
+            builder,
+            recursive: None,
+            work_table,
+            next_work_table: Chunk::new(),
+            seen: HashSet::new(),
+            buffer: Buffer::new(),
+        }))
+    }
+
+    // the next chunk of the anchor or the current iteration, starting the next iteration when the current one is done
+    fn next_input_chunk(&mut self) -> Result<Chunk, Error> {
+        loop {
+            if let Some(anchor) = self.anchor.as_mut() {
+                let chunk = anchor.next_chunk()?;
+                if !chunk.is_empty() {
+                    let input_schema = anchor.get_output_schema();
+                    return Ok(coerce_chunk(chunk, &input_schema, &self.output_schema));
+                }
+                self.anchor = None;
+            }
+
+            if let Some(recursive) = self.recursive.as_mut() {
+                let chunk = recursive.next_chunk()?;
+                if !chunk.is_empty() {
+                    let input_schema = recursive.get_output_schema();
+                    return Ok(coerce_chunk(chunk, &input_schema, &self.output_schema));
+                }
+                self.recursive = None;
+            }
+
+            if self.next_work_table.is_empty() {
+                return Ok(Chunk::new());
+            }
+
+            *self.work_table.borrow_mut() = std::mem::take(&mut self.next_work_table);
+            self.recursive = Some(self.builder.clone().build(self.recursive_plan.clone())?);
+        }
+    }
+}
+
+impl Executor for RecursiveCte {
+    fn next_chunk(&mut self) -> Result<Chunk, Error> {
+        while self.buffer.size() < VECTOR_SIZE_THRESHOLD {
+            let next_chunk = self.next_input_chunk()?;
+
+            if next_chunk.is_empty() {
+                break;
+            }
+
+            // without ALL only rows that were never returned before are kept, so a cycle stops the recursion
+            let next_chunk = if self.all {
+                next_chunk
+            } else {
+                let mask = (0..next_chunk.size())
+                    .map(|row| self.seen.insert(row_key(next_chunk.columns(), row)))
+                    .collect::<Vec<bool>>();
+                next_chunk.filter(&mask)
+            };
+
+            self.next_work_table.append(&next_chunk);
+            self.buffer.add_chunk(&next_chunk);
+        }
+
+        Ok(self.buffer.get_sized_chunk(VECTOR_SIZE_THRESHOLD))
+    }
+
+    fn get_output_schema(&self) -> OutputSchema {
+        self.output_schema.clone()
+    }
+}
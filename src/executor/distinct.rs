@@ -0,0 +1,79 @@
+use std::collections::HashSet;
+
+use sqlparser::ast::Expr;
+
+use crate::executor::expression::PhysicalExpr;
+use crate::executor::Executor;
+use crate::planner::OutputSchema;
+use crate::types::error::Error;
+use crate::types::vector::Vector;
+use crate::types::Chunk;
+
+use super::{row_key, Buffer, RowKey, VECTOR_SIZE_THRESHOLD};
+
+// keeps the first row of every distinct key, the rows stay in the order of the child so a sort below still holds
+pub struct Distinct {
+    output_schema: OutputSchema,
+    // the expressions the rows are compared on, every column when there are none
+    on: Vec<PhysicalExpr>,
+    child: Box<dyn Executor>,
+
+    seen: HashSet<RowKey>,
+    buffer: Buffer,
+}
+
+impl Distinct {
+    pub fn new(
+        child: Box<dyn Executor>,
+        on: Vec<Expr>,
+        output_schema: OutputSchema,
+    ) -> Result<Box<Distinct>, Error> {
+        Ok(Box::new(Distinct {
+            on: on
+                .iter()
+                .map(|expr| PhysicalExpr::compile(expr, &output_schema))
+                .collect::<Result<Vec<PhysicalExpr>, Error>>()?,
+            child,
+            output_schema,
+            seen: HashSet::new(),
+            buffer: Buffer::new(),
+        }))
+    }
+
+    // marks the rows whose key has not been seen in this or an earlier chunk
+    fn first_rows(&mut self, chunk: &Chunk) -> Result<Vec<bool>, Error> {
+        let key_vectors = if self.on.is_empty() {
+            chunk.columns().to_vec()
+        } else {
+            self.on
+                .iter()
+                .map(|expr| expr.evaluate(chunk))
+                .collect::<Result<Vec<Vector>, Error>>()?
+        };
+
+        Ok((0..chunk.size())
+            .map(|row| self.seen.insert(row_key(&key_vectors, row)))
+            .collect())
+    }
+}
+
+impl Executor for Distinct {
+    fn next_chunk(&mut self) -> Result<Chunk, Error> {
+        while self.buffer.size() < VECTOR_SIZE_THRESHOLD {
+            let next_chunk = self.child.next_chunk()?;
+
+            if next_chunk.is_empty() {
+                break;
+            }
+
+            let mask = self.first_rows(&next_chunk)?;
+            self.buffer.add_chunk(&next_chunk.filter(&mask));
+        }
+
+        Ok(self.buffer.get_sized_chunk(VECTOR_SIZE_THRESHOLD))
+    }
+
+    fn get_output_schema(&self) -> OutputSchema {
+        self.output_schema.clone()
+    }
+}
@@ -1,11 +1,902 @@
-use parquet::record::Field;
-use sqlparser::ast::{BinaryOperator, Expr, Ident, UnaryOperator};
+use std::{cmp::Ordering, collections::HashMap};
+
+use regex::Regex;
+use sqlparser::ast::{
+    BinaryOperator, Expr, FunctionArg, FunctionArgExpr, SelectItem, UnaryOperator,
+};
 
 use crate::{
-    planner::OutputSchema,
-    types::{error::Error, Row},
+    planner::{binder::infer_type, OutputSchema},
+    types::{
+        coercion,
+        data_type::DataType,
+        datetime::{self, Interval},
+        decimal,
+        error::Error,
+        field::Field,
+        vector::{Bitmap, Vector, VectorData},
+        Chunk,
+    },
+};
+
+use super::{
+    function::ScalarFunction,
+    pattern::{self, PatternKind},
 };
 
+// an expression compiled against the schema of its input, columns are referenced by index and literals are already parsed
+#[derive(Debug, Clone)]
+pub enum PhysicalExpr {
+    Column(usize),
+    Literal(Field),
+    UnaryOp {
+        op: UnaryOperator,
+        expr: Box<PhysicalExpr>,
+    },
+    BinaryOp {
+        left: Box<PhysicalExpr>,
+        op: BinaryOperator,
+        right: Box<PhysicalExpr>,
+    },
+    // the results of conditional expressions are converted to the type of the expression
+    Case {
+        operand: Option<Box<PhysicalExpr>>,
+        conditions: Vec<PhysicalExpr>,
+        results: Vec<PhysicalExpr>,
+        else_result: Option<Box<PhysicalExpr>>,
+        data_type: DataType,
+    },
+    Coalesce {
+        args: Vec<PhysicalExpr>,
+        data_type: DataType,
+    },
+    NullIf {
+        left: Box<PhysicalExpr>,
+        right: Box<PhysicalExpr>,
+    },
+    InList {
+        expr: Box<PhysicalExpr>,
+        list: Vec<PhysicalExpr>,
+        negated: bool,
+    },
+    Between {
+        expr: Box<PhysicalExpr>,
+        low: Box<PhysicalExpr>,
+        high: Box<PhysicalExpr>,
+        negated: bool,
+    },
+    IsNull {
+        expr: Box<PhysicalExpr>,
+        negated: bool,
+    },
+    // IS TRUE, IS FALSE and IS UNKNOWN, unknown is the truth value of null
+    IsTruth {
+        expr: Box<PhysicalExpr>,
+        truth: Option<bool>,
+        negated: bool,
+    },
+    IsDistinctFrom {
+        left: Box<PhysicalExpr>,
+        right: Box<PhysicalExpr>,
+        negated: bool,
+    },
+    // LIKE, ILIKE, SIMILAR TO and the regex operators, a constant pattern is compiled together with the expression
+    PatternMatch {
+        expr: Box<PhysicalExpr>,
+        pattern: Box<PhysicalExpr>,
+        kind: PatternKind,
+        regex: Option<Regex>,
+        negated: bool,
+    },
+    RegexpReplace {
+        expr: Box<PhysicalExpr>,
+        pattern: Box<PhysicalExpr>,
+        replacement: Box<PhysicalExpr>,
+        kind: PatternKind,
+        regex: Option<Regex>,
+        global: bool,
+    },
+    // a function of the scalar function registry, the result is converted to the type of the expression
+    Function {
+        function: ScalarFunction,
+        args: Vec<PhysicalExpr>,
+        data_type: DataType,
+    },
+    // TRY_CAST returns null for values that cannot be cast instead of an error
+    Cast {
+        expr: Box<PhysicalExpr>,
+        data_type: sqlparser::ast::DataType,
+        try_cast: bool,
+    },
+}
+
+// the patterns of the rows of a chunk
+enum Patterns<'a> {
+    Constant(&'a Regex),
+    // the index of the pattern of each row in the distinct patterns, None where the pattern is null
+    PerRow {
+        regexes: Vec<Regex>,
+        indexes: Vec<Option<usize>>,
+    },
+}
+
+impl Patterns<'_> {
+    fn get(&self, i: usize) -> Option<&Regex> {
+        match self {
+            Patterns::Constant(regex) => Some(regex),
+            Patterns::PerRow { regexes, indexes } => indexes[i].map(|index| &regexes[index]),
+        }
+    }
+}
+
+impl PhysicalExpr {
+    pub fn compile(expr: &Expr, schema: &OutputSchema) -> Result<PhysicalExpr, Error> {
+        match expr {
+            Expr::Nested(expr) => Self::compile(expr, schema),
+            Expr::UnaryOp { op, expr } => Ok(PhysicalExpr::UnaryOp {
+                op: *op,
+                expr: Box::new(Self::compile(expr, schema)?),
+            }),
+            Expr::BinaryOp {
+                left,
+                op:
+                    op @ (BinaryOperator::PGRegexMatch
+                    | BinaryOperator::PGRegexIMatch
+                    | BinaryOperator::PGRegexNotMatch
+                    | BinaryOperator::PGRegexNotIMatch),
+                right,
+            } => Self::pattern_match(
+                Self::compile(left, schema)?,
+                Self::compile(right, schema)?,
+                PatternKind::Regex {
+                    case_insensitive: matches!(
+                        op,
+                        BinaryOperator::PGRegexIMatch | BinaryOperator::PGRegexNotIMatch
+                    ),
+                },
+                matches!(
+                    op,
+                    BinaryOperator::PGRegexNotMatch | BinaryOperator::PGRegexNotIMatch
+                ),
+            ),
+            Expr::BinaryOp {
+                left,
+                op:
+                    op @ (BinaryOperator::Eq
+                    | BinaryOperator::NotEq
+                    | BinaryOperator::Lt
+                    | BinaryOperator::LtEq
+                    | BinaryOperator::Gt
+                    | BinaryOperator::GtEq),
+                right,
+            } => {
+                // string literals compared with other types are converted once instead of for every row
+                let (left_type, right_type) =
+                    (infer_type(left, schema).0, infer_type(right, schema).0);
+                Ok(PhysicalExpr::BinaryOp {
+                    left: Box::new(Self::compile(left, schema)?.convert_literal(right_type)),
+                    op: op.clone(),
+                    right: Box::new(Self::compile(right, schema)?.convert_literal(left_type)),
+                })
+            }
+            Expr::BinaryOp { left, op, right } => Ok(PhysicalExpr::BinaryOp {
+                left: Box::new(Self::compile(left, schema)?),
+                op: op.clone(),
+                right: Box::new(Self::compile(right, schema)?),
+            }),
+            Expr::Identifier(ident) => Ok(PhysicalExpr::Column(schema.resolve(&ident.value)?)),
+            Expr::CompoundIdentifier(idents) => Ok(PhysicalExpr::Column(
+                schema.resolve(
+                    &idents
+                        .iter()
+                        .map(|i| i.value.clone())
+                        .collect::<Vec<String>>()
+                        .join("."),
+                )?,
+            )),
+            Expr::Value(value) => Ok(PhysicalExpr::Literal(ExprEvaluator::evaluate_value(value)?)),
+            Expr::TypedString { data_type, value } => Ok(PhysicalExpr::Literal(Caster::cast(
+                &Field::Str(value.clone()),
+                data_type,
+            )?)),
+            // the value and the unit after it are read together, INTERVAL '1' DAY is the same as INTERVAL '1 day'
+            Expr::Interval(interval) => {
+                let value = match ExprEvaluator::evaluate_scalar(&interval.value)? {
+                    Field::Null => return Ok(PhysicalExpr::Literal(Field::Null)),
+                    value => Caster::to_string(&value).unwrap_or_default(),
+                };
+                let value = match &interval.leading_field {
+                    Some(field) => format!("{} {}", value, field),
+                    None => value,
+                };
+                match Interval::parse(&value) {
+                    Some(interval) => Ok(PhysicalExpr::Literal(Field::Interval(interval))),
+                    None => Err(Error::Expression(format!("Invalid interval: {}", value))),
+                }
+            }
+            Expr::IsNull(inner) | Expr::IsNotNull(inner) => Ok(PhysicalExpr::IsNull {
+                expr: Box::new(Self::compile(inner, schema)?),
+                negated: matches!(expr, Expr::IsNotNull(_)),
+            }),
+            Expr::IsTrue(inner) | Expr::IsNotTrue(inner) => Ok(PhysicalExpr::IsTruth {
+                expr: Box::new(Self::compile(inner, schema)?),
+                truth: Some(true),
+                negated: matches!(expr, Expr::IsNotTrue(_)),
+            }),
+            Expr::IsFalse(inner) | Expr::IsNotFalse(inner) => Ok(PhysicalExpr::IsTruth {
+                expr: Box::new(Self::compile(inner, schema)?),
+                truth: Some(false),
+                negated: matches!(expr, Expr::IsNotFalse(_)),
+            }),
+            Expr::IsUnknown(inner) | Expr::IsNotUnknown(inner) => Ok(PhysicalExpr::IsTruth {
+                expr: Box::new(Self::compile(inner, schema)?),
+                truth: None,
+                negated: matches!(expr, Expr::IsNotUnknown(_)),
+            }),
+            Expr::IsDistinctFrom(left, right) | Expr::IsNotDistinctFrom(left, right) => {
+                Ok(PhysicalExpr::IsDistinctFrom {
+                    left: Box::new(Self::compile(left, schema)?),
+                    right: Box::new(Self::compile(right, schema)?),
+                    negated: matches!(expr, Expr::IsNotDistinctFrom(..)),
+                })
+            }
+            Expr::Cast {
+                expr: inner,
+                data_type,
+            }
+            | Expr::TryCast {
+                expr: inner,
+                data_type,
+            }
+            | Expr::SafeCast {
+                expr: inner,
+                data_type,
+            } => Ok(PhysicalExpr::Cast {
+                expr: Box::new(Self::compile(inner, schema)?),
+                data_type: data_type.clone(),
+                try_cast: !matches!(expr, Expr::Cast { .. }),
+            }),
+            Expr::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => Ok(PhysicalExpr::Case {
+                operand: match operand {
+                    Some(operand) => Some(Box::new(Self::compile(operand, schema)?)),
+                    None => None,
+                },
+                conditions: Self::compile_all(conditions, schema)?,
+                results: Self::compile_all(results, schema)?,
+                else_result: match else_result {
+                    Some(else_result) => Some(Box::new(Self::compile(else_result, schema)?)),
+                    None => None,
+                },
+                data_type: infer_type(expr, schema).0,
+            }),
+            Expr::InList {
+                expr: inner,
+                list,
+                negated,
+            } => Ok(PhysicalExpr::InList {
+                expr: Box::new(Self::compile(inner, schema)?),
+                list: Self::compile_all(list, schema)?,
+                negated: *negated,
+            }),
+            Expr::Between {
+                expr: inner,
+                negated,
+                low,
+                high,
+            } => Ok(PhysicalExpr::Between {
+                expr: Box::new(Self::compile(inner, schema)?),
+                low: Box::new(Self::compile(low, schema)?),
+                high: Box::new(Self::compile(high, schema)?),
+                negated: *negated,
+            }),
+            Expr::Like {
+                negated,
+                expr: inner,
+                pattern,
+                escape_char,
+            }
+            | Expr::ILike {
+                negated,
+                expr: inner,
+                pattern,
+                escape_char,
+            } => Self::pattern_match(
+                Self::compile(inner, schema)?,
+                Self::compile(pattern, schema)?,
+                PatternKind::Like {
+                    case_insensitive: matches!(expr, Expr::ILike { .. }),
+                    escape_char: *escape_char,
+                },
+                *negated,
+            ),
+            Expr::SimilarTo {
+                negated,
+                expr: inner,
+                pattern,
+                escape_char,
+            } => Self::pattern_match(
+                Self::compile(inner, schema)?,
+                Self::compile(pattern, schema)?,
+                PatternKind::SimilarTo {
+                    escape_char: *escape_char,
+                },
+                *negated,
+            ),
+            Expr::Function(function) => {
+                let mut args = Vec::new();
+                for arg in &function.args {
+                    match arg {
+                        FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) => {
+                            args.push(Self::compile(expr, schema)?)
+                        }
+                        _ => {
+                            return Err(Error::Expression(format!(
+                                "Unsupported argument {} for function {}",
+                                arg, function.name
+                            )))
+                        }
+                    };
+                }
+
+                match function.name.to_string().to_lowercase().as_str() {
+                    "coalesce" => Ok(PhysicalExpr::Coalesce {
+                        args,
+                        data_type: infer_type(expr, schema).0,
+                    }),
+                    "nullif" if args.len() == 2 => {
+                        let right = args.pop().unwrap();
+                        let left = args.pop().unwrap();
+                        Ok(PhysicalExpr::NullIf {
+                            left: Box::new(left),
+                            right: Box::new(right),
+                        })
+                    }
+                    "regexp_matches" if matches!(args.len(), 2 | 3) => {
+                        let (case_insensitive, _) = Self::regex_flags(&mut args, 2, function)?;
+                        let pattern = args.pop().unwrap();
+                        let expr = args.pop().unwrap();
+                        Self::pattern_match(
+                            expr,
+                            pattern,
+                            PatternKind::Regex { case_insensitive },
+                            false,
+                        )
+                    }
+                    "regexp_replace" if matches!(args.len(), 3 | 4) => {
+                        let (case_insensitive, global) = Self::regex_flags(&mut args, 3, function)?;
+                        let replacement = args.pop().unwrap();
+                        let pattern = args.pop().unwrap();
+                        let expr = args.pop().unwrap();
+                        let kind = PatternKind::Regex { case_insensitive };
+                        Ok(PhysicalExpr::RegexpReplace {
+                            regex: Self::constant_pattern(&pattern, &kind)?,
+                            expr: Box::new(expr),
+                            pattern: Box::new(pattern),
+                            replacement: Box::new(replacement),
+                            kind,
+                            global,
+                        })
+                    }
+                    name => match ScalarFunction::from_name(name) {
+                        // functions without arguments such as now are evaluated once for the query
+                        Some(function) if args.is_empty() => {
+                            Ok(PhysicalExpr::Literal(function.evaluate(&[])?))
+                        }
+                        Some(function) => Ok(PhysicalExpr::Function {
+                            function,
+                            args,
+                            data_type: infer_type(expr, schema).0,
+                        }),
+                        None => Err(Error::Expression(format!(
+                            "Unsupported function: {}",
+                            function.name
+                        ))),
+                    },
+                }
+            }
+            Expr::Ceil { .. }
+            | Expr::Floor { .. }
+            | Expr::Trim { .. }
+            | Expr::Substring { .. }
+            | Expr::Extract { .. } => match ScalarFunction::from_expr(expr) {
+                Some((function, args)) => Ok(PhysicalExpr::Function {
+                    function,
+                    args: Self::compile_all(&args, schema)?,
+                    data_type: infer_type(expr, schema).0,
+                }),
+                None => Err(Error::Expression(format!(
+                    "Unsupported expression: {}",
+                    expr
+                ))),
+            },
+            _ => Err(Error::Expression(format!(
+                "Unsupported expression: {}",
+                expr
+            ))),
+        }
+    }
+
+    fn pattern_match(
+        expr: PhysicalExpr,
+        pattern: PhysicalExpr,
+        kind: PatternKind,
+        negated: bool,
+    ) -> Result<PhysicalExpr, Error> {
+        Ok(PhysicalExpr::PatternMatch {
+            regex: Self::constant_pattern(&pattern, &kind)?,
+            expr: Box::new(expr),
+            pattern: Box::new(pattern),
+            kind,
+            negated,
+        })
+    }
+
+    // literal patterns are compiled once instead of for every row
+    fn constant_pattern(
+        pattern: &PhysicalExpr,
+        kind: &PatternKind,
+    ) -> Result<Option<Regex>, Error> {
+        match pattern {
+            PhysicalExpr::Literal(Field::Str(pattern)) => Ok(Some(kind.compile(pattern)?)),
+            _ => Ok(None),
+        }
+    }
+
+    // removes the optional flags argument that follows the other arguments of a regexp function
+    fn regex_flags(
+        args: &mut Vec<PhysicalExpr>,
+        count: usize,
+        function: &sqlparser::ast::Function,
+    ) -> Result<(bool, bool), Error> {
+        if args.len() == count {
+            return Ok((false, false));
+        }
+        match args.pop() {
+            Some(PhysicalExpr::Literal(Field::Str(flags))) => pattern::parse_regex_flags(&flags),
+            _ => Err(Error::Expression(format!(
+                "The flags of {} must be a constant string",
+                function.name
+            ))),
+        }
+    }
+
+    fn compile_all(exprs: &[Expr], schema: &OutputSchema) -> Result<Vec<PhysicalExpr>, Error> {
+        exprs
+            .iter()
+            .map(|expr| Self::compile(expr, schema))
+            .collect()
+    }
+
+    // compiles the select items, wildcards are expanded to the columns that are not hidden
+    pub fn compile_select_items(
+        select: &[SelectItem],
+        schema: &OutputSchema,
+    ) -> Result<Vec<PhysicalExpr>, Error> {
+        let mut exprs = Vec::new();
+        for item in select {
+            match item {
+                SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => {
+                    exprs.push(Self::compile(expr, schema)?);
+                }
+                SelectItem::Wildcard(_) => {
+                    for (i, column) in schema.columns.iter().enumerate() {
+                        if !column.hidden {
+                            exprs.push(PhysicalExpr::Column(i));
+                        }
+                    }
+                }
+                _ => {
+                    return Err(Error::Execution(format!("{} not supported", item)));
+                }
+            }
+        }
+        Ok(exprs)
+    }
+
+    // evaluates the expression for all the rows of the chunk at once
+    pub fn evaluate(&self, chunk: &Chunk) -> Result<Vector, Error> {
+        match self {
+            PhysicalExpr::Column(index) => Ok(chunk.column(*index).clone()),
+            PhysicalExpr::Literal(field) => Ok(Vector::from_field(field, chunk.size())),
+            PhysicalExpr::UnaryOp { op, expr } => {
+                ExprEvaluator::evaluate_unary_op(op, &expr.evaluate(chunk)?)
+            }
+            PhysicalExpr::BinaryOp { left, op, right } => {
+                BinaryOpEvaluator::evaluate(&left.evaluate(chunk)?, op, &right.evaluate(chunk)?)
+            }
+            PhysicalExpr::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+                data_type,
+            } => {
+                let operand = match operand {
+                    Some(operand) => Some(operand.evaluate(chunk)?),
+                    None => None,
+                };
+
+                // every branch is only evaluated for the rows that did not match an earlier branch
+                let mut values = vec![Field::Null; chunk.size()];
+                let mut remaining: Vec<usize> = (0..chunk.size()).collect();
+                for (condition, result) in conditions.iter().zip(results.iter()) {
+                    if remaining.is_empty() {
+                        break;
+                    }
+
+                    let condition = condition.evaluate_rows(chunk, &remaining)?;
+                    let mask = match &operand {
+                        Some(operand) => ExprEvaluator::to_mask(&BinaryOpEvaluator::evaluate(
+                            &operand.take(&remaining),
+                            &BinaryOperator::Eq,
+                            &condition,
+                        )?),
+                        None => ExprEvaluator::to_mask(&condition),
+                    };
+                    let (matched, rest) = Self::partition(&remaining, &mask);
+
+                    Self::scatter(
+                        &mut values,
+                        &matched,
+                        &result.evaluate_rows(chunk, &matched)?,
+                    );
+                    remaining = rest;
+                }
+                if let Some(else_result) = else_result {
+                    Self::scatter(
+                        &mut values,
+                        &remaining,
+                        &else_result.evaluate_rows(chunk, &remaining)?,
+                    );
+                }
+
+                Ok(Self::coerce(values, *data_type))
+            }
+            PhysicalExpr::Coalesce { args, data_type } => {
+                // every argument is only evaluated for the rows where the earlier arguments are null
+                let mut values = vec![Field::Null; chunk.size()];
+                let mut remaining: Vec<usize> = (0..chunk.size()).collect();
+                for arg in args {
+                    if remaining.is_empty() {
+                        break;
+                    }
+
+                    let vector = arg.evaluate_rows(chunk, &remaining)?;
+                    let mask: Vec<bool> = vector.validity().iter().collect();
+                    let (matched, rest) = Self::partition(&remaining, &mask);
+
+                    Self::scatter(&mut values, &matched, &vector.filter(&mask));
+                    remaining = rest;
+                }
+
+                Ok(Self::coerce(values, *data_type))
+            }
+            PhysicalExpr::NullIf { left, right } => {
+                let left = left.evaluate(chunk)?;
+                let right = right.evaluate(chunk)?;
+                let equal = ExprEvaluator::to_mask(&BinaryOpEvaluator::evaluate(
+                    &left,
+                    &BinaryOperator::Eq,
+                    &right,
+                )?);
+
+                // the values of the left side stay where they are and the equal ones become null
+                let not_equal: Bitmap = equal.iter().map(|equal| !equal).collect();
+                let validity = left.validity().and(&not_equal);
+                Ok(left.with_validity(validity))
+            }
+            PhysicalExpr::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                // the value is in the list if it equals any item, when it equals none and a comparison is null the result is null
+                let vector = expr.evaluate(chunk)?;
+                let mut res = Vector::new(
+                    VectorData::Bool(vec![false; chunk.size()]),
+                    Bitmap::new(chunk.size(), true),
+                );
+                for item in list {
+                    let equal = BinaryOpEvaluator::evaluate(
+                        &vector,
+                        &BinaryOperator::Eq,
+                        &item.evaluate(chunk)?,
+                    )?;
+                    res = BinaryOpEvaluator::evaluate(&res, &BinaryOperator::Or, &equal)?;
+                }
+
+                if *negated {
+                    ExprEvaluator::evaluate_unary_op(&UnaryOperator::Not, &res)
+                } else {
+                    Ok(res)
+                }
+            }
+            PhysicalExpr::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => {
+                let vector = expr.evaluate(chunk)?;
+                let above_low = BinaryOpEvaluator::evaluate(
+                    &vector,
+                    &BinaryOperator::GtEq,
+                    &low.evaluate(chunk)?,
+                )?;
+                let below_high = BinaryOpEvaluator::evaluate(
+                    &vector,
+                    &BinaryOperator::LtEq,
+                    &high.evaluate(chunk)?,
+                )?;
+                let res =
+                    BinaryOpEvaluator::evaluate(&above_low, &BinaryOperator::And, &below_high)?;
+
+                if *negated {
+                    ExprEvaluator::evaluate_unary_op(&UnaryOperator::Not, &res)
+                } else {
+                    Ok(res)
+                }
+            }
+            PhysicalExpr::IsNull { expr, negated } => {
+                let vector = expr.evaluate(chunk)?;
+                let values = vector
+                    .validity()
+                    .iter()
+                    .map(|valid| valid == *negated)
+                    .collect();
+                Ok(Vector::new(
+                    VectorData::Bool(values),
+                    Bitmap::new(chunk.size(), true),
+                ))
+            }
+            PhysicalExpr::IsTruth {
+                expr,
+                truth,
+                negated,
+            } => {
+                let (values, validity) = ExprEvaluator::to_truths(&expr.evaluate(chunk)?);
+                let values = values
+                    .iter()
+                    .zip(validity.iter())
+                    .map(|(value, valid)| (valid.then_some(*value) == *truth) != *negated)
+                    .collect();
+                Ok(Vector::new(
+                    VectorData::Bool(values),
+                    Bitmap::new(chunk.size(), true),
+                ))
+            }
+            PhysicalExpr::IsDistinctFrom {
+                left,
+                right,
+                negated,
+            } => {
+                let left = left.evaluate(chunk)?;
+                let right = right.evaluate(chunk)?;
+                let equal = ExprEvaluator::to_mask(&BinaryOpEvaluator::evaluate(
+                    &left,
+                    &BinaryOperator::Eq,
+                    &right,
+                )?);
+
+                // two nulls are not distinct from each other but are distinct from any value
+                let values = (0..chunk.size())
+                    .map(|i| {
+                        let distinct = match (left.is_valid(i), right.is_valid(i)) {
+                            (true, true) => !equal[i],
+                            (false, false) => false,
+                            _ => true,
+                        };
+                        distinct != *negated
+                    })
+                    .collect();
+                Ok(Vector::new(
+                    VectorData::Bool(values),
+                    Bitmap::new(chunk.size(), true),
+                ))
+            }
+            PhysicalExpr::PatternMatch {
+                expr,
+                pattern,
+                kind,
+                regex,
+                negated,
+            } => {
+                let vector = expr.evaluate(chunk)?;
+                let regexes = Self::compile_patterns(pattern, kind, regex, chunk)?;
+                let (values, validity): (Vec<bool>, Vec<bool>) = (0..chunk.size())
+                    .map(|i| match (vector.get_str(i), regexes.get(i)) {
+                        (Some(s), Some(regex)) => (regex.is_match(s) != *negated, true),
+                        _ => (false, false),
+                    })
+                    .unzip();
+                Ok(Vector::new(
+                    VectorData::Bool(values),
+                    validity.into_iter().collect(),
+                ))
+            }
+            PhysicalExpr::RegexpReplace {
+                expr,
+                pattern,
+                replacement,
+                kind,
+                regex,
+                global,
+            } => {
+                let vector = expr.evaluate(chunk)?;
+                let replacements = replacement.evaluate(chunk)?;
+                let regexes = Self::compile_patterns(pattern, kind, regex, chunk)?;
+                let (values, validity): (Vec<String>, Vec<bool>) = (0..chunk.size())
+                    .map(
+                        |i| match (vector.get_str(i), regexes.get(i), replacements.get_str(i)) {
+                            (Some(s), Some(regex), Some(replacement)) => {
+                                let replacement = pattern::translate_replacement(replacement);
+                                let replaced = if *global {
+                                    regex.replace_all(s, replacement.as_str())
+                                } else {
+                                    regex.replace(s, replacement.as_str())
+                                };
+                                (replaced.into_owned(), true)
+                            }
+                            _ => (String::new(), false),
+                        },
+                    )
+                    .unzip();
+                Ok(Vector::new(
+                    VectorData::Str(values),
+                    validity.into_iter().collect(),
+                ))
+            }
+            PhysicalExpr::Function {
+                function,
+                args,
+                data_type,
+            } => {
+                let vectors = args
+                    .iter()
+                    .map(|arg| arg.evaluate(chunk))
+                    .collect::<Result<Vec<Vector>, Error>>()?;
+                if let [arg] = vectors.as_slice() {
+                    if let Some(vector) = function.evaluate_vector(arg)? {
+                        return Ok(vector);
+                    }
+                }
+
+                let mut values = Vec::with_capacity(chunk.size());
+                for i in 0..chunk.size() {
+                    let row: Vec<Field> = vectors.iter().map(|vector| vector.get(i)).collect();
+                    values.push(function.evaluate(&row)?);
+                }
+                Ok(Self::coerce(values, *data_type))
+            }
+            PhysicalExpr::Cast {
+                expr,
+                data_type,
+                try_cast,
+            } => {
+                let vector = expr.evaluate(chunk)?;
+                if let Some(res) = Caster::cast_vector(&vector, data_type) {
+                    return Ok(res);
+                }
+
+                let mut values = Vec::with_capacity(vector.len());
+                for i in 0..vector.len() {
+                    let field = match Caster::cast(&vector.get(i), data_type) {
+                        Ok(field) => field,
+                        Err(_) if *try_cast => Field::Null,
+                        Err(e) => return Err(e),
+                    };
+                    values.push(field);
+                }
+                let target = DataType::from_sql(data_type).unwrap_or(DataType::Unknown);
+                Ok(Vector::from_fields_of_type(values, target))
+            }
+        }
+    }
+
+    fn convert_literal(self, data_type: DataType) -> PhysicalExpr {
+        match &self {
+            PhysicalExpr::Literal(field @ Field::Str(_)) if data_type != DataType::String => {
+                match coercion::coerce(field, data_type) {
+                    Some(field) => PhysicalExpr::Literal(field),
+                    None => self,
+                }
+            }
+            _ => self,
+        }
+    }
+
+    // the compiled patterns of the rows, a pattern that is the same for every row is compiled once for the query
+    fn compile_patterns<'a>(
+        pattern: &PhysicalExpr,
+        kind: &PatternKind,
+        regex: &'a Option<Regex>,
+        chunk: &Chunk,
+    ) -> Result<Patterns<'a>, Error> {
+        if let Some(regex) = regex {
+            return Ok(Patterns::Constant(regex));
+        }
+
+        // each distinct pattern of the chunk is compiled once and rows refer to it by index
+        let patterns = pattern.evaluate(chunk)?;
+        let mut compiled: HashMap<&str, usize> = HashMap::new();
+        let mut regexes = Vec::new();
+        let mut indexes = Vec::with_capacity(patterns.len());
+        for i in 0..patterns.len() {
+            match patterns.get_str(i) {
+                Some(pattern) => {
+                    let index = match compiled.get(pattern) {
+                        Some(index) => *index,
+                        None => {
+                            regexes.push(kind.compile(pattern)?);
+                            compiled.insert(pattern, regexes.len() - 1);
+                            regexes.len() - 1
+                        }
+                    };
+                    indexes.push(Some(index));
+                }
+                _ => indexes.push(None),
+            }
+        }
+        Ok(Patterns::PerRow { regexes, indexes })
+    }
+
+    // evaluates the expression only for the rows at the indexes
+    fn evaluate_rows(&self, chunk: &Chunk, rows: &[usize]) -> Result<Vector, Error> {
+        // the indexes are in order and unique so all the rows are the whole chunk
+        if rows.len() == chunk.size() {
+            return self.evaluate(chunk);
+        }
+        self.evaluate(&chunk.take(rows))
+    }
+
+    // splits the rows into the rows where the mask is true and the rest
+    fn partition(rows: &[usize], mask: &[bool]) -> (Vec<usize>, Vec<usize>) {
+        let mut matched = Vec::new();
+        let mut rest = Vec::new();
+        for (row, is_match) in rows.iter().zip(mask.iter()) {
+            if *is_match {
+                matched.push(*row);
+            } else {
+                rest.push(*row);
+            }
+        }
+        (matched, rest)
+    }
+
+    // places the values of the vector at the rows
+    fn scatter(values: &mut [Field], rows: &[usize], vector: &Vector) {
+        for (i, row) in rows.iter().enumerate() {
+            values[*row] = vector.get(i);
+        }
+    }
+
+    // converts numbers of narrower types to the type of the expression so the vector has a single type
+    fn coerce(values: Vec<Field>, data_type: DataType) -> Vector {
+        Vector::from_fields_of_type(
+            values
+                .into_iter()
+                .map(|field| {
+                    if DataType::of_field(&field) == data_type {
+                        return field;
+                    }
+                    coercion::coerce(&field, data_type).unwrap_or(field)
+                })
+                .collect(),
+            data_type,
+        )
+    }
+
+    // evaluates the expression and converts the values to booleans
+    pub fn evaluate_mask(&self, chunk: &Chunk) -> Result<Vec<bool>, Error> {
+        Ok(ExprEvaluator::to_mask(&self.evaluate(chunk)?))
+    }
+}
+
 pub struct ExprEvaluator;
 
 impl ExprEvaluator {
@@ -22,53 +913,96 @@ impl ExprEvaluator {
         }
     }
 
-    pub fn evaluate(expr: &Expr, row: &Row, columns: &OutputSchema) -> Result<Field, Error> {
-        match expr {
-            Expr::Nested(expr) => Self::evaluate(expr, row, columns),
-            Expr::UnaryOp { op, expr } => Self::evaluate_unary_op(op, expr, row, columns),
-            Expr::BinaryOp { left, op, right } => {
-                let left = Self::evaluate(left, row, columns)?;
-                let right = Self::evaluate(right, row, columns)?;
-
-                Self::evaluate_binary_op(&left, op, &right)
-            }
-            Expr::Identifier(ident) => Self::evaluate_identifier(ident, row, columns),
-            Expr::Value(value) => Self::evaluate_value(value),
-            Expr::CompoundIdentifier(idents) => Self::evaluate_identifier(
-                &Ident::new(
-                    idents
-                        .iter()
-                        .map(|i| i.value.clone())
-                        .collect::<Vec<String>>()
-                        .join("."),
-                ),
-                row,
-                columns,
-            ),
-            _ => Err(Error::Expression(format!(
-                "Unsupported expression: {}",
-                expr
-            ))),
+    // the truth value of a field, null is unknown
+    pub fn to_truth(field: &Field) -> Option<bool> {
+        match field {
+            Field::Null => None,
+            field => Some(Self::to_boolean(field)),
         }
     }
 
-    pub fn evaluate_identifier(
-        ident: &Ident,
-        row: &Row,
-        output_schema: &OutputSchema,
-    ) -> Result<Field, Error> {
-        assert!(output_schema.columns.len() == row.len());
-        let index = output_schema.resolve(&ident.value)?;
-        Ok(row[index].value.clone())
+    // converts every value of the vector with to_boolean, nulls are false so only true rows pass a filter
+    pub fn to_mask(vector: &Vector) -> Vec<bool> {
+        match vector.data() {
+            VectorData::Bool(values) => values
+                .iter()
+                .zip(vector.validity().iter())
+                .map(|(value, valid)| *value && valid)
+                .collect(),
+            _ => (0..vector.len())
+                .map(|i| Self::to_boolean(&vector.get(i)))
+                .collect(),
+        }
     }
 
-    pub fn evaluate_unary_op(
-        op: &UnaryOperator,
-        expr: &Expr,
-        row: &Row,
-        columns: &OutputSchema,
-    ) -> Result<Field, Error> {
-        let field = Self::evaluate(expr, row, columns)?;
+    // converts the values of the vector to booleans and keeps which of them are unknown
+    pub fn to_truths(vector: &Vector) -> (Vec<bool>, Bitmap) {
+        (Self::to_mask(vector), vector.validity().clone())
+    }
+
+    // evaluates an expression that does not reference any columns
+    pub fn evaluate_scalar(expr: &Expr) -> Result<Field, Error> {
+        let mut chunk = Chunk::new();
+        chunk.add_row(vec![]);
+        let expr = PhysicalExpr::compile(expr, &OutputSchema::new())?;
+        Ok(expr.evaluate(&chunk)?.get(0))
+    }
+
+    pub fn evaluate_unary_op(op: &UnaryOperator, vector: &Vector) -> Result<Vector, Error> {
+        if *op == UnaryOperator::Not {
+            let (values, validity) = Self::to_truths(vector);
+            let values = values.iter().map(|b| !b).collect();
+            return Ok(Vector::new(VectorData::Bool(values), validity));
+        }
+
+        // the kernels also run over the values of the null slots, the validity stays the same
+        let data = match (op, vector.data()) {
+            (
+                UnaryOperator::Plus,
+                data @ (VectorData::Int(_)
+                | VectorData::Long(_)
+                | VectorData::Float(_)
+                | VectorData::Double(_)),
+            ) => Some(data.clone()),
+            // the smallest integer has no negation so those vectors are evaluated one value at a time
+            (UnaryOperator::Minus, VectorData::Int(v)) => v
+                .iter()
+                .map(|x| x.checked_neg())
+                .collect::<Option<_>>()
+                .map(VectorData::Int),
+            (UnaryOperator::Minus, VectorData::Long(v)) => v
+                .iter()
+                .map(|x| x.checked_neg())
+                .collect::<Option<_>>()
+                .map(VectorData::Long),
+            (UnaryOperator::Minus, VectorData::Float(v)) => {
+                Some(VectorData::Float(v.iter().map(|x| -x).collect()))
+            }
+            (UnaryOperator::Minus, VectorData::Double(v)) => {
+                Some(VectorData::Double(v.iter().map(|x| -x).collect()))
+            }
+            _ => None,
+        };
+        if let Some(data) = data {
+            return Ok(Vector::new(data, vector.validity().clone()));
+        }
+
+        // the values that do not have a kernel are evaluated one at a time
+        let mut res = Vector::default();
+        for i in 0..vector.len() {
+            res.push(Self::evaluate_unary_field(op, vector.get(i))?);
+        }
+        Ok(res)
+    }
+
+    fn evaluate_unary_field(op: &UnaryOperator, field: Field) -> Result<Field, Error> {
+        if field == Field::Null {
+            return Ok(Field::Null);
+        }
+
+        let field = coercion::cast_numeric(&field, DataType::of_field(&field).arithmetic_type())
+            .unwrap_or(field);
+
         match op {
             UnaryOperator::Not => Ok(Field::Bool(!Self::to_boolean(&field))),
             UnaryOperator::Plus => match field {
@@ -76,17 +1010,38 @@ impl ExprEvaluator {
                 Field::Long(l) => Ok(Field::Long(l)),
                 Field::Float(f) => Ok(Field::Float(f)),
                 Field::Double(d) => Ok(Field::Double(d)),
+                Field::Decimal(d) => Ok(Field::Decimal(d)),
                 _ => Err(Error::Expression(format!(
                     "Unsupported unary operation: {} {}",
                     op, field
                 ))),
             },
             UnaryOperator::Minus => match field {
-                Field::Int(i) => Ok(Field::Int(-i)),
-                Field::Long(l) => Ok(Field::Long(-l)),
+                Field::Int(i) => i.checked_neg().map(Field::Int).ok_or_else(|| {
+                    Error::Expression(format!("Result of {} {} is out of range", op, i))
+                }),
+                Field::Long(l) => l.checked_neg().map(Field::Long).ok_or_else(|| {
+                    Error::Expression(format!("Result of {} {} is out of range", op, l))
+                }),
                 Field::Float(f) => Ok(Field::Float(-f)),
                 Field::Double(d) => Ok(Field::Double(-d)),
-                _ => Err(Error::Expression(format!(
+                Field::Decimal(d) => decimal::negate(&d).ok_or_else(|| {
+                    Error::Expression(format!(
+                        "Result of {} {} is out of range",
+                        op,
+                        Field::Decimal(d.clone())
+                    ))
+                }),
+                Field::Interval(interval) => {
+                    interval.checked_neg().map(Field::Interval).ok_or_else(|| {
+                        Error::Expression(format!(
+                            "Result of {} {} is out of range",
+                            op,
+                            Field::Interval(interval)
+                        ))
+                    })
+                }
+                field => Err(Error::Expression(format!(
                     "Unsupported unary operation: {} {}",
                     op, field
                 ))),
@@ -103,19 +1058,47 @@ impl ExprEvaluator {
         op: &BinaryOperator,
         right: &Field,
     ) -> Result<Field, Error> {
+        // numbers of different types are converted to the wider type first
+        let promoted = coercion::promote(left, right);
+        let (left, right) = match &promoted {
+            Some((left, right)) => (left, right),
+            None => (left, right),
+        };
+
+        // false and anything is false and true or anything is true even when the other side is unknown
+        let (left_truth, right_truth) = (Self::to_truth(left), Self::to_truth(right));
         match op {
-            BinaryOperator::NotEq => Ok(Field::Bool(left != right)),
-            BinaryOperator::Eq => Ok(Field::Bool(left == right)),
-            BinaryOperator::And => Ok(Field::Bool(
-                Self::to_boolean(left) && Self::to_boolean(right),
-            )),
-            BinaryOperator::Or => Ok(Field::Bool(
-                Self::to_boolean(left) || Self::to_boolean(right),
-            )),
-            BinaryOperator::Xor => Ok(Field::Bool(
-                (Self::to_boolean(left) && !Self::to_boolean(right))
-                    || (!Self::to_boolean(left) && Self::to_boolean(right)),
-            )),
+            BinaryOperator::And => {
+                return Ok(match (left_truth, right_truth) {
+                    (Some(false), _) | (_, Some(false)) => Field::Bool(false),
+                    (Some(true), Some(true)) => Field::Bool(true),
+                    _ => Field::Null,
+                })
+            }
+            BinaryOperator::Or => {
+                return Ok(match (left_truth, right_truth) {
+                    (Some(true), _) | (_, Some(true)) => Field::Bool(true),
+                    (Some(false), Some(false)) => Field::Bool(false),
+                    _ => Field::Null,
+                })
+            }
+            BinaryOperator::Xor => {
+                return Ok(match (left_truth, right_truth) {
+                    (Some(l), Some(r)) => Field::Bool(l != r),
+                    _ => Field::Null,
+                })
+            }
+            _ => {}
+        }
+
+        // comparisons and arithmetic with null are null
+        if (*left == Field::Null || *right == Field::Null) && Self::propagates_null(op) {
+            return Ok(Field::Null);
+        }
+
+        match op {
+            BinaryOperator::NotEq => Ok(Field::Bool(!Self::equals(left, right))),
+            BinaryOperator::Eq => Ok(Field::Bool(Self::equals(left, right))),
             BinaryOperator::Plus => Ok(BinaryOpEvaluator::add(left, right)?),
             BinaryOperator::Minus => Ok(BinaryOpEvaluator::subtract(left, right)?),
             BinaryOperator::Multiply => Ok(BinaryOpEvaluator::multipy(left, right)?),
@@ -132,7 +1115,32 @@ impl ExprEvaluator {
         }
     }
 
-    // Converts from sqlparser::ast::Value to parquet::record::Field
+    // decimals are equal when their values are, whatever their precision and scale
+    fn equals(left: &Field, right: &Field) -> bool {
+        match (left, right) {
+            (Field::Decimal(l), Field::Decimal(r)) => decimal::compare(l, r) == Ordering::Equal,
+            _ => left == right,
+        }
+    }
+
+    fn propagates_null(op: &BinaryOperator) -> bool {
+        matches!(
+            op,
+            BinaryOperator::Eq
+                | BinaryOperator::NotEq
+                | BinaryOperator::Lt
+                | BinaryOperator::LtEq
+                | BinaryOperator::Gt
+                | BinaryOperator::GtEq
+                | BinaryOperator::Plus
+                | BinaryOperator::Minus
+                | BinaryOperator::Multiply
+                | BinaryOperator::Divide
+                | BinaryOperator::Modulo
+        )
+    }
+
+    // Converts from sqlparser::ast::Value to Field
     pub fn evaluate_value(value: &sqlparser::ast::Value) -> Result<Field, Error> {
         match value {
             sqlparser::ast::Value::Number(n, _b) => {
@@ -157,52 +1165,243 @@ impl ExprEvaluator {
 pub struct BinaryOpEvaluator;
 
 impl BinaryOpEvaluator {
+    // evaluates the operation for every pair of values of the vectors
+    pub fn evaluate(left: &Vector, op: &BinaryOperator, right: &Vector) -> Result<Vector, Error> {
+        assert!(left.len() == right.len());
+
+        if matches!(
+            op,
+            BinaryOperator::And | BinaryOperator::Or | BinaryOperator::Xor
+        ) {
+            // unknown values are false in the masks so a known false or true decides the result on its own
+            let (left, left_validity) = ExprEvaluator::to_truths(left);
+            let (right, right_validity) = ExprEvaluator::to_truths(right);
+            let (values, validity): (Vec<bool>, Vec<bool>) = (0..left.len())
+                .map(|i| {
+                    let (l, r) = (left[i], right[i]);
+                    let known = left_validity.get(i) && right_validity.get(i);
+                    match op {
+                        BinaryOperator::And => (
+                            l && r,
+                            known || (left_validity.get(i) && !l) || (right_validity.get(i) && !r),
+                        ),
+                        BinaryOperator::Or => (l || r, known || l || r),
+                        _ => (l != r, known),
+                    }
+                })
+                .unzip();
+            return Ok(Vector::new(
+                VectorData::Bool(values),
+                validity.into_iter().collect(),
+            ));
+        }
+
+        // the kernels also run over the values of the null slots, the results are masked by the validity
+        let promoted = coercion::promote_vectors(left.data(), right.data());
+        let (left_data, right_data) = match &promoted {
+            Some((left, right)) => (left.as_ref(), right.as_ref()),
+            None => (left.data(), right.data()),
+        };
+        if !Self::divides_by_zero(op, right_data) {
+            if let Some(data) = Self::evaluate_kernel(left_data, op, right_data) {
+                return Ok(Vector::new(data, left.validity().and(right.validity())));
+            }
+        }
+
+        // nulls and values without a kernel are evaluated one at a time
+        let mut res = Vector::default();
+        for i in 0..left.len() {
+            res.push(ExprEvaluator::evaluate_binary_op(
+                &left.get(i),
+                op,
+                &right.get(i),
+            )?);
+        }
+        Ok(res)
+    }
+
+    // integer division by zero is an error for valid values so those vectors are evaluated one value at a time
+    fn divides_by_zero(op: &BinaryOperator, right: &VectorData) -> bool {
+        if !matches!(op, BinaryOperator::Divide | BinaryOperator::Modulo) {
+            return false;
+        }
+        match right {
+            VectorData::Int(values) => values.contains(&0),
+            VectorData::Long(values) => values.contains(&0),
+            _ => false,
+        }
+    }
+
+    // runs the operation over whole vectors when both sides have the same type
+    fn evaluate_kernel(
+        left: &VectorData,
+        op: &BinaryOperator,
+        right: &VectorData,
+    ) -> Option<VectorData> {
+        match (left, right) {
+            (VectorData::Int(l), VectorData::Int(r)) => Self::arithmetic(l, op, r)
+                .map(VectorData::Int)
+                .or_else(|| Self::compare(l, op, r).map(VectorData::Bool)),
+            (VectorData::Long(l), VectorData::Long(r)) => Self::arithmetic(l, op, r)
+                .map(VectorData::Long)
+                .or_else(|| Self::compare(l, op, r).map(VectorData::Bool)),
+            (VectorData::Float(l), VectorData::Float(r)) => Self::arithmetic(l, op, r)
+                .map(VectorData::Float)
+                .or_else(|| Self::compare(l, op, r).map(VectorData::Bool)),
+            (VectorData::Double(l), VectorData::Double(r)) => Self::arithmetic(l, op, r)
+                .map(VectorData::Double)
+                .or_else(|| Self::compare(l, op, r).map(VectorData::Bool)),
+            (VectorData::Str(l), VectorData::Str(r)) if *op == BinaryOperator::Plus => {
+                Some(VectorData::Str(
+                    l.iter()
+                        .zip(r)
+                        .map(|(l, r)| format!("{}{}", l, r))
+                        .collect(),
+                ))
+            }
+            (VectorData::Str(l), VectorData::Str(r)) => {
+                Self::compare(l, op, r).map(VectorData::Bool)
+            }
+            (VectorData::Bool(l), VectorData::Bool(r)) => {
+                Self::compare(l, op, r).map(VectorData::Bool)
+            }
+            (VectorData::Date(l), VectorData::Date(r)) => {
+                Self::compare(l, op, r).map(VectorData::Bool)
+            }
+            (VectorData::Timestamp(l), VectorData::Timestamp(r)) => {
+                Self::compare(l, op, r).map(VectorData::Bool)
+            }
+            _ => None,
+        }
+    }
+
+    // none when the operation is not arithmetic or a result does not fit in the type, the values are then
+    // evaluated one at a time so the value that overflows is reported
+    fn arithmetic<T: Arithmetic>(left: &[T], op: &BinaryOperator, right: &[T]) -> Option<Vec<T>> {
+        let f: fn(T, T) -> Option<T> = match op {
+            BinaryOperator::Plus => T::checked_add,
+            BinaryOperator::Minus => T::checked_sub,
+            BinaryOperator::Multiply => T::checked_mul,
+            BinaryOperator::Divide => T::checked_div,
+            BinaryOperator::Modulo => T::checked_rem,
+            _ => return None,
+        };
+
+        left.iter().zip(right).map(|(l, r)| f(*l, *r)).collect()
+    }
+
+    fn compare<T: PartialOrd>(left: &[T], op: &BinaryOperator, right: &[T]) -> Option<Vec<bool>> {
+        let f: fn(&T, &T) -> bool = match op {
+            BinaryOperator::Eq => T::eq,
+            BinaryOperator::NotEq => T::ne,
+            BinaryOperator::Lt => T::lt,
+            BinaryOperator::LtEq => T::le,
+            BinaryOperator::Gt => T::gt,
+            BinaryOperator::GtEq => T::ge,
+            _ => return None,
+        };
+
+        Some(left.iter().zip(right).map(|(l, r)| f(l, r)).collect())
+    }
+
     fn add(left: &Field, right: &Field) -> Result<Field, Error> {
         match (left, right) {
-            (Field::Int(l), Field::Int(r)) => Ok(Field::Int(l + r)),
-            (Field::Long(l), Field::Long(r)) => Ok(Field::Long(l + r)),
+            (Field::Int(l), Field::Int(r)) => {
+                Self::checked(left, "+", right, l.checked_add(*r).map(Field::Int))
+            }
+            (Field::Long(l), Field::Long(r)) => {
+                Self::checked(left, "+", right, l.checked_add(*r).map(Field::Long))
+            }
+            (Field::Decimal(l), Field::Decimal(r)) => Self::checked(
+                left,
+                "+",
+                right,
+                decimal::arithmetic(l, &BinaryOperator::Plus, r),
+            ),
             (Field::Float(l), Field::Float(r)) => Ok(Field::Float(l + r)),
             (Field::Double(l), Field::Double(r)) => Ok(Field::Double(l + r)),
             (Field::Str(l), Field::Str(r)) => Ok(Field::Str(format!("{}{}", l, r))),
-            _ => Err(Error::Expression(format!(
-                "Unsupported binary operation: {} + {}",
-                left, right
-            ))),
+            _ => Self::temporal(left, &BinaryOperator::Plus, right).unwrap_or_else(|| {
+                Err(Error::Expression(format!(
+                    "Unsupported binary operation: {} + {}",
+                    left, right
+                )))
+            }),
         }
     }
 
     fn subtract(left: &Field, right: &Field) -> Result<Field, Error> {
         match (left, right) {
-            (Field::Int(l), Field::Int(r)) => Ok(Field::Int(l - r)),
-            (Field::Long(l), Field::Long(r)) => Ok(Field::Long(l - r)),
+            (Field::Int(l), Field::Int(r)) => {
+                Self::checked(left, "-", right, l.checked_sub(*r).map(Field::Int))
+            }
+            (Field::Long(l), Field::Long(r)) => {
+                Self::checked(left, "-", right, l.checked_sub(*r).map(Field::Long))
+            }
+            (Field::Decimal(l), Field::Decimal(r)) => Self::checked(
+                left,
+                "-",
+                right,
+                decimal::arithmetic(l, &BinaryOperator::Minus, r),
+            ),
             (Field::Float(l), Field::Float(r)) => Ok(Field::Float(l - r)),
             (Field::Double(l), Field::Double(r)) => Ok(Field::Double(l - r)),
-            _ => Err(Error::Expression(format!(
-                "Unsupported binary operation: {} - {}",
-                left, right
-            ))),
+            _ => Self::temporal(left, &BinaryOperator::Minus, right).unwrap_or_else(|| {
+                Err(Error::Expression(format!(
+                    "Unsupported binary operation: {} - {}",
+                    left, right
+                )))
+            }),
         }
     }
 
     fn multipy(left: &Field, right: &Field) -> Result<Field, Error> {
         match (left, right) {
-            (Field::Int(l), Field::Int(r)) => Ok(Field::Int(l * r)),
-            (Field::Long(l), Field::Long(r)) => Ok(Field::Long(l * r)),
+            (Field::Int(l), Field::Int(r)) => {
+                Self::checked(left, "*", right, l.checked_mul(*r).map(Field::Int))
+            }
+            (Field::Long(l), Field::Long(r)) => {
+                Self::checked(left, "*", right, l.checked_mul(*r).map(Field::Long))
+            }
+            (Field::Decimal(l), Field::Decimal(r)) => Self::checked(
+                left,
+                "*",
+                right,
+                decimal::arithmetic(l, &BinaryOperator::Multiply, r),
+            ),
             (Field::Float(l), Field::Float(r)) => Ok(Field::Float(l * r)),
             (Field::Double(l), Field::Double(r)) => Ok(Field::Double(l * r)),
-            _ => Err(Error::Expression(format!(
-                "Unsupported binary operation: {} * {}",
-                left, right
-            ))),
+            _ => Self::temporal(left, &BinaryOperator::Multiply, right).unwrap_or_else(|| {
+                Err(Error::Expression(format!(
+                    "Unsupported binary operation: {} * {}",
+                    left, right
+                )))
+            }),
         }
     }
 
     fn divide(left: &Field, right: &Field) -> Result<Field, Error> {
         match (left, right) {
-            (Field::Int(l), Field::Int(r)) => Ok(Field::Int(l / r)),
-            (Field::Long(l), Field::Long(r)) => Ok(Field::Long(l / r)),
+            (_, Field::Int(0) | Field::Long(0)) => {
+                Err(Error::Expression("Division by zero".to_string()))
+            }
+            (_, Field::Decimal(d)) if decimal::is_zero(d) => {
+                Err(Error::Expression("Division by zero".to_string()))
+            }
+            (Field::Int(l), Field::Int(r)) => {
+                Self::checked(left, "/", right, l.checked_div(*r).map(Field::Int))
+            }
+            (Field::Long(l), Field::Long(r)) => {
+                Self::checked(left, "/", right, l.checked_div(*r).map(Field::Long))
+            }
             (Field::Float(l), Field::Float(r)) => Ok(Field::Float(l / r)),
             (Field::Double(l), Field::Double(r)) => Ok(Field::Double(l / r)),
+            (Field::Decimal(l), Field::Decimal(r)) => Self::checked(
+                left,
+                "/",
+                right,
+                decimal::arithmetic(l, &BinaryOperator::Divide, r),
+            ),
             _ => Err(Error::Expression(format!(
                 "Unsupported binary operation: {} / {}",
                 left, right
@@ -217,6 +1416,12 @@ impl BinaryOpEvaluator {
             (Field::Float(l), Field::Float(r)) => Ok(Field::Bool(l < r)),
             (Field::Double(l), Field::Double(r)) => Ok(Field::Bool(l < r)),
             (Field::Str(l), Field::Str(r)) => Ok(Field::Bool(l < r)),
+            (Field::Bool(l), Field::Bool(r)) => Ok(Field::Bool(l < r)),
+            (Field::Date(l), Field::Date(r)) => Ok(Field::Bool(l < r)),
+            (Field::TimestampMicros(l), Field::TimestampMicros(r)) => Ok(Field::Bool(l < r)),
+            (left, right) if coercion::compare(left, right).is_some() => Ok(Field::Bool(
+                coercion::compare(left, right) < Some(Ordering::Equal),
+            )),
             _ => Err(Error::Expression(format!(
                 "Unsupported binary operation: {} < {}",
                 left, right
@@ -235,6 +1440,12 @@ impl BinaryOpEvaluator {
             (Field::Float(l), Field::Float(r)) => Ok(Field::Bool(l <= r)),
             (Field::Double(l), Field::Double(r)) => Ok(Field::Bool(l <= r)),
             (Field::Str(l), Field::Str(r)) => Ok(Field::Bool(l <= r)),
+            (Field::Bool(l), Field::Bool(r)) => Ok(Field::Bool(l <= r)),
+            (Field::Date(l), Field::Date(r)) => Ok(Field::Bool(l <= r)),
+            (Field::TimestampMicros(l), Field::TimestampMicros(r)) => Ok(Field::Bool(l <= r)),
+            (left, right) if coercion::compare(left, right).is_some() => Ok(Field::Bool(
+                coercion::compare(left, right) <= Some(Ordering::Equal),
+            )),
             _ => Err(Error::Expression(format!(
                 "Unsupported binary operation: {} =< {}",
                 left, right
@@ -246,12 +1457,140 @@ impl BinaryOpEvaluator {
         Self::less_than_or_equal(right, left)
     }
 
+    // arithmetic with dates, timestamps and intervals, none when the values are not of those types
+    fn temporal(left: &Field, op: &BinaryOperator, right: &Field) -> Option<Result<Field, Error>> {
+        let minus = *op == BinaryOperator::Minus;
+        let interval = |field: &Field| match field {
+            Field::Interval(interval) => Some(*interval),
+            _ => None,
+        };
+        let (left_interval, right_interval) = (interval(left), interval(right));
+        let (left_time, right_time) = (Self::timestamp(left), Self::timestamp(right));
+
+        let result = match (op, left, right) {
+            // a number of days can be added to and subtracted from a date
+            (BinaryOperator::Plus | BinaryOperator::Minus, Field::Date(date), days)
+            | (BinaryOperator::Plus, days, Field::Date(date))
+                if Self::days(days).is_some() =>
+            {
+                let days = Self::days(days)?;
+                let days = if minus {
+                    days.checked_neg()
+                } else {
+                    Some(days)
+                };
+                days.and_then(|days| i64::from(*date).checked_add(days))
+                    .and_then(|days| i32::try_from(days).ok())
+                    .map(Field::Date)
+            }
+            (BinaryOperator::Minus, Field::Date(l), Field::Date(r)) => {
+                l.checked_sub(*r).map(Field::Int)
+            }
+            (BinaryOperator::Minus, _, _) if left_time.is_some() && right_time.is_some() => {
+                datetime::subtract_timestamps(left_time?, right_time?).map(Field::Interval)
+            }
+            (BinaryOperator::Plus | BinaryOperator::Minus, _, _)
+                if left_time.is_some() && right_interval.is_some() =>
+            {
+                let interval = right_interval?;
+                let interval = if minus {
+                    interval.checked_neg()
+                } else {
+                    Some(interval)
+                };
+                interval
+                    .and_then(|interval| datetime::add_interval(left_time?, &interval))
+                    .map(Field::TimestampMicros)
+            }
+            (BinaryOperator::Plus, _, _) if left_interval.is_some() && right_time.is_some() => {
+                datetime::add_interval(right_time?, &left_interval?).map(Field::TimestampMicros)
+            }
+            (BinaryOperator::Plus | BinaryOperator::Minus, _, _)
+                if left_interval.is_some() && right_interval.is_some() =>
+            {
+                let interval = right_interval?;
+                let interval = if minus {
+                    interval.checked_neg()
+                } else {
+                    Some(interval)
+                };
+                interval
+                    .and_then(|interval| left_interval?.checked_add(&interval))
+                    .map(Field::Interval)
+            }
+            (BinaryOperator::Multiply, _, factor) if left_interval.is_some() => left_interval?
+                .checked_mul(Self::days(factor)?)
+                .map(Field::Interval),
+            (BinaryOperator::Multiply, factor, _) if right_interval.is_some() => right_interval?
+                .checked_mul(Self::days(factor)?)
+                .map(Field::Interval),
+            _ => return None,
+        };
+
+        Some(result.ok_or_else(|| {
+            Error::Expression(format!(
+                "Result of {} {} {} is out of range",
+                left, op, right
+            ))
+        }))
+    }
+
+    // the result of integer arithmetic or an error when it does not fit in the type
+    fn checked(
+        left: &Field,
+        op: &str,
+        right: &Field,
+        result: Option<Field>,
+    ) -> Result<Field, Error> {
+        result.ok_or_else(|| {
+            Error::Expression(format!(
+                "Result of {} {} {} is out of range",
+                left, op, right
+            ))
+        })
+    }
+
+    fn days(field: &Field) -> Option<i64> {
+        match field {
+            Field::Byte(n) => Some(i64::from(*n)),
+            Field::Short(n) => Some(i64::from(*n)),
+            Field::Int(n) => Some(i64::from(*n)),
+            Field::Long(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn timestamp(field: &Field) -> Option<i64> {
+        match field {
+            Field::Date(_) | Field::TimestampMillis(_) | Field::TimestampMicros(_) => {
+                datetime::to_timestamp(field)
+            }
+            _ => None,
+        }
+    }
+
     fn modulo(left: &Field, right: &Field) -> Result<Field, Error> {
         match (left, right) {
-            (Field::Int(l), Field::Int(r)) => Ok(Field::Int(l % r)),
-            (Field::Long(l), Field::Long(r)) => Ok(Field::Long(l % r)),
+            (_, Field::Int(0) | Field::Long(0)) => {
+                Err(Error::Expression("Division by zero".to_string()))
+            }
+            (_, Field::Decimal(d)) if decimal::is_zero(d) => {
+                Err(Error::Expression("Division by zero".to_string()))
+            }
+            (Field::Int(l), Field::Int(r)) => {
+                Self::checked(left, "%", right, l.checked_rem(*r).map(Field::Int))
+            }
+            (Field::Long(l), Field::Long(r)) => {
+                Self::checked(left, "%", right, l.checked_rem(*r).map(Field::Long))
+            }
             (Field::Float(l), Field::Float(r)) => Ok(Field::Float(l % r)),
             (Field::Double(l), Field::Double(r)) => Ok(Field::Double(l % r)),
+            (Field::Decimal(l), Field::Decimal(r)) => Self::checked(
+                left,
+                "%",
+                right,
+                decimal::arithmetic(l, &BinaryOperator::Modulo, r),
+            ),
             _ => Err(Error::Expression(format!(
                 "Unsupported binary operation: {} % {}",
                 left, right
@@ -263,45 +1602,286 @@ impl BinaryOpEvaluator {
 pub struct Caster {}
 
 impl Caster {
+    // converts a value to the sql type, null stays null and values that cannot be converted are errors
     pub fn cast(field: &Field, data_type: &sqlparser::ast::DataType) -> Result<Field, Error> {
-        match data_type {
-            sqlparser::ast::DataType::Int(_) => match field {
-                Field::Int(i) => Ok(Field::Int(*i)),
-                Field::Long(l) => Ok(Field::Int(*l as i32)),
-                Field::Float(f) => Ok(Field::Int(*f as i32)),
-                Field::Double(d) => Ok(Field::Int(*d as i32)),
-                Field::Str(s) => Ok(Field::Int(s.parse::<i32>().unwrap())),
-                _ => Err(Error::Expression(format!(
-                    "Unable to cast {} to Int",
-                    field
-                ))),
-            },
-            sqlparser::ast::DataType::Float(_) => match field {
-                Field::Int(i) => Ok(Field::Float(*i as f32)),
-                Field::Long(l) => Ok(Field::Float(*l as f32)),
-                Field::Float(f) => Ok(Field::Float(*f)),
-                Field::Double(d) => Ok(Field::Float(*d as f32)),
-                Field::Str(s) => Ok(Field::Float(s.parse::<f32>().unwrap())),
-                _ => Err(Error::Expression(format!(
-                    "Unable to cast {} to Float",
-                    field
-                ))),
+        use sqlparser::ast::DataType as SqlType;
+
+        if *field == Field::Null {
+            return Ok(Field::Null);
+        }
+
+        let res = match data_type {
+            SqlType::Boolean => Self::to_boolean(field).map(Field::Bool),
+            SqlType::TinyInt(_) => Self::to_integer(field)
+                .and_then(|i| i8::try_from(i).ok())
+                .map(Field::Byte),
+            SqlType::SmallInt(_) => Self::to_integer(field)
+                .and_then(|i| i16::try_from(i).ok())
+                .map(Field::Short),
+            SqlType::Int(_) | SqlType::Integer(_) => Self::to_integer(field)
+                .and_then(|i| i32::try_from(i).ok())
+                .map(Field::Int),
+            SqlType::BigInt(_) => Self::to_integer(field).map(Field::Long),
+            SqlType::Float(_) | SqlType::Real => {
+                Self::to_double(field).map(|d| Field::Float(d as f32))
+            }
+            SqlType::Double | SqlType::DoublePrecision => Self::to_double(field).map(Field::Double),
+            SqlType::Decimal(info) | SqlType::Numeric(info) | SqlType::Dec(info) => {
+                match DataType::decimal_precision_and_scale(info) {
+                    Some((precision, scale)) => Self::to_decimal(field, precision, scale),
+                    None => {
+                        return Err(Error::Expression(format!(
+                            "Unsupported cast data type: {}",
+                            data_type
+                        )))
+                    }
+                }
+            }
+            SqlType::Char(_)
+            | SqlType::Character(_)
+            | SqlType::Varchar(_)
+            | SqlType::CharVarying(_)
+            | SqlType::CharacterVarying(_)
+            | SqlType::Text
+            | SqlType::String => Self::to_string(field).map(Field::Str),
+            SqlType::Date => Self::to_date(field).map(Field::Date),
+            SqlType::Timestamp(_, _) | SqlType::Datetime(_) => {
+                datetime::to_timestamp(field).map(Field::TimestampMicros)
+            }
+            SqlType::Interval => match field {
+                Field::Str(s) => Interval::parse(s).map(Field::Interval),
+                Field::Interval(interval) => Some(Field::Interval(*interval)),
+                _ => None,
             },
-            sqlparser::ast::DataType::Double => match field {
-                Field::Int(i) => Ok(Field::Double(*i as f64)),
-                Field::Long(l) => Ok(Field::Double(*l as f64)),
-                Field::Float(f) => Ok(Field::Double(*f as f64)),
-                Field::Double(d) => Ok(Field::Double(*d)),
-                Field::Str(s) => Ok(Field::Double(s.parse::<f64>().unwrap())),
-                _ => Err(Error::Expression(format!(
-                    "Unable to cast {} to Float",
-                    field
-                ))),
+            _ => {
+                return Err(Error::Expression(format!(
+                    "Unsupported cast data type: {}",
+                    data_type
+                )))
+            }
+        };
+
+        res.ok_or_else(|| Error::Expression(format!("Unable to cast {} to {}", field, data_type)))
+    }
+
+    // casts a whole vector when its values are all converted the same way, none when they have to be cast one at a time
+    pub fn cast_vector(vector: &Vector, data_type: &sqlparser::ast::DataType) -> Option<Vector> {
+        let target = DataType::from_sql(data_type)?;
+        let validity = vector.validity();
+        let data = match (vector.data(), target) {
+            (VectorData::Byte(values), target) => Self::cast_integers(values, validity, target)?,
+            (VectorData::Short(values), target) => Self::cast_integers(values, validity, target)?,
+            (VectorData::Int(values), target) => Self::cast_integers(values, validity, target)?,
+            (VectorData::Long(values), target) => Self::cast_integers(values, validity, target)?,
+            (VectorData::Float(values), DataType::Double) => {
+                VectorData::Double(values.iter().map(|f| *f as f64).collect())
+            }
+            (VectorData::Float(values), DataType::String) => Self::format_values(values, validity),
+            (VectorData::Double(values), DataType::Float) => {
+                VectorData::Float(values.iter().map(|d| *d as f32).collect())
+            }
+            (VectorData::Double(values), DataType::String) => Self::format_values(values, validity),
+            (VectorData::Float(_), DataType::Float)
+            | (VectorData::Double(_), DataType::Double)
+            | (VectorData::Str(_), DataType::String)
+            | (VectorData::Date(_), DataType::Date)
+            | (VectorData::Timestamp(_), DataType::Timestamp) => vector.data().clone(),
+            _ => return None,
+        };
+        Some(Vector::new(data, validity.clone()))
+    }
+
+    // integers are narrowed only when all of them fit, floats are converted through doubles like single values
+    fn cast_integers<T: Copy + Into<i64> + ToString>(
+        values: &[T],
+        validity: &Bitmap,
+        target: DataType,
+    ) -> Option<VectorData> {
+        fn narrow<T: Copy + Into<i64>, U: TryFrom<i64>>(values: &[T]) -> Option<Vec<U>> {
+            values
+                .iter()
+                .map(|v| U::try_from((*v).into()).ok())
+                .collect()
+        }
+
+        let data = match target {
+            DataType::Byte => VectorData::Byte(narrow(values)?),
+            DataType::Short => VectorData::Short(narrow(values)?),
+            DataType::Int => VectorData::Int(narrow(values)?),
+            DataType::Long => VectorData::Long(values.iter().map(|v| (*v).into()).collect()),
+            DataType::Float => {
+                VectorData::Float(values.iter().map(|v| (*v).into() as f64 as f32).collect())
+            }
+            DataType::Double => {
+                VectorData::Double(values.iter().map(|v| (*v).into() as f64).collect())
+            }
+            DataType::String => Self::format_values(values, validity),
+            _ => return None,
+        };
+        Some(data)
+    }
+
+    fn format_values<T: ToString>(values: &[T], validity: &Bitmap) -> VectorData {
+        VectorData::Str(
+            values
+                .iter()
+                .zip(validity.iter())
+                .map(|(value, valid)| {
+                    if valid {
+                        value.to_string()
+                    } else {
+                        String::new()
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    fn to_boolean(field: &Field) -> Option<bool> {
+        match field {
+            Field::Bool(b) => Some(*b),
+            Field::Str(s) => match s.trim().to_lowercase().as_str() {
+                "true" | "t" | "yes" | "y" | "on" | "1" => Some(true),
+                "false" | "f" | "no" | "n" | "off" | "0" => Some(false),
+                _ => None,
             },
-            _ => Err(Error::Expression(format!(
-                "Unsupported cast data type: {}",
-                data_type
-            ))),
+            field => coercion::as_double(field).map(|d| d != 0.0),
+        }
+    }
+
+    // numbers with a fraction are rounded to the nearest integer
+    fn to_integer(field: &Field) -> Option<i64> {
+        match field {
+            Field::Bool(b) => Some(*b as i64),
+            Field::Byte(b) => Some(*b as i64),
+            Field::Short(s) => Some(*s as i64),
+            Field::Int(i) => Some(*i as i64),
+            Field::Long(l) => Some(*l),
+            Field::Str(s) => s.trim().parse::<i64>().ok(),
+            field => {
+                let d = coercion::as_double(field)?.round();
+                // i64::MAX as f64 rounds up so the upper bound is exclusive
+                (d.is_finite() && d >= i64::MIN as f64 && d < i64::MAX as f64).then_some(d as i64)
+            }
+        }
+    }
+
+    fn to_double(field: &Field) -> Option<f64> {
+        match field {
+            Field::Bool(b) => Some(*b as i64 as f64),
+            Field::Str(s) => s.trim().parse::<f64>().ok(),
+            field => coercion::as_double(field),
+        }
+    }
+
+    fn to_decimal(field: &Field, precision: i32, scale: i32) -> Option<Field> {
+        let factor = 10i128.pow(scale as u32);
+        let unscaled = match field {
+            Field::Decimal(d) => {
+                let unscaled = coercion::decimal_unscaled(d);
+                if d.scale() <= scale {
+                    unscaled.checked_mul(10i128.pow((scale - d.scale()) as u32))?
+                } else {
+                    decimal::divide_rounded(unscaled, 10i128.pow((d.scale() - scale) as u32))
+                }
+            }
+            Field::Str(s) => decimal::parse(s.trim(), scale)?,
+            Field::Float(_) | Field::Double(_) => {
+                let d = (coercion::as_double(field)? * factor as f64).round();
+                if !d.is_finite() || d.abs() >= 1e38 {
+                    return None;
+                }
+                d as i128
+            }
+            field => (Self::to_integer(field)? as i128).checked_mul(factor)?,
+        };
+
+        // the value has to fit in the digits of the precision
+        decimal::new(unscaled, precision, scale)
+    }
+
+    pub fn to_string(field: &Field) -> Option<String> {
+        match field {
+            Field::Str(s) => Some(s.clone()),
+            Field::Bool(b) => Some(b.to_string()),
+            Field::Byte(b) => Some(b.to_string()),
+            Field::Short(s) => Some(s.to_string()),
+            Field::Int(i) => Some(i.to_string()),
+            Field::Long(l) => Some(l.to_string()),
+            Field::Float(f) => Some(f.to_string()),
+            Field::Double(d) => Some(d.to_string()),
+            Field::Decimal(_) => Some(field.to_string()),
+            Field::Date(d) => Some(datetime::format_date(*d)),
+            Field::TimestampMillis(t) => Some(datetime::format_timestamp(t * 1000)),
+            Field::TimestampMicros(t) => Some(datetime::format_timestamp(*t)),
+            Field::Interval(interval) => Some(interval.to_string()),
+            _ => None,
         }
     }
+
+    fn to_date(field: &Field) -> Option<i32> {
+        match field {
+            Field::Date(d) => Some(*d),
+            Field::TimestampMillis(t) => Some(datetime::timestamp_to_date(t * 1000)),
+            Field::TimestampMicros(t) => Some(datetime::timestamp_to_date(*t)),
+            Field::Str(s) => datetime::parse_date(s),
+            _ => None,
+        }
+    }
+}
+
+// the operations of the vector kernels, integers report when the result does not fit in the type
+trait Arithmetic: Copy {
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn checked_div(self, other: Self) -> Option<Self>;
+    fn checked_rem(self, other: Self) -> Option<Self>;
 }
+
+macro_rules! integer_arithmetic {
+    ($($t:ty),*) => {$(
+        impl Arithmetic for $t {
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                <$t>::checked_sub(self, other)
+            }
+            fn checked_mul(self, other: Self) -> Option<Self> {
+                <$t>::checked_mul(self, other)
+            }
+            fn checked_div(self, other: Self) -> Option<Self> {
+                <$t>::checked_div(self, other)
+            }
+            fn checked_rem(self, other: Self) -> Option<Self> {
+                <$t>::checked_rem(self, other)
+            }
+        }
+    )*};
+}
+
+macro_rules! float_arithmetic {
+    ($($t:ty),*) => {$(
+        impl Arithmetic for $t {
+            fn checked_add(self, other: Self) -> Option<Self> {
+                Some(self + other)
+            }
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                Some(self - other)
+            }
+            fn checked_mul(self, other: Self) -> Option<Self> {
+                Some(self * other)
+            }
+            fn checked_div(self, other: Self) -> Option<Self> {
+                Some(self / other)
+            }
+            fn checked_rem(self, other: Self) -> Option<Self> {
+                Some(self % other)
+            }
+        }
+    )*};
+}
+
+integer_arithmetic!(i32, i64);
+float_arithmetic!(f32, f64);
@@ -1,4 +1,4 @@
-use crate::executor::expression::ExprEvaluator;
+use crate::executor::expression::PhysicalExpr;
 use crate::executor::Executor;
 use crate::planner::OutputSchema;
 use crate::types::error::Error;
@@ -9,7 +9,7 @@ use super::{Buffer, VECTOR_SIZE_THRESHOLD};
 
 pub struct Filter {
     output_schema: OutputSchema,
-    filter: Expr,
+    filter: PhysicalExpr,
     child: Box<dyn Executor>,
 
     buffer: Buffer,
@@ -22,7 +22,7 @@ impl Filter {
         output_schema: OutputSchema,
     ) -> Result<Box<Filter>, Error> {
         Ok(Box::new(Filter {
-            filter,
+            filter: PhysicalExpr::compile(&filter, &output_schema)?,
             child,
             output_schema,
             buffer: Buffer::new(),
@@ -39,12 +39,8 @@ impl Executor for Filter {
                 break;
             }
 
-            for row in next_chunk.get_rows().iter() {
-                let e = ExprEvaluator::evaluate(&self.filter, row, &self.output_schema)?;
-                if ExprEvaluator::to_boolean(&e) {
-                    self.buffer.add_row(row.clone());
-                }
-            }
+            let mask = self.filter.evaluate_mask(&next_chunk)?;
+            self.buffer.add_chunk(&next_chunk.filter(&mask));
         }
 
         Ok(self.buffer.get_sized_chunk(VECTOR_SIZE_THRESHOLD))
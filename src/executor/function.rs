@@ -0,0 +1,595 @@
+use std::cmp::Ordering;
+
+use sqlparser::ast::{DateTimeField, Expr, TrimWhereField, Value};
+
+use crate::{
+    planner::binder::common_type,
+    types::{
+        coercion,
+        data_type::DataType,
+        datetime,
+        error::Error,
+        field::Field,
+        vector::{Vector, VectorData},
+    },
+};
+
+use super::expression::Caster;
+
+// the functions that are evaluated for every row, aggregate functions are handled by the aggregation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScalarFunction {
+    Upper,
+    Lower,
+    Length,
+    Substr,
+    Trim,
+    LTrim,
+    RTrim,
+    Concat,
+    Replace,
+    SplitPart,
+    Abs,
+    Round,
+    Ceil,
+    Floor,
+    Sqrt,
+    Power,
+    Ln,
+    Greatest,
+    Least,
+    DateTrunc,
+    DatePart,
+    Now,
+    CurrentDate,
+    Strftime,
+}
+
+// the names a function can be called by, the first one is used in messages
+const SCALAR_FUNCTIONS: [(&str, ScalarFunction); 29] = [
+    ("upper", ScalarFunction::Upper),
+    ("lower", ScalarFunction::Lower),
+    ("length", ScalarFunction::Length),
+    ("char_length", ScalarFunction::Length),
+    ("substr", ScalarFunction::Substr),
+    ("substring", ScalarFunction::Substr),
+    ("trim", ScalarFunction::Trim),
+    ("ltrim", ScalarFunction::LTrim),
+    ("rtrim", ScalarFunction::RTrim),
+    ("concat", ScalarFunction::Concat),
+    ("replace", ScalarFunction::Replace),
+    ("split_part", ScalarFunction::SplitPart),
+    ("abs", ScalarFunction::Abs),
+    ("round", ScalarFunction::Round),
+    ("ceil", ScalarFunction::Ceil),
+    ("ceiling", ScalarFunction::Ceil),
+    ("floor", ScalarFunction::Floor),
+    ("sqrt", ScalarFunction::Sqrt),
+    ("power", ScalarFunction::Power),
+    ("pow", ScalarFunction::Power),
+    ("ln", ScalarFunction::Ln),
+    ("greatest", ScalarFunction::Greatest),
+    ("least", ScalarFunction::Least),
+    ("date_trunc", ScalarFunction::DateTrunc),
+    ("date_part", ScalarFunction::DatePart),
+    ("now", ScalarFunction::Now),
+    ("current_timestamp", ScalarFunction::Now),
+    ("current_date", ScalarFunction::CurrentDate),
+    ("strftime", ScalarFunction::Strftime),
+];
+
+impl ScalarFunction {
+    pub fn from_name(name: &str) -> Option<ScalarFunction> {
+        let name = name.to_lowercase();
+        SCALAR_FUNCTIONS
+            .iter()
+            .find(|(function_name, _)| *function_name == name)
+            .map(|(_, function)| *function)
+    }
+
+    pub fn name(&self) -> &'static str {
+        SCALAR_FUNCTIONS
+            .iter()
+            .find(|(_, function)| function == self)
+            .map(|(name, _)| *name)
+            .unwrap()
+    }
+
+    // TRIM, SUBSTRING, CEIL, FLOOR and EXTRACT have their own syntax but are the same as calling the functions
+    pub fn from_expr(expr: &Expr) -> Option<(ScalarFunction, Vec<Expr>)> {
+        match expr {
+            Expr::Ceil {
+                expr,
+                field: DateTimeField::NoDateTime,
+            } => Some((ScalarFunction::Ceil, vec![*expr.clone()])),
+            Expr::Floor {
+                expr,
+                field: DateTimeField::NoDateTime,
+            } => Some((ScalarFunction::Floor, vec![*expr.clone()])),
+            Expr::Trim {
+                expr,
+                trim_where,
+                trim_what,
+            } => {
+                let function = match trim_where {
+                    Some(TrimWhereField::Leading) => ScalarFunction::LTrim,
+                    Some(TrimWhereField::Trailing) => ScalarFunction::RTrim,
+                    Some(TrimWhereField::Both) | None => ScalarFunction::Trim,
+                };
+                let mut args = vec![*expr.clone()];
+                args.extend(trim_what.iter().map(|what| *what.clone()));
+                Some((function, args))
+            }
+            Expr::Substring {
+                expr,
+                substring_from,
+                substring_for,
+            } => {
+                let from = match substring_from {
+                    Some(from) => *from.clone(),
+                    None => Expr::Value(Value::Number("1".to_string(), false)),
+                };
+                let mut args = vec![*expr.clone(), from];
+                args.extend(substring_for.iter().map(|count| *count.clone()));
+                Some((ScalarFunction::Substr, args))
+            }
+            Expr::Extract { field, expr } => {
+                let part = Expr::Value(Value::SingleQuotedString(field.to_string().to_lowercase()));
+                Some((ScalarFunction::DatePart, vec![part, *expr.clone()]))
+            }
+            _ => None,
+        }
+    }
+
+    // the minimum and maximum number of arguments, there is no maximum for None
+    pub fn arity(&self) -> (usize, Option<usize>) {
+        match self {
+            ScalarFunction::Upper
+            | ScalarFunction::Lower
+            | ScalarFunction::Length
+            | ScalarFunction::Abs
+            | ScalarFunction::Ceil
+            | ScalarFunction::Floor
+            | ScalarFunction::Sqrt
+            | ScalarFunction::Ln => (1, Some(1)),
+            ScalarFunction::Trim
+            | ScalarFunction::LTrim
+            | ScalarFunction::RTrim
+            | ScalarFunction::Round => (1, Some(2)),
+            ScalarFunction::Substr => (2, Some(3)),
+            ScalarFunction::Power
+            | ScalarFunction::DateTrunc
+            | ScalarFunction::DatePart
+            | ScalarFunction::Strftime => (2, Some(2)),
+            ScalarFunction::Now | ScalarFunction::CurrentDate => (0, Some(0)),
+            ScalarFunction::Replace | ScalarFunction::SplitPart => (3, Some(3)),
+            ScalarFunction::Concat | ScalarFunction::Greatest | ScalarFunction::Least => (1, None),
+        }
+    }
+
+    // the type of the result or None if the function does not support arguments of the types
+    pub fn return_type(&self, args: &[DataType]) -> Option<DataType> {
+        let is_string = |data_type: &DataType| {
+            matches!(
+                data_type,
+                DataType::String | DataType::Null | DataType::Unknown
+            )
+        };
+        let is_integer = |data_type: &DataType| {
+            data_type.is_integer() || matches!(data_type, DataType::Null | DataType::Unknown)
+        };
+        let is_numeric = |data_type: &DataType| {
+            data_type.is_numeric() || matches!(data_type, DataType::Null | DataType::Unknown)
+        };
+        // strings are parsed as timestamps
+        let is_temporal = |data_type: &DataType| {
+            matches!(
+                data_type,
+                DataType::Date
+                    | DataType::Timestamp
+                    | DataType::String
+                    | DataType::Null
+                    | DataType::Unknown
+            )
+        };
+
+        match self {
+            ScalarFunction::Upper
+            | ScalarFunction::Lower
+            | ScalarFunction::Trim
+            | ScalarFunction::LTrim
+            | ScalarFunction::RTrim
+            | ScalarFunction::Replace => args.iter().all(is_string).then_some(DataType::String),
+            ScalarFunction::Length => args.iter().all(is_string).then_some(DataType::Int),
+            ScalarFunction::Substr => (is_string(&args[0]) && args[1..].iter().all(is_integer))
+                .then_some(DataType::String),
+            ScalarFunction::SplitPart => {
+                (is_string(&args[0]) && is_string(&args[1]) && is_integer(&args[2]))
+                    .then_some(DataType::String)
+            }
+            // the arguments are converted to strings
+            ScalarFunction::Concat => Some(DataType::String),
+            ScalarFunction::Abs | ScalarFunction::Ceil | ScalarFunction::Floor => {
+                is_numeric(&args[0]).then(|| Self::number_type(&args[0]))
+            }
+            ScalarFunction::Round => (is_numeric(&args[0]) && args[1..].iter().all(is_integer))
+                .then(|| Self::number_type(&args[0])),
+            ScalarFunction::Sqrt | ScalarFunction::Power | ScalarFunction::Ln => {
+                args.iter().all(is_numeric).then_some(DataType::Double)
+            }
+            ScalarFunction::Greatest | ScalarFunction::Least => args
+                .iter()
+                .try_fold(DataType::Null, |res, arg| common_type(res, *arg)),
+            ScalarFunction::DateTrunc => {
+                (is_string(&args[0]) && is_temporal(&args[1])).then_some(DataType::Timestamp)
+            }
+            ScalarFunction::DatePart => {
+                (is_string(&args[0]) && is_temporal(&args[1])).then_some(DataType::Long)
+            }
+            ScalarFunction::Strftime => {
+                (is_temporal(&args[0]) && is_string(&args[1])).then_some(DataType::String)
+            }
+            ScalarFunction::Now => Some(DataType::Timestamp),
+            ScalarFunction::CurrentDate => Some(DataType::Date),
+        }
+    }
+
+    // the type numeric functions compute in, narrow integers are evaluated as ints and decimals as doubles
+    fn number_type(data_type: &DataType) -> DataType {
+        match data_type {
+            DataType::Decimal => DataType::Double,
+            data_type => data_type.arithmetic_type(),
+        }
+    }
+
+    // whether the result can be null given which arguments can be null
+    pub fn nullable(&self, args: &[bool]) -> bool {
+        match self {
+            // null arguments are skipped
+            ScalarFunction::Concat => false,
+            ScalarFunction::Greatest | ScalarFunction::Least => args.iter().all(|arg| *arg),
+            _ => args.iter().any(|arg| *arg),
+        }
+    }
+
+    // evaluates the function for the arguments of one row, most functions are null when any argument is null
+    pub fn evaluate(&self, args: &[Field]) -> Result<Field, Error> {
+        match self {
+            ScalarFunction::Concat => {
+                return Ok(Field::Str(
+                    args.iter()
+                        .filter(|arg| **arg != Field::Null)
+                        .filter_map(Caster::to_string)
+                        .collect(),
+                ))
+            }
+            ScalarFunction::Greatest | ScalarFunction::Least => {
+                let wanted = match self {
+                    ScalarFunction::Greatest => Ordering::Greater,
+                    _ => Ordering::Less,
+                };
+                let mut res = Field::Null;
+                for arg in args.iter().filter(|arg| **arg != Field::Null) {
+                    if res == Field::Null || coercion::compare(arg, &res) == Some(wanted) {
+                        res = arg.clone();
+                    }
+                }
+                return Ok(res);
+            }
+            _ if args.contains(&Field::Null) => return Ok(Field::Null),
+            _ => {}
+        }
+
+        match self {
+            ScalarFunction::Upper => Ok(Field::Str(self.string(&args[0])?.to_uppercase())),
+            ScalarFunction::Lower => Ok(Field::Str(self.string(&args[0])?.to_lowercase())),
+            ScalarFunction::Length => Ok(Field::Int(self.string(&args[0])?.chars().count() as i32)),
+            ScalarFunction::Substr => {
+                let count = match args.get(2) {
+                    Some(count) => Some(self.integer(count)?),
+                    None => None,
+                };
+                self.substr(self.string(&args[0])?, self.integer(&args[1])?, count)
+            }
+            ScalarFunction::Trim | ScalarFunction::LTrim | ScalarFunction::RTrim => {
+                let s = self.string(&args[0])?;
+                let characters = match args.get(1) {
+                    Some(characters) => self.string(characters)?,
+                    None => " ",
+                };
+                let trimmed = match self {
+                    ScalarFunction::LTrim => s.trim_start_matches(|c| characters.contains(c)),
+                    ScalarFunction::RTrim => s.trim_end_matches(|c| characters.contains(c)),
+                    _ => s.trim_matches(|c| characters.contains(c)),
+                };
+                Ok(Field::Str(trimmed.to_string()))
+            }
+            ScalarFunction::Replace => {
+                let s = self.string(&args[0])?;
+                let from = self.string(&args[1])?;
+                // replacing the empty string would insert the replacement between every character
+                if from.is_empty() {
+                    return Ok(Field::Str(s.to_string()));
+                }
+                Ok(Field::Str(s.replace(from, self.string(&args[2])?)))
+            }
+            ScalarFunction::SplitPart => {
+                let s = self.string(&args[0])?;
+                let delimiter = self.string(&args[1])?;
+                let position = self.integer(&args[2])?;
+                let parts: Vec<&str> = if delimiter.is_empty() {
+                    vec![s]
+                } else {
+                    s.split(delimiter).collect()
+                };
+
+                // negative positions count from the last part
+                let index = match position.cmp(&0) {
+                    Ordering::Greater => position - 1,
+                    Ordering::Less => parts.len() as i64 + position,
+                    Ordering::Equal => {
+                        return Err(Error::Expression(
+                            "Field position of split_part must not be zero".to_string(),
+                        ))
+                    }
+                };
+                let part = usize::try_from(index)
+                    .ok()
+                    .and_then(|index| parts.get(index))
+                    .unwrap_or(&"");
+                Ok(Field::Str(part.to_string()))
+            }
+            ScalarFunction::Abs => match self.number(&args[0])? {
+                Field::Int(i) => i
+                    .checked_abs()
+                    .map(Field::Int)
+                    .ok_or_else(|| self.out_of_range()),
+                Field::Long(l) => l
+                    .checked_abs()
+                    .map(Field::Long)
+                    .ok_or_else(|| self.out_of_range()),
+                Field::Float(f) => Ok(Field::Float(f.abs())),
+                Field::Double(d) => Ok(Field::Double(d.abs())),
+                field => Err(self.unsupported(&field)),
+            },
+            ScalarFunction::Round => {
+                let digits = match args.get(1) {
+                    Some(digits) => self.integer(digits)?,
+                    None => 0,
+                };
+                self.round(self.number(&args[0])?, digits)
+            }
+            ScalarFunction::Ceil | ScalarFunction::Floor => {
+                let f = match self {
+                    ScalarFunction::Ceil => f64::ceil,
+                    _ => f64::floor,
+                };
+                match self.number(&args[0])? {
+                    Field::Float(v) => Ok(Field::Float(f(v as f64) as f32)),
+                    Field::Double(v) => Ok(Field::Double(f(v))),
+                    // integers do not have a fraction
+                    field => Ok(field),
+                }
+            }
+            ScalarFunction::Sqrt => {
+                let d = self.double(&args[0])?;
+                if d < 0.0 {
+                    return Err(Error::Expression(
+                        "Cannot take the square root of a negative number".to_string(),
+                    ));
+                }
+                Ok(Field::Double(d.sqrt()))
+            }
+            ScalarFunction::Ln => {
+                let d = self.double(&args[0])?;
+                if d <= 0.0 {
+                    return Err(Error::Expression(
+                        "Cannot take the logarithm of zero or a negative number".to_string(),
+                    ));
+                }
+                Ok(Field::Double(d.ln()))
+            }
+            ScalarFunction::Power => {
+                let res = self.double(&args[0])?.powf(self.double(&args[1])?);
+                if res.is_nan() {
+                    return Err(Error::Expression(
+                        "Cannot raise a negative number to a fractional power".to_string(),
+                    ));
+                }
+                if res.is_infinite() {
+                    return Err(self.out_of_range());
+                }
+                Ok(Field::Double(res))
+            }
+            ScalarFunction::DateTrunc => {
+                let unit = self.string(&args[0])?;
+                datetime::truncate(self.timestamp(&args[1])?, unit)
+                    .map(Field::TimestampMicros)
+                    .ok_or_else(|| self.unsupported_part(unit))
+            }
+            ScalarFunction::DatePart => {
+                let part = self.string(&args[0])?;
+                datetime::date_part(self.timestamp(&args[1])?, part)
+                    .map(Field::Long)
+                    .ok_or_else(|| self.unsupported_part(part))
+            }
+            ScalarFunction::Strftime => {
+                let format = self.string(&args[1])?;
+                datetime::strftime(self.timestamp(&args[0])?, format)
+                    .map(Field::Str)
+                    .ok_or_else(|| {
+                        Error::Expression(format!("Invalid format of strftime: {}", format))
+                    })
+            }
+            ScalarFunction::Now => Ok(Field::TimestampMicros(datetime::now())),
+            ScalarFunction::CurrentDate => {
+                Ok(Field::Date(datetime::timestamp_to_date(datetime::now())))
+            }
+            ScalarFunction::Concat | ScalarFunction::Greatest | ScalarFunction::Least => {
+                unreachable!()
+            }
+        }
+    }
+
+    // the characters from the 1 based start position, positions before the string are counted but not returned
+    fn substr(&self, s: &str, start: i64, count: Option<i64>) -> Result<Field, Error> {
+        let end = match count {
+            Some(count) if count < 0 => {
+                return Err(Error::Expression(
+                    "Length of substr must not be negative".to_string(),
+                ))
+            }
+            Some(count) => start.saturating_add(count),
+            None => i64::MAX,
+        };
+
+        let start = start.max(1);
+        if end <= start {
+            return Ok(Field::Str(String::new()));
+        }
+        Ok(Field::Str(
+            s.chars()
+                .skip((start - 1) as usize)
+                .take((end - start) as usize)
+                .collect(),
+        ))
+    }
+
+    // rounds to the number of digits after the decimal point, negative digits round before the decimal point
+    fn round(&self, field: Field, digits: i64) -> Result<Field, Error> {
+        let digits = digits.clamp(-30, 30) as i32;
+        let round = |d: f64| {
+            let factor = 10f64.powi(digits);
+            (d * factor).round() / factor
+        };
+
+        match field {
+            Field::Float(f) => Ok(Field::Float(round(f as f64) as f32)),
+            Field::Double(d) => Ok(Field::Double(round(d))),
+            field if digits >= 0 => Ok(field),
+            Field::Int(i) => {
+                let d = round(i as f64);
+                (d >= i32::MIN as f64 && d <= i32::MAX as f64)
+                    .then_some(Field::Int(d as i32))
+                    .ok_or_else(|| self.out_of_range())
+            }
+            Field::Long(l) => {
+                let d = round(l as f64);
+                (d >= i64::MIN as f64 && d < i64::MAX as f64)
+                    .then_some(Field::Long(d as i64))
+                    .ok_or_else(|| self.out_of_range())
+            }
+            field => Err(self.unsupported(&field)),
+        }
+    }
+
+    // evaluates a function of one argument for the whole vector at once, none when it is evaluated row by row
+    pub fn evaluate_vector(&self, arg: &Vector) -> Result<Option<Vector>, Error> {
+        let rounding = match self {
+            ScalarFunction::Ceil => f64::ceil,
+            _ => f64::floor,
+        };
+
+        let data = match (self, arg.data()) {
+            (ScalarFunction::Upper, VectorData::Str(values)) => {
+                VectorData::Str(values.iter().map(|s| s.to_uppercase()).collect())
+            }
+            (ScalarFunction::Lower, VectorData::Str(values)) => {
+                VectorData::Str(values.iter().map(|s| s.to_lowercase()).collect())
+            }
+            (ScalarFunction::Length, VectorData::Str(values)) => {
+                VectorData::Int(values.iter().map(|s| s.chars().count() as i32).collect())
+            }
+            (ScalarFunction::Abs, VectorData::Byte(values)) => {
+                VectorData::Int(values.iter().map(|v| (*v as i32).abs()).collect())
+            }
+            (ScalarFunction::Abs, VectorData::Short(values)) => {
+                VectorData::Int(values.iter().map(|v| (*v as i32).abs()).collect())
+            }
+            // the values of nulls are not checked since they can be anything an earlier operation left there
+            (ScalarFunction::Abs, VectorData::Int(values)) => VectorData::Int(
+                values
+                    .iter()
+                    .zip(arg.validity().iter())
+                    .map(|(v, valid)| match valid {
+                        true => v.checked_abs().ok_or_else(|| self.out_of_range()),
+                        false => Ok(0),
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            (ScalarFunction::Abs, VectorData::Long(values)) => VectorData::Long(
+                values
+                    .iter()
+                    .zip(arg.validity().iter())
+                    .map(|(v, valid)| match valid {
+                        true => v.checked_abs().ok_or_else(|| self.out_of_range()),
+                        false => Ok(0),
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            (ScalarFunction::Abs, VectorData::Float(values)) => {
+                VectorData::Float(values.iter().map(|v| v.abs()).collect())
+            }
+            (ScalarFunction::Abs, VectorData::Double(values)) => {
+                VectorData::Double(values.iter().map(|v| v.abs()).collect())
+            }
+            (ScalarFunction::Ceil | ScalarFunction::Floor, VectorData::Float(values)) => {
+                VectorData::Float(values.iter().map(|v| rounding(*v as f64) as f32).collect())
+            }
+            (ScalarFunction::Ceil | ScalarFunction::Floor, VectorData::Double(values)) => {
+                VectorData::Double(values.iter().map(|v| rounding(*v)).collect())
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(Vector::new(data, arg.validity().clone())))
+    }
+
+    fn string<'a>(&self, field: &'a Field) -> Result<&'a str, Error> {
+        match field {
+            Field::Str(s) => Ok(s),
+            field => Err(self.unsupported(field)),
+        }
+    }
+
+    fn integer(&self, field: &Field) -> Result<i64, Error> {
+        match field {
+            Field::Byte(b) => Ok(*b as i64),
+            Field::Short(s) => Ok(*s as i64),
+            Field::Int(i) => Ok(*i as i64),
+            Field::Long(l) => Ok(*l),
+            field => Err(self.unsupported(field)),
+        }
+    }
+
+    fn number(&self, field: &Field) -> Result<Field, Error> {
+        coercion::cast_numeric(
+            field,
+            ScalarFunction::number_type(&DataType::of_field(field)),
+        )
+        .ok_or_else(|| self.unsupported(field))
+    }
+
+    fn double(&self, field: &Field) -> Result<f64, Error> {
+        coercion::as_double(field).ok_or_else(|| self.unsupported(field))
+    }
+
+    fn timestamp(&self, field: &Field) -> Result<i64, Error> {
+        datetime::to_timestamp(field).ok_or_else(|| self.unsupported(field))
+    }
+
+    fn unsupported_part(&self, part: &str) -> Error {
+        Error::Expression(format!("Unsupported date part: {}", part))
+    }
+
+    fn unsupported(&self, field: &Field) -> Error {
+        Error::Expression(format!(
+            "Function {} does not support the argument {}",
+            self.name(),
+            field
+        ))
+    }
+
+    fn out_of_range(&self) -> Error {
+        Error::Expression(format!("Result of {} is out of range", self.name()))
+    }
+}
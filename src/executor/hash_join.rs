@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+
+use sqlparser::ast::Expr;
+
+use crate::{
+    planner::{
+        binder::{infer_type, join_predicate_schema},
+        JoinType, OutputSchema,
+    },
+    types::{coercion, data_type::DataType, error::Error, vector::Vector, Chunk, KeyValue},
+};
+
+use super::{
+    collect_chunks, expression::PhysicalExpr, nested_join::null_chunk, Buffer, Executor,
+    VECTOR_SIZE_THRESHOLD,
+};
+
+type JoinKey = Vec<KeyValue>;
+
+pub struct HashJoin {
+    output_schema: OutputSchema,
+    left_keys: Vec<PhysicalExpr>,
+    right_keys: Vec<PhysicalExpr>,
+    // the type each key is converted to before it is hashed so an int and a double with the same value match
+    left_key_types: Vec<Option<DataType>>,
+    right_key_types: Vec<Option<DataType>>,
+    predicate: Option<PhysicalExpr>,
+    join_type: JoinType,
+    // whether the hash table is built from the left child instead of the right child
+    build_left: bool,
+    child_left: Box<dyn Executor>,
+    child_right: Box<dyn Executor>,
+
+    buffer: Buffer,
+    build_chunk: Chunk,
+    // tracks which build rows found a match so the unmatched ones can be emitted for outer joins
+    build_matched: Vec<bool>,
+    hash_table: Option<HashMap<JoinKey, Vec<usize>>>,
+    probe_done: bool,
+}
+
+impl HashJoin {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        child_left: Box<dyn Executor>,
+        child_right: Box<dyn Executor>,
+        left_keys: Vec<Expr>,
+        right_keys: Vec<Expr>,
+        predicate: Option<Expr>,
+        join_type: JoinType,
+        build_left: bool,
+        output_schema: OutputSchema,
+    ) -> Result<Box<HashJoin>, Error> {
+        let left_output_schema = child_left.get_output_schema();
+        let right_output_schema = child_right.get_output_schema();
+
+        // the left rows of a semi or anti join are probed so each of them is returned at most once
+        if build_left && join_type.outputs_left_only() {
+            return Err(Error::Execution(
+                "Semi and anti joins must build the hash table on the right side".to_string(),
+            ));
+        }
+
+        let predicate_schema = join_predicate_schema(
+            &left_output_schema,
+            &right_output_schema,
+            &output_schema,
+            join_type,
+        );
+
+        let (left_key_types, right_key_types) = Self::key_types(
+            &left_keys,
+            &right_keys,
+            &left_output_schema,
+            &right_output_schema,
+        );
+
+        Ok(Box::new(HashJoin {
+            buffer: Buffer::new(),
+            build_chunk: Chunk::new(),
+            build_matched: Vec::new(),
+            hash_table: None,
+            probe_done: false,
+            left_keys: Self::compile_keys(&left_keys, &left_output_schema)?,
+            right_keys: Self::compile_keys(&right_keys, &right_output_schema)?,
+            left_key_types,
+            right_key_types,
+            predicate: predicate
+                .map(|predicate| PhysicalExpr::compile(&predicate, &predicate_schema))
+                .transpose()?,
+            join_type,
+            build_left,
+            child_left,
+            child_right,
+            output_schema,
+        }))
+    }
+
+    fn init_hash_table(&mut self) -> Result<(), Error> {
+        // TODO: consider when the build side is too large to fit in memory
+        if self.hash_table.is_some() {
+            return Ok(());
+        }
+
+        let (child, keys, key_types) = if self.build_left {
+            (&mut self.child_left, &self.left_keys, &self.left_key_types)
+        } else {
+            (
+                &mut self.child_right,
+                &self.right_keys,
+                &self.right_key_types,
+            )
+        };
+
+        let build_chunk = collect_chunks(child.as_mut())?;
+        let key_vectors = Self::evaluate_keys(keys, key_types, &build_chunk)?;
+
+        let mut hash_table: HashMap<JoinKey, Vec<usize>> = HashMap::new();
+        for i in 0..build_chunk.size() {
+            // rows with null keys never match anything but are still kept for outer joins
+            if let Some(key) = Self::get_key(&key_vectors, i) {
+                hash_table.entry(key).or_default().push(i);
+            }
+        }
+
+        self.build_matched = vec![false; build_chunk.size()];
+        self.build_chunk = build_chunk;
+        self.hash_table = Some(hash_table);
+        Ok(())
+    }
+
+    fn compile_keys(
+        keys: &[Expr],
+        output_schema: &OutputSchema,
+    ) -> Result<Vec<PhysicalExpr>, Error> {
+        keys.iter()
+            .map(|key| PhysicalExpr::compile(key, output_schema))
+            .collect()
+    }
+
+    // the types the keys of each side are converted to, none for the keys that already have the type of the other side
+    fn key_types(
+        left_keys: &[Expr],
+        right_keys: &[Expr],
+        left_output_schema: &OutputSchema,
+        right_output_schema: &OutputSchema,
+    ) -> (Vec<Option<DataType>>, Vec<Option<DataType>>) {
+        left_keys
+            .iter()
+            .zip(right_keys)
+            .map(|(left, right)| {
+                let (left_type, _) = infer_type(left, left_output_schema);
+                let (right_type, _) = infer_type(right, right_output_schema);
+                match coercion::promoted_type(left_type, right_type) {
+                    Some(data_type) => (
+                        (left_type != data_type).then_some(data_type),
+                        (right_type != data_type).then_some(data_type),
+                    ),
+                    None => (None, None),
+                }
+            })
+            .unzip()
+    }
+
+    fn evaluate_keys(
+        keys: &[PhysicalExpr],
+        key_types: &[Option<DataType>],
+        chunk: &Chunk,
+    ) -> Result<Vec<Vector>, Error> {
+        keys.iter()
+            .zip(key_types)
+            .map(|(key, key_type)| {
+                let vector = key.evaluate(chunk)?;
+                Ok(match key_type {
+                    Some(data_type) => Vector::from_fields(
+                        vector
+                            .to_fields()
+                            .into_iter()
+                            .map(|field| coercion::coerce(&field, *data_type).unwrap_or(field))
+                            .collect(),
+                    ),
+                    None => vector,
+                })
+            })
+            .collect()
+    }
+
+    fn get_key(key_vectors: &[Vector], i: usize) -> Option<JoinKey> {
+        let mut key = JoinKey::new();
+
+        for vector in key_vectors {
+            if !vector.is_valid(i) {
+                return None;
+            }
+            key.push(KeyValue::from(&vector.get(i)));
+        }
+
+        Some(key)
+    }
+
+    // the output always has the left columns followed by the right columns
+    fn combine(&self, build_chunk: Chunk, probe_chunk: Chunk) -> Chunk {
+        if self.build_left {
+            build_chunk.concat_columns(probe_chunk)
+        } else {
+            probe_chunk.concat_columns(build_chunk)
+        }
+    }
+
+    fn preserves_build(&self) -> bool {
+        if self.build_left {
+            self.join_type.preserves_left()
+        } else {
+            self.join_type.preserves_right()
+        }
+    }
+
+    fn preserves_probe(&self) -> bool {
+        if self.build_left {
+            self.join_type.preserves_right()
+        } else {
+            self.join_type.preserves_left()
+        }
+    }
+}
+
+impl Executor for HashJoin {
+    fn next_chunk(&mut self) -> Result<Chunk, Error> {
+        self.init_hash_table()?;
+
+        let probe_output_schema = if self.build_left {
+            self.child_right.get_output_schema()
+        } else {
+            self.child_left.get_output_schema()
+        };
+
+        while !self.probe_done && self.buffer.size() < VECTOR_SIZE_THRESHOLD {
+            let next_chunk = if self.build_left {
+                self.child_right.next_chunk()?
+            } else {
+                self.child_left.next_chunk()?
+            };
+
+            if next_chunk.is_empty() {
+                self.probe_done = true;
+
+                // the build rows that never matched are padded with nulls for the probe columns
+                if self.preserves_build() {
+                    let unmatched: Vec<bool> = self.build_matched.iter().map(|m| !m).collect();
+                    let build_rows = self.build_chunk.filter(&unmatched);
+                    let len = build_rows.size();
+                    let chunk = self.combine(
+                        build_rows,
+                        null_chunk(probe_output_schema.columns.len(), len),
+                    );
+                    self.buffer.add_chunk(&chunk);
+                }
+                break;
+            }
+
+            let (probe_keys, probe_key_types) = if self.build_left {
+                (&self.right_keys, &self.right_key_types)
+            } else {
+                (&self.left_keys, &self.left_key_types)
+            };
+            let key_vectors = Self::evaluate_keys(probe_keys, probe_key_types, &next_chunk)?;
+
+            // the pairs of build and probe rows with equal keys
+            let mut build_indexes = Vec::new();
+            let mut probe_indexes = Vec::new();
+            for i in 0..next_chunk.size() {
+                let Some(key) = Self::get_key(&key_vectors, i) else {
+                    continue;
+                };
+                for build_index in self
+                    .hash_table
+                    .as_ref()
+                    .unwrap()
+                    .get(&key)
+                    .into_iter()
+                    .flatten()
+                {
+                    build_indexes.push(*build_index);
+                    probe_indexes.push(i);
+                }
+            }
+
+            let mask = match &self.predicate {
+                Some(predicate) => {
+                    let candidates = self.combine(
+                        self.build_chunk.take(&build_indexes),
+                        next_chunk.take(&probe_indexes),
+                    );
+                    predicate.evaluate_mask(&candidates)?
+                }
+                None => vec![true; build_indexes.len()],
+            };
+
+            // the probe row is padded with nulls for the build columns if it never matched
+            let mut output_build_indexes = Vec::new();
+            let mut output_probe_indexes = Vec::new();
+            let mut pair = 0;
+            for i in 0..next_chunk.size() {
+                let mut probe_matched = false;
+                while pair < probe_indexes.len() && probe_indexes[pair] == i {
+                    if mask[pair] && !self.join_type.outputs_left_only() {
+                        self.build_matched[build_indexes[pair]] = true;
+                        output_build_indexes.push(Some(build_indexes[pair]));
+                        output_probe_indexes.push(i);
+                    }
+                    probe_matched |= mask[pair];
+                    pair += 1;
+                }
+
+                // a semi or anti join returns the probe row once depending on whether it matched
+                if self.join_type.outputs_left_only() {
+                    if probe_matched == (self.join_type == JoinType::Semi) {
+                        output_probe_indexes.push(i);
+                    }
+                } else if !probe_matched && self.preserves_probe() {
+                    output_build_indexes.push(None);
+                    output_probe_indexes.push(i);
+                }
+            }
+
+            // a semi or anti join returns the probe rows without the build columns
+            if self.join_type.outputs_left_only() {
+                self.buffer
+                    .add_chunk(&next_chunk.take(&output_probe_indexes));
+                continue;
+            }
+
+            let chunk = self.combine(
+                self.build_chunk.take_optional(&output_build_indexes),
+                next_chunk.take(&output_probe_indexes),
+            );
+            self.buffer.add_chunk(&chunk);
+        }
+
+        Ok(self.buffer.get_sized_chunk(VECTOR_SIZE_THRESHOLD))
+    }
+
+    fn get_output_schema(&self) -> OutputSchema {
+        self.output_schema.clone()
+    }
+}
@@ -7,7 +7,8 @@ use super::{Buffer, VECTOR_SIZE_THRESHOLD};
 
 pub struct Limit {
     output_schema: OutputSchema,
-    limit: u64,
+    limit: Option<u64>,
+    offset: u64,
     child: Box<dyn Executor>,
 
     buffer: Buffer,
@@ -16,11 +17,13 @@ pub struct Limit {
 impl Limit {
     pub fn new(
         child: Box<dyn Executor>,
-        limit: u64,
+        limit: Option<u64>,
+        offset: u64,
         output_schema: OutputSchema,
     ) -> Result<Box<Limit>, Error> {
         Ok(Box::new(Limit {
             limit,
+            offset,
             child,
             output_schema,
             buffer: Buffer::new(),
@@ -30,8 +33,9 @@ impl Limit {
 
 impl Executor for Limit {
     fn next_chunk(&mut self) -> Result<Chunk, Error> {
-        if self.limit == 0 {
-            return Ok(Chunk::default());
+        // once the limit is reached we only need to drain what is buffered
+        if self.limit == Some(0) {
+            return Ok(self.buffer.get_sized_chunk(VECTOR_SIZE_THRESHOLD));
         }
 
         while self.buffer.size() < VECTOR_SIZE_THRESHOLD {
@@ -41,14 +45,19 @@ impl Executor for Limit {
                 break;
             }
 
-            for row in next_chunk.get_rows().iter() {
-                self.buffer.add_row(row.clone());
-                self.limit -= 1;
-                if self.limit == 0 {
-                    break;
-                }
+            // rows before the offset are skipped
+            let skipped = next_chunk.size().min(self.offset as usize);
+            self.offset -= skipped as u64;
+
+            let mut end = next_chunk.size();
+            if let Some(limit) = self.limit.as_mut() {
+                end = end.min(skipped + *limit as usize);
+                *limit -= (end - skipped) as u64;
             }
-            if self.limit == 0 {
+
+            self.buffer.add_chunk(&next_chunk.slice(skipped, end));
+
+            if self.limit == Some(0) {
                 break;
             }
         }
@@ -1,22 +1,47 @@
 mod aggregation;
+mod apply;
+mod cte;
+mod distinct;
 mod empty;
-mod expression;
+pub mod expression;
 mod filter;
+pub mod function;
+mod hash_join;
 mod limit;
 mod nested_join;
+mod pattern;
 mod projection;
 mod scan;
+mod set_operation;
+mod sort;
+mod subquery_alias;
+mod work_table;
 
-use std::cmp;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
 use crate::{
     planner::{Node, OutputSchema, Plan, PlanNode},
-    types::{error::Error, Chunk, ResultSet, Row},
+    types::{coercion, error::Error, vector::Vector, Chunk, KeyValue, ResultSet, Row},
 };
 
 use self::{
-    aggregation::Aggregation, empty::Empty, filter::Filter, limit::Limit,
-    nested_join::NestedLoopJoin, projection::Projection, scan::Scan,
+    aggregation::Aggregation,
+    apply::Apply,
+    cte::{MaterializedCte, RecursiveCte},
+    distinct::Distinct,
+    empty::Empty,
+    filter::Filter,
+    hash_join::HashJoin,
+    limit::Limit,
+    nested_join::NestedLoopJoin,
+    projection::Projection,
+    scan::Scan,
+    set_operation::SetOperation,
+    sort::Sort,
+    subquery_alias::SubqueryAlias,
+    work_table::{WorkTable, WorkTableScan},
 };
 
 const VECTOR_SIZE_THRESHOLD: usize = 1024;
@@ -26,23 +51,45 @@ pub trait Executor {
     fn next_chunk(&mut self) -> Result<Chunk, Error>;
 }
 
-struct ExecutorBuilder {}
+// work tables are shared between the executor that fills them and the executors that read them
+#[derive(Clone)]
+struct ExecutorBuilder {
+    metrics: Arc<ExecutionMetrics>,
+    work_tables: HashMap<usize, WorkTable>,
+}
 
 impl ExecutorBuilder {
-    fn build_from_plan(plan: Plan) -> Result<Box<dyn Executor>, Error> {
-        return Self::build(plan.root);
+    fn new(metrics: Arc<ExecutionMetrics>) -> ExecutorBuilder {
+        ExecutorBuilder {
+            metrics,
+            work_tables: HashMap::new(),
+        }
+    }
+
+    fn build_from_plan(&mut self, plan: Plan) -> Result<Box<dyn Executor>, Error> {
+        self.build(plan.root)
     }
 
-    fn build(plan_node: PlanNode) -> Result<Box<dyn Executor>, Error> {
+    fn build(&mut self, plan_node: PlanNode) -> Result<Box<dyn Executor>, Error> {
         match plan_node.node {
-            Node::Scan { table_name, filter } => {
-                match Scan::new(table_name, filter, plan_node.output_schema.clone()) {
+            Node::Scan {
+                table_name,
+                filter,
+                projection,
+            } => {
+                match Scan::new(
+                    table_name,
+                    filter,
+                    projection,
+                    plan_node.output_schema.clone(),
+                    &self.metrics,
+                ) {
                     Ok(e) => Ok(e),
                     Err(e) => Err(e),
                 }
             }
             Node::Filter { filter, child } => {
-                let child = Self::build(*child)?;
+                let child = self.build(*child)?;
 
                 match Filter::new(child, filter, plan_node.output_schema) {
                     Ok(e) => Ok(e),
@@ -50,7 +97,7 @@ impl ExecutorBuilder {
                 }
             }
             Node::Projection { select, child } => {
-                let child = Self::build(*child)?;
+                let child = self.build(*child)?;
 
                 match Projection::new(child, select, plan_node.output_schema) {
                     Ok(e) => Ok(e),
@@ -65,27 +112,55 @@ impl ExecutorBuilder {
                 child_left,
                 child_right,
                 predicate,
+                join_type,
             } => {
-                let child_left = Self::build(*child_left)?;
-                let child_right = Self::build(*child_right)?;
+                let child_left = self.build(*child_left)?;
+                let child_right = self.build(*child_right)?;
 
                 match NestedLoopJoin::new(
                     child_left,
                     child_right,
                     predicate,
+                    join_type,
                     plan_node.output_schema.clone(),
                 ) {
                     Ok(e) => Ok(e),
                     Err(e) => Err(e),
                 }
             }
+            Node::HashJoin {
+                child_left,
+                child_right,
+                left_keys,
+                right_keys,
+                predicate,
+                join_type,
+                build_left,
+            } => {
+                let child_left = self.build(*child_left)?;
+                let child_right = self.build(*child_right)?;
+
+                match HashJoin::new(
+                    child_left,
+                    child_right,
+                    left_keys,
+                    right_keys,
+                    predicate,
+                    join_type,
+                    build_left,
+                    plan_node.output_schema,
+                ) {
+                    Ok(e) => Ok(e),
+                    Err(e) => Err(e),
+                }
+            }
             Node::Aggregate {
                 child,
                 aggregates,
                 group_by,
                 non_aggregates,
             } => {
-                let child = Self::build(*child)?;
+                let child = self.build(*child)?;
 
                 match Aggregation::new(
                     child,
@@ -98,10 +173,124 @@ impl ExecutorBuilder {
                     Err(e) => Err(e),
                 }
             }
-            Node::Limit { limit, child } => {
-                let child = Self::build(*child)?;
+            Node::Sort { order_by, child } => {
+                let child = self.build(*child)?;
+
+                match Sort::new(child, order_by, plan_node.output_schema) {
+                    Ok(e) => Ok(e),
+                    Err(e) => Err(e),
+                }
+            }
+            Node::Distinct { on, child } => {
+                let child = self.build(*child)?;
+
+                match Distinct::new(child, on, plan_node.output_schema) {
+                    Ok(e) => Ok(e),
+                    Err(e) => Err(e),
+                }
+            }
+            Node::SetOperation {
+                op,
+                all,
+                left,
+                right,
+            } => {
+                let left = self.build(*left)?;
+                let right = self.build(*right)?;
+
+                match SetOperation::new(op, all, left, right, plan_node.output_schema) {
+                    Ok(e) => Ok(e),
+                    Err(e) => Err(e),
+                }
+            }
+            Node::Limit {
+                limit,
+                offset,
+                child,
+            } => {
+                let child = self.build(*child)?;
+
+                match Limit::new(child, limit, offset, plan_node.output_schema) {
+                    Ok(e) => Ok(e),
+                    Err(e) => Err(e),
+                }
+            }
+            Node::MaterializedCte { id, cte, child } => {
+                let cte = self.build(*cte)?;
+                let work_table = WorkTable::default();
+                self.work_tables.insert(id, work_table.clone());
+                let child = self.build(*child)?;
+
+                match MaterializedCte::new(cte, work_table, child, plan_node.output_schema) {
+                    Ok(e) => Ok(e),
+                    Err(e) => Err(e),
+                }
+            }
+            Node::RecursiveCte {
+                id,
+                all,
+                anchor,
+                recursive,
+            } => {
+                let anchor = self.build(*anchor)?;
+                let work_table = WorkTable::default();
+                self.work_tables.insert(id, work_table.clone());
+
+                // the recursive term is built for every iteration so it keeps its own builder
+                match RecursiveCte::new(
+                    all,
+                    anchor,
+                    *recursive,
+                    self.clone(),
+                    work_table,
+                    plan_node.output_schema,
+                ) {
+                    Ok(e) => Ok(e),
+                    Err(e) => Err(e),
+                }
+            }
+            Node::WorkTableScan { id } => {
+                let Some(work_table) = self.work_tables.get(&id) else {
+                    return Err(Error::Execution(format!("Work table {} not found", id)));
+                };
+
+                match WorkTableScan::new(work_table.clone(), plan_node.output_schema) {
+                    Ok(e) => Ok(e),
+                    Err(e) => Err(e),
+                }
+            }
+            Node::Apply {
+                kind,
+                correlated,
+                child,
+                subquery,
+            } => {
+                let child = self.build(*child)?;
+
+                // a correlated subquery reads the row it runs for from a work table
+                let outer_row = correlated.map(|id| {
+                    let work_table = WorkTable::default();
+                    self.work_tables.insert(id, work_table.clone());
+                    work_table
+                });
+
+                // the subquery is built when it runs so it keeps its own builder
+                match Apply::new(
+                    child,
+                    kind,
+                    *subquery,
+                    self.clone(),
+                    outer_row,
+                    plan_node.output_schema,
+                ) {
+                    Ok(e) => Ok(e),
+                    Err(e) => Err(e),
+                }
+            }
+            Node::SubqueryAlias { child, .. } => {
+                let child = self.build(*child)?;
 
-                match Limit::new(child, limit, plan_node.output_schema) {
+                match SubqueryAlias::new(child, plan_node.output_schema) {
                     Ok(e) => Ok(e),
                     Err(e) => Err(e),
                 }
@@ -110,15 +299,39 @@ impl ExecutorBuilder {
     }
 }
 
-pub struct ExecutionEngine {}
+// counters that are updated by the executors across all the queries that are run
+#[derive(Default)]
+pub struct ExecutionMetrics {
+    row_groups_pruned: AtomicU64,
+}
+
+impl ExecutionMetrics {
+    pub fn add_row_groups_pruned(&self, count: u64) {
+        self.row_groups_pruned.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn row_groups_pruned(&self) -> u64 {
+        self.row_groups_pruned.load(Ordering::Relaxed)
+    }
+}
+
+pub struct ExecutionEngine {
+    metrics: Arc<ExecutionMetrics>,
+}
 
 impl ExecutionEngine {
     pub fn new() -> ExecutionEngine {
-        ExecutionEngine {}
+        ExecutionEngine {
+            metrics: Arc::new(ExecutionMetrics::default()),
+        }
+    }
+
+    pub fn metrics(&self) -> &ExecutionMetrics {
+        &self.metrics
     }
 
     pub fn execute(&self, plan: Plan) -> Result<ResultSet, Error> {
-        let mut executor = ExecutorBuilder::build_from_plan(plan)?;
+        let mut executor = ExecutorBuilder::new(self.metrics.clone()).build_from_plan(plan)?;
         let mut result = ResultSet::new(executor.get_output_schema());
 
         loop {
@@ -136,31 +349,86 @@ impl ExecutionEngine {
 }
 
 struct Buffer {
-    rows: Vec<Row>,
+    chunk: Chunk,
 }
 
 impl Buffer {
     fn new() -> Buffer {
-        Buffer { rows: Vec::new() }
+        Buffer {
+            chunk: Chunk::new(),
+        }
     }
 
     fn add_row(&mut self, row: Row) {
-        self.rows.push(row);
+        self.chunk.add_row(row);
+    }
+
+    fn add_chunk(&mut self, chunk: &Chunk) {
+        if !chunk.is_empty() {
+            self.chunk.append(chunk);
+        }
     }
 
     fn size(&self) -> usize {
-        self.rows.len()
+        self.chunk.size()
     }
 
     pub fn get_sized_chunk(&mut self, size: usize) -> Chunk {
-        let mut res = Chunk::new();
+        if size >= self.size() {
+            return std::mem::take(&mut self.chunk);
+        }
 
-        let n = cmp::min(size, self.size());
+        let res = self.chunk.slice(0, size);
+        self.chunk = self.chunk.slice(size, self.size());
+        res
+    }
+}
 
-        self.rows.drain(0..n).for_each(|row| {
-            res.add_row(row);
-        });
+// the values of a row as keys so rows can be compared in hash maps, nulls are equal to each other
+type RowKey = Vec<KeyValue>;
 
-        res
+fn row_key(vectors: &[Vector], row: usize) -> RowKey {
+    vectors
+        .iter()
+        .map(|vector| KeyValue::from(&vector.get(row)))
+        .collect()
+}
+
+// reads every chunk of the child into a single chunk that has a column for each output column even when there are no rows
+fn collect_chunks(child: &mut dyn Executor) -> Result<Chunk, Error> {
+    let width = child.get_output_schema().columns.len();
+    let mut res = Chunk::from_columns(vec![Vector::default(); width], 0);
+
+    loop {
+        let chunk = child.next_chunk()?;
+        if chunk.is_empty() {
+            break;
+        }
+        res.append(&chunk);
     }
+
+    Ok(res)
+}
+
+// converts the values of a chunk to the types of the output such as ints to longs
+fn coerce_chunk(chunk: Chunk, input_schema: &OutputSchema, output_schema: &OutputSchema) -> Chunk {
+    let columns = chunk
+        .columns()
+        .iter()
+        .zip(input_schema.columns.iter())
+        .zip(output_schema.columns.iter())
+        .map(|((vector, input), output)| {
+            if input.data_type == output.data_type {
+                return vector.clone();
+            }
+            Vector::from_fields(
+                (0..vector.len())
+                    .map(|i| vector.get(i))
+                    .map(|field| coercion::coerce(&field, output.data_type).unwrap_or(field))
+                    .collect(),
+            )
+        })
+        .collect();
+
+    Chunk::from_columns(columns, chunk.size())
 }
@@ -1,20 +1,24 @@
 use sqlparser::ast::Expr;
 
 use crate::{
-    planner::OutputSchema,
-    types::{error::Error, Chunk, Row},
+    planner::{binder::join_predicate_schema, JoinType, OutputSchema},
+    types::{error::Error, field::Field, vector::Vector, Chunk},
 };
 
-use super::{expression::ExprEvaluator, Buffer, Executor, VECTOR_SIZE_THRESHOLD};
+use super::{collect_chunks, expression::PhysicalExpr, Buffer, Executor, VECTOR_SIZE_THRESHOLD};
 
 pub struct NestedLoopJoin {
     output_schema: OutputSchema,
-    predicate: Option<Expr>,
+    predicate: Option<PhysicalExpr>,
+    join_type: JoinType,
     child_left: Box<dyn Executor>,
     child_right: Box<dyn Executor>,
 
     buffer: Buffer,
-    right_rows: Option<Vec<Row>>,
+    right_chunk: Option<Chunk>,
+    // tracks which right rows found a match so the unmatched ones can be emitted for right and full joins
+    right_matched: Vec<bool>,
+    left_done: bool,
 }
 
 impl NestedLoopJoin {
@@ -22,32 +26,37 @@ impl NestedLoopJoin {
         child_left: Box<dyn Executor>,
         child_right: Box<dyn Executor>,
         predicate: Option<Expr>,
+        join_type: JoinType,
         output_schema: OutputSchema,
     ) -> Result<Box<NestedLoopJoin>, Error> {
+        let predicate_schema = join_predicate_schema(
+            &child_left.get_output_schema(),
+            &child_right.get_output_schema(),
+            &output_schema,
+            join_type,
+        );
+
         Ok(Box::new(NestedLoopJoin {
             buffer: Buffer::new(),
-            right_rows: None,
-            predicate,
+            right_chunk: None,
+            right_matched: Vec::new(),
+            left_done: false,
+            predicate: predicate
+                .map(|predicate| PhysicalExpr::compile(&predicate, &predicate_schema))
+                .transpose()?,
+            join_type,
             child_left,
             child_right,
             output_schema,
         }))
     }
 
-    fn init_right_rows(&mut self) -> Result<(), Error> {
+    fn init_right_chunk(&mut self) -> Result<(), Error> {
         // TODO: consider when right rows is too large to fit in memory
-        let mut res = Vec::new();
-        if self.right_rows.is_none() {
-            loop {
-                let chunk = self.child_right.next_chunk()?;
-                if chunk.is_empty() {
-                    break;
-                }
-                for row in chunk.get_rows() {
-                    res.push(row.clone())
-                }
-            }
-            self.right_rows = Some(res);
+        if self.right_chunk.is_none() {
+            let chunk = collect_chunks(self.child_right.as_mut())?;
+            self.right_matched = vec![false; chunk.size()];
+            self.right_chunk = Some(chunk);
         }
         Ok(())
     }
@@ -55,34 +64,66 @@ impl NestedLoopJoin {
 
 impl Executor for NestedLoopJoin {
     fn next_chunk(&mut self) -> Result<Chunk, Error> {
-        self.init_right_rows()?;
+        self.init_right_chunk()?;
+        let right_chunk = self.right_chunk.as_ref().unwrap();
 
-        while self.buffer.size() < VECTOR_SIZE_THRESHOLD {
+        while !self.left_done && self.buffer.size() < VECTOR_SIZE_THRESHOLD {
             let next_chunk = self.child_left.next_chunk()?;
 
             if next_chunk.is_empty() {
+                self.left_done = true;
+
+                // the right rows that never matched are padded with nulls for the left columns
+                if self.join_type.preserves_right() {
+                    let unmatched: Vec<bool> = self.right_matched.iter().map(|m| !m).collect();
+                    let right_rows = right_chunk.filter(&unmatched);
+                    let left_width = self.child_left.get_output_schema().columns.len();
+                    self.buffer.add_chunk(
+                        &null_chunk(left_width, right_rows.size()).concat_columns(right_rows),
+                    );
+                }
                 break;
             }
 
-            for left_row in next_chunk.get_rows() {
-                for right_row in self.right_rows.as_ref().unwrap().iter() {
-                    let mut new_row = left_row.clone();
-                    new_row.append(&mut right_row.clone());
-
-                    if self.predicate.is_some() {
-                        let e = ExprEvaluator::evaluate(
-                            self.predicate.as_ref().unwrap(),
-                            &new_row,
-                            &self.output_schema,
-                        )?;
-                        if !ExprEvaluator::to_boolean(&e) {
-                            continue;
-                        }
+            // the left rows a semi or anti join returns
+            let mut left_rows = Vec::new();
+
+            for i in 0..next_chunk.size() {
+                // the left row is repeated for every right row so the predicate is evaluated once for all of them
+                let candidates = next_chunk
+                    .take(&vec![i; right_chunk.size()])
+                    .concat_columns(right_chunk.clone());
+
+                let mask = match &self.predicate {
+                    Some(predicate) => predicate.evaluate_mask(&candidates)?,
+                    None => vec![true; candidates.size()],
+                };
+
+                if self.join_type.outputs_left_only() {
+                    if mask.contains(&true) == (self.join_type == JoinType::Semi) {
+                        left_rows.push(i);
                     }
+                    continue;
+                }
+
+                for (matched, passed) in self.right_matched.iter_mut().zip(mask.iter()) {
+                    *matched |= *passed;
+                }
+                self.buffer.add_chunk(&candidates.filter(&mask));
 
-                    self.buffer.add_row(new_row);
+                // the left row is padded with nulls for the right columns if it never matched
+                if !mask.contains(&true) && self.join_type.preserves_left() {
+                    self.buffer.add_chunk(
+                        &next_chunk
+                            .slice(i, i + 1)
+                            .concat_columns(null_chunk(right_chunk.columns().len(), 1)),
+                    );
                 }
             }
+
+            if self.join_type.outputs_left_only() {
+                self.buffer.add_chunk(&next_chunk.take(&left_rows));
+            }
         }
 
         Ok(self.buffer.get_sized_chunk(VECTOR_SIZE_THRESHOLD))
@@ -91,3 +132,8 @@ impl Executor for NestedLoopJoin {
         self.output_schema.clone()
     }
 }
+
+// a chunk where every value is null
+pub fn null_chunk(width: usize, len: usize) -> Chunk {
+    Chunk::from_columns(vec![Vector::from_field(&Field::Null, len); width], len)
+}
@@ -0,0 +1,153 @@
+use regex::{Regex, RegexBuilder};
+
+use crate::types::error::Error;
+
+// the escape character of LIKE and SIMILAR TO when the query does not set one
+const DEFAULT_ESCAPE_CHAR: char = '\\';
+
+// how a pattern is matched against a string, every kind is translated to a regular expression
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PatternKind {
+    // % matches any sequence of characters and _ matches a single character
+    Like {
+        case_insensitive: bool,
+        escape_char: Option<char>,
+    },
+    // like LIKE but also supports the | * + ? {} () [] operators of regular expressions
+    SimilarTo {
+        escape_char: Option<char>,
+    },
+    // a regular expression that can match any part of the string
+    Regex {
+        case_insensitive: bool,
+    },
+}
+
+impl PatternKind {
+    pub fn compile(&self, pattern: &str) -> Result<Regex, Error> {
+        let (regex, case_insensitive) = match self {
+            PatternKind::Like {
+                case_insensitive,
+                escape_char,
+            } => (
+                Self::translate_like(pattern, escape_char.unwrap_or(DEFAULT_ESCAPE_CHAR))?,
+                *case_insensitive,
+            ),
+            PatternKind::SimilarTo { escape_char } => (
+                Self::translate_similar_to(pattern, escape_char.unwrap_or(DEFAULT_ESCAPE_CHAR))?,
+                false,
+            ),
+            PatternKind::Regex { case_insensitive } => (pattern.to_string(), *case_insensitive),
+        };
+
+        RegexBuilder::new(&regex)
+            .case_insensitive(case_insensitive)
+            .build()
+            .map_err(|e| {
+                // the last line of a syntax error describes the problem
+                let e = e.to_string();
+                let reason = e.lines().last().unwrap_or_default();
+                Error::Expression(format!(
+                    "Invalid pattern {}: {}",
+                    pattern,
+                    reason.trim_start_matches("error: ")
+                ))
+            })
+    }
+
+    // the whole string has to match, . also matches new lines
+    fn translate_like(pattern: &str, escape_char: char) -> Result<String, Error> {
+        let mut regex = String::from("^(?s:");
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                c if c == escape_char => {
+                    regex.push_str(&regex::escape(&Self::escaped(&mut chars, pattern)?))
+                }
+                '%' => regex.push_str(".*"),
+                '_' => regex.push('.'),
+                c => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        regex.push_str(")$");
+        Ok(regex)
+    }
+
+    fn translate_similar_to(pattern: &str, escape_char: char) -> Result<String, Error> {
+        let mut regex = String::from("^(?s:");
+        let mut chars = pattern.chars();
+        let mut in_brackets = false;
+        while let Some(c) = chars.next() {
+            match c {
+                c if c == escape_char => {
+                    regex.push_str(&regex::escape(&Self::escaped(&mut chars, pattern)?))
+                }
+                // the characters of a bracket expression are used as they are
+                c if in_brackets => {
+                    in_brackets = c != ']';
+                    regex.push(c);
+                }
+                '[' => {
+                    in_brackets = true;
+                    regex.push(c);
+                }
+                '%' => regex.push_str(".*"),
+                '_' => regex.push('.'),
+                '|' | '*' | '+' | '?' | '{' | '}' | '(' | ')' => regex.push(c),
+                c => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        regex.push_str(")$");
+        Ok(regex)
+    }
+
+    // the character after an escape character is matched literally
+    fn escaped(chars: &mut std::str::Chars, pattern: &str) -> Result<String, Error> {
+        match chars.next() {
+            Some(c) => Ok(c.to_string()),
+            None => Err(Error::Expression(format!(
+                "Pattern {} must not end with the escape character",
+                pattern
+            ))),
+        }
+    }
+}
+
+// the flags of the regexp functions, i makes the match case insensitive and g replaces every match instead of the first
+pub fn parse_regex_flags(flags: &str) -> Result<(bool, bool), Error> {
+    let mut case_insensitive = false;
+    let mut global = false;
+    for flag in flags.chars() {
+        match flag {
+            'i' => case_insensitive = true,
+            'c' => case_insensitive = false,
+            'g' => global = true,
+            _ => {
+                return Err(Error::Expression(format!(
+                    "Unsupported regular expression flag: {}",
+                    flag
+                )))
+            }
+        }
+    }
+    Ok((case_insensitive, global))
+}
+
+// converts the \1 and \& references of a replacement to the ${1} and ${0} syntax of the regex crate
+pub fn translate_replacement(replacement: &str) -> String {
+    let mut res = String::new();
+    let mut chars = replacement.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(d) if d.is_ascii_digit() => res.push_str(&format!("${{{}}}", d)),
+                Some('&') => res.push_str("${0}"),
+                Some(d) => res.push(d),
+                None => res.push('\\'),
+            },
+            '$' => res.push_str("$$"),
+            c => res.push(c),
+        }
+    }
+    res
+}
@@ -1,7 +1,7 @@
 use crate::{
-    executor::expression::ExprEvaluator,
+    executor::expression::PhysicalExpr,
     planner::OutputSchema,
-    types::{error::Error, Chunk, Row, TupleValue},
+    types::{error::Error, Chunk},
 };
 use sqlparser::ast::SelectItem;
 
@@ -9,7 +9,7 @@ use super::{Buffer, Executor, VECTOR_SIZE_THRESHOLD};
 
 pub struct Projection {
     output_schema: OutputSchema,
-    select: Vec<SelectItem>,
+    select: Vec<PhysicalExpr>,
     child: Box<dyn Executor>,
 
     buffer: Buffer,
@@ -23,7 +23,7 @@ impl Projection {
     ) -> Result<Box<Projection>, Error> {
         Ok(Box::new(Projection {
             buffer: Buffer::new(),
-            select,
+            select: PhysicalExpr::compile_select_items(&select, &child.get_output_schema())?,
             child,
             output_schema,
         }))
@@ -39,39 +39,14 @@ impl Executor for Projection {
                 break;
             }
 
-            for row in next_chunk.get_rows() {
-                let mut new_row = Row::new();
+            let columns = self
+                .select
+                .iter()
+                .map(|expr| expr.evaluate(&next_chunk))
+                .collect::<Result<_, Error>>()?;
 
-                for item in &self.select {
-                    match &item {
-                        SelectItem::UnnamedExpr(expr) => {
-                            let e = ExprEvaluator::evaluate(
-                                expr,
-                                row,
-                                &self.child.get_output_schema(),
-                            )?;
-                            new_row.push(TupleValue { value: e });
-                        }
-                        SelectItem::ExprWithAlias { expr, .. } => {
-                            let e = ExprEvaluator::evaluate(
-                                expr,
-                                row,
-                                &self.child.get_output_schema(),
-                            )?;
-                            new_row.push(TupleValue { value: e });
-                        }
-                        SelectItem::Wildcard(_) => {
-                            for col in row {
-                                new_row.push(col.clone());
-                            }
-                        }
-                        _ => {
-                            return Err(Error::Execution(format!("{} not supported", item)));
-                        }
-                    }
-                }
-                self.buffer.add_row(new_row);
-            }
+            self.buffer
+                .add_chunk(&Chunk::from_columns(columns, next_chunk.size()));
         }
 
         Ok(self.buffer.get_sized_chunk(VECTOR_SIZE_THRESHOLD))
@@ -1,40 +1,67 @@
 use sqlparser::ast::Expr;
 
+use crate::executor::expression::PhysicalExpr;
 use crate::planner::OutputSchema;
 use crate::storage::parquet::ParquetReader;
 use crate::storage::{get_table_path, StorageReader};
 use crate::types::error::Error;
 use crate::types::Chunk;
 
-use super::Executor;
+use super::{Buffer, ExecutionMetrics, Executor, VECTOR_SIZE_THRESHOLD};
 
 pub struct Scan {
     _table: String,
-    _filter: Option<Expr>,
+    filter: Option<PhysicalExpr>,
     output_schema: OutputSchema,
     reader: Box<dyn StorageReader>,
+
+    buffer: Buffer,
 }
 
 impl Scan {
     pub fn new(
         table: String,
         filter: Option<Expr>,
+        projection: Option<Vec<usize>>,
         output_schema: OutputSchema,
+        metrics: &ExecutionMetrics,
     ) -> Result<Box<Self>, Error> {
         let table_path = get_table_path(&table);
 
+        let reader = ParquetReader::new(table_path, projection, filter.as_ref())?;
+        metrics.add_row_groups_pruned(reader.row_groups_pruned());
+
         Ok(Box::new(Scan {
             _table: table,
-            reader: Box::new(ParquetReader::new(table_path)?),
-            _filter: filter,
+            reader: Box::new(reader),
+            filter: filter
+                .map(|filter| PhysicalExpr::compile(&filter, &output_schema))
+                .transpose()?,
             output_schema,
+            buffer: Buffer::new(),
         }))
     }
 }
 
 impl Executor for Scan {
     fn next_chunk(&mut self) -> Result<Chunk, Error> {
-        self.reader.next_chunk()
+        let Some(filter) = &self.filter else {
+            return self.reader.next_chunk();
+        };
+
+        // rows that do not pass the filter are dropped as soon as they are read
+        while self.buffer.size() < VECTOR_SIZE_THRESHOLD {
+            let next_chunk = self.reader.next_chunk()?;
+
+            if next_chunk.is_empty() {
+                break;
+            }
+
+            let mask = filter.evaluate_mask(&next_chunk)?;
+            self.buffer.add_chunk(&next_chunk.filter(&mask));
+        }
+
+        Ok(self.buffer.get_sized_chunk(VECTOR_SIZE_THRESHOLD))
     }
     fn get_output_schema(&self) -> OutputSchema {
         self.output_schema.clone()
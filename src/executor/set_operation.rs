@@ -0,0 +1,152 @@
+use std::collections::{HashMap, HashSet};
+
+use sqlparser::ast::SetOperator;
+
+use crate::executor::Executor;
+use crate::planner::OutputSchema;
+use crate::types::error::Error;
+use crate::types::Chunk;
+
+use super::{coerce_chunk, row_key, Buffer, RowKey, VECTOR_SIZE_THRESHOLD};
+
+// combines the rows of two inputs, rows are equal when all their values are equal and nulls are equal to each other
+pub struct SetOperation {
+    output_schema: OutputSchema,
+    op: SetOperator,
+    all: bool,
+    left: Box<dyn Executor>,
+    right: Box<dyn Executor>,
+
+    left_done: bool,
+    // how many times each row of the right input is left to match for INTERSECT and EXCEPT
+    right_counts: Option<HashMap<RowKey, usize>>,
+    // the rows that are already in the output when duplicates are removed
+    seen: HashSet<RowKey>,
+    buffer: Buffer,
+}
+
+impl SetOperation {
+    pub fn new(
+        op: SetOperator,
+        all: bool,
+        left: Box<dyn Executor>,
+        right: Box<dyn Executor>,
+        output_schema: OutputSchema,
+    ) -> Result<Box<SetOperation>, Error> {
+        Ok(Box::new(SetOperation {
+            output_schema,
+            op,
+            all,
+            left,
+            right,
+            left_done: false,
+            right_counts: None,
+            seen: HashSet::new(),
+            buffer: Buffer::new(),
+        }))
+    }
+
+    // the next chunk of the union of the inputs, the left input is read first
+    fn next_union_chunk(&mut self) -> Result<Chunk, Error> {
+        if !self.left_done {
+            let chunk = self.left.next_chunk()?;
+            if !chunk.is_empty() {
+                return Ok(coerce_chunk(
+                    chunk,
+                    &self.left.get_output_schema(),
+                    &self.output_schema,
+                ));
+            }
+            self.left_done = true;
+        }
+
+        let chunk = self.right.next_chunk()?;
+        Ok(coerce_chunk(
+            chunk,
+            &self.right.get_output_schema(),
+            &self.output_schema,
+        ))
+    }
+
+    fn init_right_counts(&mut self) -> Result<(), Error> {
+        if self.right_counts.is_some() {
+            return Ok(());
+        }
+
+        let right_schema = self.right.get_output_schema();
+        let mut right_counts = HashMap::new();
+        loop {
+            let chunk = self.right.next_chunk()?;
+            if chunk.is_empty() {
+                break;
+            }
+
+            let chunk = coerce_chunk(chunk, &right_schema, &self.output_schema);
+            for row in 0..chunk.size() {
+                *right_counts
+                    .entry(row_key(chunk.columns(), row))
+                    .or_insert(0) += 1;
+            }
+        }
+
+        self.right_counts = Some(right_counts);
+        Ok(())
+    }
+
+    // marks the rows of the chunk that are part of the result
+    fn result_rows(&mut self, chunk: &Chunk) -> Vec<bool> {
+        (0..chunk.size())
+            .map(|row| {
+                let key = row_key(chunk.columns(), row);
+                let in_right = match self.right_counts.as_mut() {
+                    Some(right_counts) => match right_counts.get_mut(&key) {
+                        // with ALL every row of the right input only matches a single row of the left input
+                        Some(count) if *count > 0 => {
+                            if self.all {
+                                *count -= 1;
+                            }
+                            true
+                        }
+                        _ => false,
+                    },
+                    None => false,
+                };
+
+                let keep = match self.op {
+                    SetOperator::Union => true,
+                    SetOperator::Intersect => in_right,
+                    SetOperator::Except => !in_right,
+                };
+                keep && (self.all || self.seen.insert(key))
+            })
+            .collect()
+    }
+}
+
+impl Executor for SetOperation {
+    fn next_chunk(&mut self) -> Result<Chunk, Error> {
+        while self.buffer.size() < VECTOR_SIZE_THRESHOLD {
+            let next_chunk = match self.op {
+                SetOperator::Union => self.next_union_chunk()?,
+                SetOperator::Intersect | SetOperator::Except => {
+                    self.init_right_counts()?;
+                    let chunk = self.left.next_chunk()?;
+                    coerce_chunk(chunk, &self.left.get_output_schema(), &self.output_schema)
+                }
+            };
+
+            if next_chunk.is_empty() {
+                break;
+            }
+
+            let mask = self.result_rows(&next_chunk);
+            self.buffer.add_chunk(&next_chunk.filter(&mask));
+        }
+
+        Ok(self.buffer.get_sized_chunk(VECTOR_SIZE_THRESHOLD))
+    }
+
+    fn get_output_schema(&self) -> OutputSchema {
+        self.output_schema.clone()
+    }
+}
@@ -0,0 +1,161 @@
+use std::cmp::Ordering;
+
+use sqlparser::ast::{BinaryOperator, OrderByExpr};
+
+use crate::{
+    planner::OutputSchema,
+    types::{error::Error, field::Field, vector::Vector, Chunk},
+};
+
+use super::{
+    collect_chunks,
+    expression::{ExprEvaluator, PhysicalExpr},
+    Buffer, Executor, VECTOR_SIZE_THRESHOLD,
+};
+
+pub struct Sort {
+    output_schema: OutputSchema,
+    order_by: Vec<OrderByExpr>,
+    // the compiled expressions of the order by items
+    keys: Vec<PhysicalExpr>,
+    child: Box<dyn Executor>,
+
+    buffer: Option<Buffer>,
+}
+
+impl Sort {
+    pub fn new(
+        child: Box<dyn Executor>,
+        order_by: Vec<OrderByExpr>,
+        output_schema: OutputSchema,
+    ) -> Result<Box<Sort>, Error> {
+        let child_output_schema = child.get_output_schema();
+        let keys = order_by
+            .iter()
+            .map(|o| PhysicalExpr::compile(&o.expr, &child_output_schema))
+            .collect::<Result<Vec<PhysicalExpr>, Error>>()?;
+
+        Ok(Box::new(Sort {
+            output_schema,
+            order_by,
+            keys,
+            child,
+            buffer: None,
+        }))
+    }
+
+    fn init_sorted_rows(&mut self) -> Result<(), Error> {
+        // TODO: consider when the rows are too large to fit in memory
+        if self.buffer.is_some() {
+            return Ok(());
+        }
+
+        let chunk = collect_chunks(self.child.as_mut())?;
+
+        // we evaluate the sort keys once for all the rows and sort the row indexes by them
+        let keys = self
+            .keys
+            .iter()
+            .map(|key| key.evaluate(&chunk))
+            .collect::<Result<Vec<Vector>, Error>>()?;
+        let keyed_rows: Vec<Vec<Field>> = (0..chunk.size())
+            .map(|i| keys.iter().map(|key| key.get(i)).collect())
+            .collect();
+        let mut indexes: Vec<usize> = (0..chunk.size()).collect();
+
+        // sort_by cannot return errors so we record the first one and report it after sorting
+        let mut error = None;
+        indexes.sort_by(|left, right| {
+            match Self::compare_keys(&self.order_by, &keyed_rows[*left], &keyed_rows[*right]) {
+                Ok(ordering) => ordering,
+                Err(e) => {
+                    error.get_or_insert(e);
+                    Ordering::Equal
+                }
+            }
+        });
+
+        if let Some(e) = error {
+            return Err(e);
+        }
+
+        let mut buffer = Buffer::new();
+        buffer.add_chunk(&chunk.take(&indexes));
+
+        self.buffer = Some(buffer);
+        Ok(())
+    }
+
+    fn compare_keys(
+        order_by: &[OrderByExpr],
+        left: &[Field],
+        right: &[Field],
+    ) -> Result<Ordering, Error> {
+        for (i, o) in order_by.iter().enumerate() {
+            let asc = o.asc.unwrap_or(true);
+            // nulls are considered larger than any other value by default
+            let nulls_first = o.nulls_first.unwrap_or(!asc);
+
+            let ordering = match (&left[i], &right[i]) {
+                (Field::Null, Field::Null) => Ordering::Equal,
+                (Field::Null, _) => {
+                    if nulls_first {
+                        Ordering::Less
+                    } else {
+                        Ordering::Greater
+                    }
+                }
+                (_, Field::Null) => {
+                    if nulls_first {
+                        Ordering::Greater
+                    } else {
+                        Ordering::Less
+                    }
+                }
+                (l, r) => {
+                    let ordering = Self::compare_fields(l, r)?;
+                    if asc {
+                        ordering
+                    } else {
+                        ordering.reverse()
+                    }
+                }
+            };
+
+            if ordering != Ordering::Equal {
+                return Ok(ordering);
+            }
+        }
+
+        Ok(Ordering::Equal)
+    }
+
+    fn compare_fields(left: &Field, right: &Field) -> Result<Ordering, Error> {
+        let lt = ExprEvaluator::evaluate_binary_op(left, &BinaryOperator::Lt, right)?;
+        if ExprEvaluator::to_boolean(&lt) {
+            return Ok(Ordering::Less);
+        }
+
+        let gt = ExprEvaluator::evaluate_binary_op(left, &BinaryOperator::Gt, right)?;
+        if ExprEvaluator::to_boolean(&gt) {
+            return Ok(Ordering::Greater);
+        }
+
+        Ok(Ordering::Equal)
+    }
+}
+
+impl Executor for Sort {
+    fn next_chunk(&mut self) -> Result<Chunk, Error> {
+        self.init_sorted_rows()?;
+        Ok(self
+            .buffer
+            .as_mut()
+            .unwrap()
+            .get_sized_chunk(VECTOR_SIZE_THRESHOLD))
+    }
+
+    fn get_output_schema(&self) -> OutputSchema {
+        self.output_schema.clone()
+    }
+}
@@ -0,0 +1,32 @@
+use crate::executor::Executor;
+use crate::planner::OutputSchema;
+use crate::types::error::Error;
+use crate::types::Chunk;
+
+// passes the rows of a subquery through with the renamed columns of its alias
+pub struct SubqueryAlias {
+    output_schema: OutputSchema,
+    child: Box<dyn Executor>,
+}
+
+impl SubqueryAlias {
+    pub fn new(
+        child: Box<dyn Executor>,
+        output_schema: OutputSchema,
+    ) -> Result<Box<SubqueryAlias>, Error> {
+        Ok(Box::new(SubqueryAlias {
+            output_schema,
+            child,
+        }))
+    }
+}
+
+impl Executor for SubqueryAlias {
+    fn next_chunk(&mut self) -> Result<Chunk, Error> {
+        self.child.next_chunk()
+    }
+
+    fn get_output_schema(&self) -> OutputSchema {
+        self.output_schema.clone()
+    }
+}
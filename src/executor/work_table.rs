@@ -0,0 +1,48 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::executor::Executor;
+use crate::planner::OutputSchema;
+use crate::types::error::Error;
+use crate::types::Chunk;
+
+use super::VECTOR_SIZE_THRESHOLD;
+
+// the rows of a common table expression that are shared by the executor that fills them and the scans that read them
+pub type WorkTable = Rc<RefCell<Chunk>>;
+
+pub struct WorkTableScan {
+    output_schema: OutputSchema,
+    work_table: WorkTable,
+    // the index of the next row to read
+    position: usize,
+}
+
+impl WorkTableScan {
+    pub fn new(
+        work_table: WorkTable,
+        output_schema: OutputSchema,
+    ) -> Result<Box<WorkTableScan>, Error> {
+        Ok(Box::new(WorkTableScan {
+            output_schema,
+            work_table,
+            position: 0,
+        }))
+    }
+}
+
+impl Executor for WorkTableScan {
+    fn next_chunk(&mut self) -> Result<Chunk, Error> {
+        let work_table = self.work_table.borrow();
+
+        let start = self.position.min(work_table.size());
+        let end = work_table.size().min(start + VECTOR_SIZE_THRESHOLD);
+        self.position = end;
+
+        Ok(work_table.slice(start, end))
+    }
+
+    fn get_output_schema(&self) -> OutputSchema {
+        self.output_schema.clone()
+    }
+}
@@ -1,16 +1,8 @@
 use rustyline::DefaultEditor;
-
-mod executor;
-mod optimizer;
-mod parser;
-mod planner;
-mod storage;
-mod types;
-
-mod database;
+use sqlengine::database::Database;
 
 fn main() {
-    let db = database::Database::new().unwrap();
+    let db = Database::new().unwrap();
 
     let mut rl = DefaultEditor::new().unwrap();
 
@@ -19,13 +11,9 @@ fn main() {
         match readline {
             Ok(line) => {
                 rl.add_history_entry(line.as_str()).unwrap();
-                let res = db.execute(line.as_str());
-
-                if res.is_err() {
-                    println!("{}", res.err().unwrap());
-                    continue;
-                } else {
-                    println!("{}", res.unwrap());
+                match db.execute(line.as_str()) {
+                    Ok(res) => println!("{}", res),
+                    Err(e) => println!("{}", e),
                 }
             }
             Err(e) => {
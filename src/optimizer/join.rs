@@ -0,0 +1,189 @@
+use sqlparser::ast::{BinaryOperator, Expr, SetOperator};
+
+use crate::{
+    planner::{
+        binder::{common_type, infer_type, join_predicate_schema},
+        Node, OutputSchema, PlanNode,
+    },
+    storage::{get_table_path, parquet::ParquetReader},
+    types::error::Error,
+};
+
+use super::{
+    utils::{combine_conjunction, is_bound_by, referenced_columns, split_conjunction},
+    OptimizerRule, Transformed,
+};
+
+// replaces a nested loop join with a hash join if the predicate contains equality conditions between both sides
+pub struct HashJoinSelection {}
+
+impl OptimizerRule for HashJoinSelection {
+    fn name(&self) -> &'static str {
+        "hash_join_selection"
+    }
+
+    fn rewrite(&self, plan_node: PlanNode) -> Result<Transformed, Error> {
+        let Node::NestedLoopJoin {
+            child_left,
+            child_right,
+            predicate: Some(predicate),
+            join_type,
+        } = &plan_node.node
+        else {
+            return Ok(Transformed::No(plan_node));
+        };
+
+        let predicate_schema = join_predicate_schema(
+            &child_left.output_schema,
+            &child_right.output_schema,
+            &plan_node.output_schema,
+            *join_type,
+        );
+
+        let mut left_keys = Vec::new();
+        let mut right_keys = Vec::new();
+        let mut residual = Vec::new();
+
+        for conjunct in split_conjunction(predicate) {
+            match extract_join_keys(&conjunct, &predicate_schema, child_left, child_right) {
+                Some((left_key, right_key)) => {
+                    left_keys.push(left_key);
+                    right_keys.push(right_key);
+                }
+                None => residual.push(conjunct),
+            }
+        }
+
+        if left_keys.is_empty() {
+            return Ok(Transformed::No(plan_node));
+        }
+
+        // we build the hash table on the side we expect to be smaller, semi and anti joins look up each left row once
+        let build_left = !join_type.outputs_left_only()
+            && estimate_row_count(child_left)? < estimate_row_count(child_right)?;
+
+        let Node::NestedLoopJoin {
+            child_left,
+            child_right,
+            join_type,
+            ..
+        } = plan_node.node
+        else {
+            unreachable!()
+        };
+
+        Ok(Transformed::Yes(PlanNode {
+            output_schema: plan_node.output_schema,
+            node: Node::HashJoin {
+                child_left,
+                child_right,
+                left_keys,
+                right_keys,
+                predicate: combine_conjunction(residual),
+                join_type,
+                build_left,
+            },
+        }))
+    }
+}
+
+// returns the left and right key if the expression is an equality between an expression on the left side and one on the right side
+fn extract_join_keys(
+    expr: &Expr,
+    output_schema: &OutputSchema,
+    child_left: &PlanNode,
+    child_right: &PlanNode,
+) -> Option<(Expr, Expr)> {
+    let Expr::BinaryOp {
+        left,
+        op: BinaryOperator::Eq,
+        right,
+    } = expr
+    else {
+        return None;
+    };
+
+    // every column has to be unambiguous in the join output so we know which side it comes from
+    if !is_bound_by(expr, output_schema)
+        || referenced_columns(left)?.is_empty()
+        || referenced_columns(right)?.is_empty()
+    {
+        return None;
+    }
+
+    let (left_key, right_key) = if is_bound_by(left, &child_left.output_schema)
+        && is_bound_by(right, &child_right.output_schema)
+    {
+        (*left.clone(), *right.clone())
+    } else if is_bound_by(left, &child_right.output_schema)
+        && is_bound_by(right, &child_left.output_schema)
+    {
+        (*right.clone(), *left.clone())
+    } else {
+        return None;
+    };
+
+    // the keys are hashed as the type they are compared as so they need one
+    let (left_type, _) = infer_type(&left_key, &child_left.output_schema);
+    let (right_type, _) = infer_type(&right_key, &child_right.output_schema);
+    common_type(left_type, right_type)?;
+
+    Some((left_key, right_key))
+}
+
+// a rough estimate of the number of rows a plan node produces
+pub fn estimate_row_count(plan_node: &PlanNode) -> Result<u64, Error> {
+    match &plan_node.node {
+        Node::Scan { table_name, .. } => ParquetReader::read_row_count(&get_table_path(table_name)),
+        Node::NestedLoopJoin {
+            child_left,
+            join_type,
+            ..
+        }
+        | Node::HashJoin {
+            child_left,
+            join_type,
+            ..
+        } if join_type.outputs_left_only() => estimate_row_count(child_left),
+        Node::NestedLoopJoin {
+            child_left,
+            child_right,
+            ..
+        } => Ok(estimate_row_count(child_left)?.saturating_mul(estimate_row_count(child_right)?)),
+        Node::HashJoin {
+            child_left,
+            child_right,
+            ..
+        } => Ok(std::cmp::max(
+            estimate_row_count(child_left)?,
+            estimate_row_count(child_right)?,
+        )),
+        Node::Filter { child, .. }
+        | Node::Projection { child, .. }
+        | Node::Aggregate { child, .. }
+        | Node::Sort { child, .. }
+        | Node::Distinct { child, .. }
+        | Node::SubqueryAlias { child, .. }
+        | Node::MaterializedCte { child, .. }
+        | Node::Apply { child, .. } => estimate_row_count(child),
+        Node::RecursiveCte { anchor, .. } => estimate_row_count(anchor),
+        // the rows of a work table are only known once it is filled, the rows of an iteration are usually few
+        Node::WorkTableScan { .. } => Ok(1),
+        Node::SetOperation {
+            op, left, right, ..
+        } => {
+            let left = estimate_row_count(left)?;
+            let right = estimate_row_count(right)?;
+            Ok(match op {
+                SetOperator::Union => left.saturating_add(right),
+                SetOperator::Intersect => std::cmp::min(left, right),
+                SetOperator::Except => left,
+            })
+        }
+        Node::Limit { limit, child, .. } => {
+            let count = estimate_row_count(child)?;
+            Ok(limit.map_or(count, |limit| std::cmp::min(limit, count)))
+        }
+        Node::Empty {} => Ok(1),
+    }
+}
@@ -1,13 +1,117 @@
-use crate::{planner::Plan, types::error::Error};
+mod join;
+mod predicate;
+mod projection;
+mod subquery;
+pub(crate) mod utils;
 
-pub struct Optimizer {}
+use std::collections::HashSet;
+
+use crate::{
+    planner::{Plan, PlanNode},
+    types::error::Error,
+};
+
+use self::{
+    join::HashJoinSelection, predicate::PredicatePushdown, projection::ProjectionPushdown,
+    subquery::SubqueryDecorrelation,
+};
+
+// the maximum number of passes over the rules before we stop even if rules are still firing
+const MAX_ITERATIONS: usize = 16;
+
+pub enum Transformed {
+    Yes(PlanNode),
+    No(PlanNode),
+}
+
+pub trait OptimizerRule: Send {
+    fn name(&self) -> &'static str;
+    // rewrites a single plan node, the optimizer takes care of visiting every node of the plan
+    fn rewrite(&self, plan_node: PlanNode) -> Result<Transformed, Error>;
+}
+
+pub struct Optimizer {
+    rules: Vec<Box<dyn OptimizerRule>>,
+    disabled_rules: HashSet<String>,
+}
 
 impl Optimizer {
     pub fn new() -> Optimizer {
-        Optimizer {}
+        Optimizer {
+            rules: vec![
+                Box::new(SubqueryDecorrelation {}),
+                Box::new(PredicatePushdown {}),
+                Box::new(ProjectionPushdown {}),
+                Box::new(HashJoinSelection {}),
+            ],
+            disabled_rules: HashSet::new(),
+        }
+    }
+
+    pub fn rule_names(&self) -> Vec<&'static str> {
+        self.rules.iter().map(|rule| rule.name()).collect()
+    }
+
+    pub fn set_rule_enabled(&mut self, name: &str, enabled: bool) -> Result<(), Error> {
+        if !self.rule_names().contains(&name) {
+            return Err(Error::Planner(format!("Unknown optimizer rule: {}", name)));
+        }
+
+        if enabled {
+            self.disabled_rules.remove(name);
+        } else {
+            self.disabled_rules.insert(name.to_string());
+        }
+
+        Ok(())
     }
 
     pub fn optimize(&self, plan: Plan) -> Result<Plan, Error> {
+        let (plan, _) = self.optimize_with_trace(plan)?;
         Ok(plan)
     }
+
+    // optimizes the plan and also returns the names of the rules in the order they fired
+    pub fn optimize_with_trace(&self, plan: Plan) -> Result<(Plan, Vec<String>), Error> {
+        let mut root = plan.root;
+        let mut fired_rules = Vec::new();
+
+        // we keep applying the rules until none of them change the plan
+        for _ in 0..MAX_ITERATIONS {
+            let mut changed = false;
+
+            for rule in self.rules.iter() {
+                if self.disabled_rules.contains(rule.name()) {
+                    continue;
+                }
+
+                root = Self::apply_rule(rule.as_ref(), root, &mut changed, &mut fired_rules)?;
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        Ok((Plan { root }, fired_rules))
+    }
+
+    // applies the rule to the node and then to each of its children
+    fn apply_rule(
+        rule: &dyn OptimizerRule,
+        plan_node: PlanNode,
+        changed: &mut bool,
+        fired_rules: &mut Vec<String>,
+    ) -> Result<PlanNode, Error> {
+        let plan_node = match rule.rewrite(plan_node)? {
+            Transformed::Yes(plan_node) => {
+                *changed = true;
+                fired_rules.push(rule.name().to_string());
+                plan_node
+            }
+            Transformed::No(plan_node) => plan_node,
+        };
+
+        plan_node.map_children(|child| Self::apply_rule(rule, child, changed, fired_rules))
+    }
 }
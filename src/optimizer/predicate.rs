@@ -0,0 +1,440 @@
+use sqlparser::ast::{Expr, SelectItem};
+
+use crate::{
+    planner::{binder::join_predicate_schema, JoinType, Node, PlanNode},
+    types::error::Error,
+};
+
+use super::{
+    utils::{
+        combine_conjunction, is_bound_by, referenced_columns, replace_columns, split_conjunction,
+    },
+    OptimizerRule, Transformed,
+};
+
+// splits filters and join predicates into their conjunctions and moves each one as far down the plan as it can go
+pub struct PredicatePushdown {}
+
+impl OptimizerRule for PredicatePushdown {
+    fn name(&self) -> &'static str {
+        "predicate_pushdown"
+    }
+
+    fn rewrite(&self, plan_node: PlanNode) -> Result<Transformed, Error> {
+        match &plan_node.node {
+            Node::Filter { child, .. } => match &child.node {
+                Node::Filter { .. } | Node::Scan { .. } | Node::Sort { .. } => {
+                    Ok(Transformed::Yes(push_filter_into_child(plan_node)))
+                }
+                Node::Projection { .. } => push_filter_through_projection(plan_node),
+                Node::SubqueryAlias { .. } => push_filter_through_subquery_alias(plan_node),
+                Node::NestedLoopJoin { .. } | Node::HashJoin { .. } => {
+                    push_filter_into_join(plan_node)
+                }
+                _ => Ok(Transformed::No(plan_node)),
+            },
+            Node::NestedLoopJoin {
+                predicate: Some(_), ..
+            }
+            | Node::HashJoin {
+                predicate: Some(_), ..
+            } => push_join_predicate(plan_node),
+            _ => Ok(Transformed::No(plan_node)),
+        }
+    }
+}
+
+// merges a filter into a filter or scan below it or swaps it with a sort
+fn push_filter_into_child(plan_node: PlanNode) -> PlanNode {
+    let Node::Filter { filter, child } = plan_node.node else {
+        unreachable!()
+    };
+
+    match child.node {
+        Node::Filter {
+            filter: child_filter,
+            child,
+        } => PlanNode {
+            output_schema: plan_node.output_schema,
+            node: Node::Filter {
+                filter: and(child_filter, filter),
+                child,
+            },
+        },
+        Node::Scan {
+            table_name,
+            filter: scan_filter,
+            projection,
+        } => PlanNode {
+            output_schema: plan_node.output_schema,
+            node: Node::Scan {
+                table_name,
+                projection,
+                filter: Some(match scan_filter {
+                    Some(scan_filter) => and(scan_filter, filter),
+                    None => filter,
+                }),
+            },
+        },
+        Node::Sort { order_by, child } => PlanNode {
+            output_schema: plan_node.output_schema,
+            node: Node::Sort {
+                order_by,
+                child: Box::new(with_filter(*child, vec![filter])),
+            },
+        },
+        _ => unreachable!(),
+    }
+}
+
+// rewrites the conjunctions of a filter in terms of the projection input so they can be evaluated below it
+fn push_filter_through_projection(plan_node: PlanNode) -> Result<Transformed, Error> {
+    let Node::Filter { filter, child } = &plan_node.node else {
+        unreachable!()
+    };
+    let Node::Projection { select, .. } = &child.node else {
+        unreachable!()
+    };
+
+    // with wildcards the select items no longer line up with the output columns
+    let mut exprs = Vec::new();
+    for select_item in select {
+        match select_item {
+            SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => {
+                exprs.push(expr)
+            }
+            _ => return Ok(Transformed::No(plan_node)),
+        }
+    }
+
+    let mut pushed = Vec::new();
+    let mut remaining = Vec::new();
+
+    for conjunct in split_conjunction(filter) {
+        let replaced = replace_columns(&conjunct, &mut |column| {
+            let index = child.output_schema.resolve(column).ok()?;
+            Some(exprs[index].clone())
+        });
+
+        match replaced {
+            Some(replaced) => pushed.push(replaced),
+            None => remaining.push(conjunct),
+        }
+    }
+
+    if pushed.is_empty() {
+        return Ok(Transformed::No(plan_node));
+    }
+
+    let Node::Filter { child, .. } = plan_node.node else {
+        unreachable!()
+    };
+    let Node::Projection { select, child } = child.node else {
+        unreachable!()
+    };
+
+    let projection = PlanNode {
+        output_schema: plan_node.output_schema.clone(),
+        node: Node::Projection {
+            select,
+            child: Box::new(with_filter(*child, pushed)),
+        },
+    };
+
+    Ok(Transformed::Yes(with_filter(projection, remaining)))
+}
+
+// rewrites the conjunctions of a filter with the names the columns have in the subquery so they can be evaluated inside it
+fn push_filter_through_subquery_alias(plan_node: PlanNode) -> Result<Transformed, Error> {
+    let Node::Filter { filter, child } = &plan_node.node else {
+        unreachable!()
+    };
+    let Node::SubqueryAlias {
+        child: subquery, ..
+    } = &child.node
+    else {
+        unreachable!()
+    };
+
+    let mut pushed = Vec::new();
+    let mut remaining = Vec::new();
+
+    for conjunct in split_conjunction(filter) {
+        // a column can only be replaced when its name in the subquery resolves back to the same column
+        let replaced = replace_columns(&conjunct, &mut |column| {
+            let index = child.output_schema.resolve(column).ok()?;
+            let expr = subquery.output_schema.columns[index].as_expr();
+            let name = referenced_columns(&expr)?.pop()?;
+            match subquery.output_schema.resolve(&name) {
+                Ok(subquery_index) if subquery_index == index => Some(expr),
+                _ => None,
+            }
+        });
+
+        match replaced {
+            Some(replaced) => pushed.push(replaced),
+            None => remaining.push(conjunct),
+        }
+    }
+
+    if pushed.is_empty() {
+        return Ok(Transformed::No(plan_node));
+    }
+
+    let Node::Filter { child, .. } = plan_node.node else {
+        unreachable!()
+    };
+    let Node::SubqueryAlias {
+        alias,
+        child: subquery,
+    } = child.node
+    else {
+        unreachable!()
+    };
+
+    let subquery_alias = PlanNode {
+        output_schema: plan_node.output_schema.clone(),
+        node: Node::SubqueryAlias {
+            alias,
+            child: Box::new(with_filter(*subquery, pushed)),
+        },
+    };
+
+    Ok(Transformed::Yes(with_filter(subquery_alias, remaining)))
+}
+
+// moves the conjunctions of a filter above a join into the join predicate or into either side of the join
+fn push_filter_into_join(plan_node: PlanNode) -> Result<Transformed, Error> {
+    let Node::Filter { filter, child } = plan_node.node else {
+        unreachable!()
+    };
+    let join_type = get_join_type(&child);
+
+    // a filter above an outer join cannot be pushed into the side that is padded with nulls
+    let (left, right, predicate, remaining) = partition_conjunctions(
+        split_conjunction(&filter),
+        &child,
+        !join_type.preserves_right(),
+        !join_type.preserves_left(),
+        join_type == JoinType::Inner,
+    );
+
+    if left.is_empty() && right.is_empty() && predicate.is_empty() {
+        return Ok(Transformed::No(PlanNode {
+            output_schema: plan_node.output_schema,
+            node: Node::Filter { filter, child },
+        }));
+    }
+
+    let join = rebuild_join(*child, left, right, predicate);
+    Ok(Transformed::Yes(with_filter(join, remaining)))
+}
+
+// moves the conjunctions of a join predicate that only reference one side of the join into that side
+fn push_join_predicate(plan_node: PlanNode) -> Result<Transformed, Error> {
+    let join_type = get_join_type(&plan_node);
+    let (Node::NestedLoopJoin {
+        predicate: Some(predicate),
+        ..
+    }
+    | Node::HashJoin {
+        predicate: Some(predicate),
+        ..
+    }) = &plan_node.node
+    else {
+        unreachable!()
+    };
+
+    // a join predicate only decides which rows match so it cannot remove rows from the side that is preserved
+    let (left, right, _, remaining) = partition_conjunctions(
+        split_conjunction(predicate),
+        &plan_node,
+        !join_type.preserves_left(),
+        !join_type.preserves_right(),
+        false,
+    );
+
+    if left.is_empty() && right.is_empty() {
+        return Ok(Transformed::No(plan_node));
+    }
+
+    let plan_node = set_join_predicate(plan_node, combine_conjunction(remaining));
+    Ok(Transformed::Yes(rebuild_join(
+        plan_node,
+        left,
+        right,
+        vec![],
+    )))
+}
+
+// splits the conjunctions into the ones for the left side, the right side, the join predicate and the ones that stay where they are
+fn partition_conjunctions(
+    conjuncts: Vec<Expr>,
+    join: &PlanNode,
+    push_left: bool,
+    push_right: bool,
+    push_predicate: bool,
+) -> (Vec<Expr>, Vec<Expr>, Vec<Expr>, Vec<Expr>) {
+    let (child_left, child_right) = match &join.node {
+        Node::NestedLoopJoin {
+            child_left,
+            child_right,
+            ..
+        }
+        | Node::HashJoin {
+            child_left,
+            child_right,
+            ..
+        } => (child_left, child_right),
+        _ => unreachable!(),
+    };
+
+    let predicate_schema = join_predicate_schema(
+        &child_left.output_schema,
+        &child_right.output_schema,
+        &join.output_schema,
+        get_join_type(join),
+    );
+
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+    let mut predicate = Vec::new();
+    let mut remaining = Vec::new();
+
+    for conjunct in conjuncts {
+        // the conjunction has to resolve in the join output so pushing it down cannot hide an ambiguous column
+        if !is_bound_by(&conjunct, &predicate_schema) {
+            remaining.push(conjunct);
+        } else if push_left && is_bound_by(&conjunct, &child_left.output_schema) {
+            left.push(conjunct);
+        } else if push_right && is_bound_by(&conjunct, &child_right.output_schema) {
+            right.push(conjunct);
+        } else if push_predicate {
+            predicate.push(conjunct);
+        } else {
+            remaining.push(conjunct);
+        }
+    }
+
+    (left, right, predicate, remaining)
+}
+
+fn get_join_type(plan_node: &PlanNode) -> JoinType {
+    match &plan_node.node {
+        Node::NestedLoopJoin { join_type, .. } | Node::HashJoin { join_type, .. } => *join_type,
+        _ => unreachable!(),
+    }
+}
+
+fn set_join_predicate(plan_node: PlanNode, new_predicate: Option<Expr>) -> PlanNode {
+    let node = match plan_node.node {
+        Node::NestedLoopJoin {
+            child_left,
+            child_right,
+            join_type,
+            ..
+        } => Node::NestedLoopJoin {
+            child_left,
+            child_right,
+            predicate: new_predicate,
+            join_type,
+        },
+        Node::HashJoin {
+            child_left,
+            child_right,
+            left_keys,
+            right_keys,
+            join_type,
+            build_left,
+            ..
+        } => Node::HashJoin {
+            child_left,
+            child_right,
+            left_keys,
+            right_keys,
+            predicate: new_predicate,
+            join_type,
+            build_left,
+        },
+        _ => unreachable!(),
+    };
+
+    PlanNode {
+        output_schema: plan_node.output_schema,
+        node,
+    }
+}
+
+// adds filters to both sides of the join and the conjunctions to the join predicate
+fn rebuild_join(
+    plan_node: PlanNode,
+    left: Vec<Expr>,
+    right: Vec<Expr>,
+    predicate: Vec<Expr>,
+) -> PlanNode {
+    let node = match plan_node.node {
+        Node::NestedLoopJoin {
+            child_left,
+            child_right,
+            predicate: current,
+            join_type,
+        } => Node::NestedLoopJoin {
+            child_left: Box::new(with_filter(*child_left, left)),
+            child_right: Box::new(with_filter(*child_right, right)),
+            predicate: append_conjunctions(current, predicate),
+            join_type,
+        },
+        Node::HashJoin {
+            child_left,
+            child_right,
+            left_keys,
+            right_keys,
+            predicate: current,
+            join_type,
+            build_left,
+        } => Node::HashJoin {
+            child_left: Box::new(with_filter(*child_left, left)),
+            child_right: Box::new(with_filter(*child_right, right)),
+            left_keys,
+            right_keys,
+            predicate: append_conjunctions(current, predicate),
+            join_type,
+            build_left,
+        },
+        _ => unreachable!(),
+    };
+
+    PlanNode {
+        output_schema: plan_node.output_schema,
+        node,
+    }
+}
+
+fn append_conjunctions(current: Option<Expr>, mut conjuncts: Vec<Expr>) -> Option<Expr> {
+    match current {
+        Some(current) => {
+            let mut res = split_conjunction(&current);
+            res.append(&mut conjuncts);
+            combine_conjunction(res)
+        }
+        None => combine_conjunction(conjuncts),
+    }
+}
+
+// wraps the node in a filter with the conjunctions if there are any
+fn with_filter(plan_node: PlanNode, conjuncts: Vec<Expr>) -> PlanNode {
+    match combine_conjunction(conjuncts) {
+        Some(filter) => PlanNode {
+            output_schema: plan_node.output_schema.clone(),
+            node: Node::Filter {
+                filter,
+                child: Box::new(plan_node),
+            },
+        },
+        None => plan_node,
+    }
+}
+
+fn and(left: Expr, right: Expr) -> Expr {
+    combine_conjunction(vec![left, right]).unwrap()
+}
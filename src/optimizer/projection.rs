@@ -0,0 +1,420 @@
+use std::collections::BTreeSet;
+
+use sqlparser::ast::{Expr, SelectItem};
+
+use crate::{
+    planner::{
+        binder::join_predicate_schema, JoinType, Node, OutputSchema, PlanNode, SubqueryKind,
+    },
+    types::error::Error,
+};
+
+use super::{utils::referenced_columns, OptimizerRule, Transformed};
+
+// removes the columns that are never used by a projection or aggregate from the nodes below it so scans only read the columns that are needed
+pub struct ProjectionPushdown {}
+
+impl OptimizerRule for ProjectionPushdown {
+    fn name(&self) -> &'static str {
+        "projection_pushdown"
+    }
+
+    fn rewrite(&self, plan_node: PlanNode) -> Result<Transformed, Error> {
+        let (exprs, child) = match &plan_node.node {
+            Node::Projection { select, child } => (select_item_exprs(select), child),
+            Node::Aggregate {
+                child,
+                aggregates,
+                non_aggregates,
+                group_by,
+            } => {
+                let mut exprs = select_item_exprs(non_aggregates);
+                if let Some(exprs) = &mut exprs {
+                    exprs.extend(aggregates.iter().map(|f| Expr::Function(f.clone())));
+                    exprs.extend(group_by.iter().cloned());
+                }
+                (exprs, child)
+            }
+            _ => return Ok(Transformed::No(plan_node)),
+        };
+
+        let Some(required) = required_columns(&exprs, &child.output_schema) else {
+            return Ok(Transformed::No(plan_node));
+        };
+
+        let mut changed = false;
+        let plan_node = plan_node.map_children(|child| {
+            let child = prune_columns(child, required.clone())?;
+            changed = child.changed;
+            Ok(child.plan_node)
+        })?;
+
+        if changed {
+            Ok(Transformed::Yes(plan_node))
+        } else {
+            Ok(Transformed::No(plan_node))
+        }
+    }
+}
+
+// returns the expressions of the select items or None if there is a wildcard since it needs every column
+fn select_item_exprs(select: &[SelectItem]) -> Option<Vec<Expr>> {
+    let mut exprs = Vec::new();
+    for select_item in select {
+        match select_item {
+            SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => {
+                exprs.push(expr.clone())
+            }
+            _ => return None,
+        }
+    }
+    Some(exprs)
+}
+
+// returns the indexes of the columns the expressions use or None if they could not all be resolved
+fn required_columns(
+    exprs: &Option<Vec<Expr>>,
+    output_schema: &OutputSchema,
+) -> Option<BTreeSet<usize>> {
+    let mut required = BTreeSet::new();
+
+    for expr in exprs.as_ref()? {
+        for column in referenced_columns(expr)? {
+            required.insert(output_schema.resolve(&column).ok()?);
+        }
+    }
+
+    Some(required)
+}
+
+// adds the columns used by the expressions or every column if they cannot be analyzed
+fn add_required_columns(
+    required: &mut BTreeSet<usize>,
+    exprs: Vec<Expr>,
+    output_schema: &OutputSchema,
+) {
+    match required_columns(&Some(exprs), output_schema) {
+        Some(columns) => required.extend(columns),
+        None => required.extend(0..output_schema.columns.len()),
+    }
+}
+
+// the result of pruning a node with the indexes of its original output columns that are kept
+struct Pruned {
+    plan_node: PlanNode,
+    kept: Vec<usize>,
+    changed: bool,
+}
+
+impl Pruned {
+    fn new(output_schema: OutputSchema, node: Node, kept: Vec<usize>, changed: bool) -> Pruned {
+        Pruned {
+            plan_node: PlanNode {
+                output_schema: select_columns(&output_schema, &kept),
+                node,
+            },
+            kept,
+            changed,
+        }
+    }
+}
+
+// removes the columns that are not in the required indexes from the output of the node
+fn prune_columns(plan_node: PlanNode, mut required: BTreeSet<usize>) -> Result<Pruned, Error> {
+    let output_schema = plan_node.output_schema;
+
+    match plan_node.node {
+        Node::Scan {
+            table_name,
+            filter,
+            projection,
+        } => {
+            if let Some(filter) = &filter {
+                add_required_columns(&mut required, vec![filter.clone()], &output_schema);
+            }
+
+            // we always read at least one column so the number of rows stays the same
+            if required.is_empty() {
+                required.insert(0);
+            }
+
+            let kept = required.into_iter().collect::<Vec<usize>>();
+            let changed = kept.len() != output_schema.columns.len();
+
+            let projection = if changed {
+                Some(
+                    kept.iter()
+                        .map(|i| projection.as_ref().map_or(*i, |projection| projection[*i]))
+                        .collect(),
+                )
+            } else {
+                projection
+            };
+
+            let node = Node::Scan {
+                table_name,
+                filter,
+                projection,
+            };
+            Ok(Pruned::new(output_schema, node, kept, changed))
+        }
+        Node::Projection { select, child } => {
+            // with wildcards the select items no longer line up with the output columns so they are all kept
+            let (kept, select) = match select_item_exprs(&select) {
+                Some(_) => {
+                    let kept = required.into_iter().collect::<Vec<usize>>();
+                    let select = kept.iter().map(|i| select[*i].clone()).collect();
+                    (kept, select)
+                }
+                None => ((0..output_schema.columns.len()).collect(), select),
+            };
+
+            let mut child_required = BTreeSet::new();
+            match select_item_exprs(&select) {
+                Some(exprs) => {
+                    add_required_columns(&mut child_required, exprs, &child.output_schema)
+                }
+                None => child_required.extend(0..child.output_schema.columns.len()),
+            }
+
+            let child = prune_columns(*child, child_required)?;
+            let changed = kept.len() != output_schema.columns.len() || child.changed;
+
+            let node = Node::Projection {
+                select,
+                child: Box::new(child.plan_node),
+            };
+            Ok(Pruned::new(output_schema, node, kept, changed))
+        }
+        Node::Filter { filter, child } => {
+            add_required_columns(&mut required, vec![filter.clone()], &output_schema);
+            let child = prune_columns(*child, required)?;
+
+            let node = Node::Filter {
+                filter,
+                child: Box::new(child.plan_node),
+            };
+            Ok(Pruned::new(output_schema, node, child.kept, child.changed))
+        }
+        Node::Sort { order_by, child } => {
+            let exprs = order_by.iter().map(|o| o.expr.clone()).collect();
+            add_required_columns(&mut required, exprs, &output_schema);
+            let child = prune_columns(*child, required)?;
+
+            let node = Node::Sort {
+                order_by,
+                child: Box::new(child.plan_node),
+            };
+            Ok(Pruned::new(output_schema, node, child.kept, child.changed))
+        }
+        Node::Distinct { on, child } => {
+            // every column is part of the key without expressions
+            if on.is_empty() {
+                required.extend(0..output_schema.columns.len());
+            } else {
+                add_required_columns(&mut required, on.clone(), &output_schema);
+            }
+            let child = prune_columns(*child, required)?;
+
+            let node = Node::Distinct {
+                on,
+                child: Box::new(child.plan_node),
+            };
+            Ok(Pruned::new(output_schema, node, child.kept, child.changed))
+        }
+        Node::Limit {
+            limit,
+            offset,
+            child,
+        } => {
+            let child = prune_columns(*child, required)?;
+
+            let node = Node::Limit {
+                limit,
+                offset,
+                child: Box::new(child.plan_node),
+            };
+            Ok(Pruned::new(output_schema, node, child.kept, child.changed))
+        }
+        Node::NestedLoopJoin {
+            child_left,
+            child_right,
+            predicate,
+            join_type,
+        } => {
+            if let Some(predicate) = &predicate {
+                let predicate_schema = join_predicate_schema(
+                    &child_left.output_schema,
+                    &child_right.output_schema,
+                    &output_schema,
+                    join_type,
+                );
+                add_required_columns(&mut required, vec![predicate.clone()], &predicate_schema);
+            }
+
+            let (child_left, child_right, kept, changed) =
+                prune_join_columns(*child_left, *child_right, required, join_type)?;
+
+            let node = Node::NestedLoopJoin {
+                child_left: Box::new(child_left),
+                child_right: Box::new(child_right),
+                predicate,
+                join_type,
+            };
+            Ok(Pruned::new(output_schema, node, kept, changed))
+        }
+        Node::HashJoin {
+            child_left,
+            child_right,
+            left_keys,
+            right_keys,
+            predicate,
+            join_type,
+            build_left,
+        } => {
+            let predicate_schema = join_predicate_schema(
+                &child_left.output_schema,
+                &child_right.output_schema,
+                &output_schema,
+                join_type,
+            );
+            let mut exprs = left_keys.clone();
+            exprs.extend(right_keys.iter().cloned());
+            exprs.extend(predicate.iter().cloned());
+            add_required_columns(&mut required, exprs, &predicate_schema);
+
+            let (child_left, child_right, kept, changed) =
+                prune_join_columns(*child_left, *child_right, required, join_type)?;
+
+            let node = Node::HashJoin {
+                child_left: Box::new(child_left),
+                child_right: Box::new(child_right),
+                left_keys,
+                right_keys,
+                predicate,
+                join_type,
+                build_left,
+            };
+            Ok(Pruned::new(output_schema, node, kept, changed))
+        }
+        // the columns of the alias are the columns of the subquery in the same order
+        Node::SubqueryAlias { alias, child } => {
+            let child = prune_columns(*child, required)?;
+
+            let node = Node::SubqueryAlias {
+                alias,
+                child: Box::new(child.plan_node),
+            };
+            Ok(Pruned::new(output_schema, node, child.kept, child.changed))
+        }
+        // the common table expression is pruned when the rule visits the projections inside it
+        Node::MaterializedCte { id, cte, child } => {
+            let child = prune_columns(*child, required)?;
+
+            let node = Node::MaterializedCte {
+                id,
+                cte,
+                child: Box::new(child.plan_node),
+            };
+            Ok(Pruned::new(output_schema, node, child.kept, child.changed))
+        }
+        // a correlated subquery reads every column of the row it runs for
+        Node::Apply {
+            kind,
+            correlated,
+            child,
+            subquery,
+        } => {
+            let child_len = child.output_schema.columns.len();
+
+            let mut child_required = match correlated {
+                Some(_) => (0..child_len).collect(),
+                None => required
+                    .iter()
+                    .filter(|i| **i < child_len)
+                    .cloned()
+                    .collect(),
+            };
+            if let SubqueryKind::In(expr) = &kind {
+                add_required_columns(
+                    &mut child_required,
+                    vec![expr.clone()],
+                    &child.output_schema,
+                );
+            }
+
+            let child = prune_columns(*child, child_required)?;
+
+            // the result of the subquery is always kept after the columns of the child
+            let mut kept = child.kept;
+            kept.push(child_len);
+
+            let node = Node::Apply {
+                kind,
+                correlated,
+                child: Box::new(child.plan_node),
+                subquery,
+            };
+            Ok(Pruned::new(output_schema, node, kept, child.changed))
+        }
+        // every column of a set operation is compared between the inputs so none of them can be removed
+        // the rows of a recursive query are compared with each other and read back by the recursive term so none of the columns can be removed
+        node @ (Node::SetOperation { .. }
+        | Node::RecursiveCte { .. }
+        | Node::WorkTableScan { .. }) => {
+            let kept = (0..output_schema.columns.len()).collect();
+            Ok(Pruned::new(output_schema, node, kept, false))
+        }
+        // the output of an aggregate comes from its own expressions which are pruned when the rule visits it
+        node @ (Node::Aggregate { .. } | Node::Empty {}) => {
+            let kept = (0..output_schema.columns.len()).collect();
+            Ok(Pruned::new(output_schema, node, kept, false))
+        }
+    }
+}
+
+// splits the required columns of a join between both sides and returns the join output columns that are kept
+fn prune_join_columns(
+    child_left: PlanNode,
+    child_right: PlanNode,
+    required: BTreeSet<usize>,
+    join_type: JoinType,
+) -> Result<(PlanNode, PlanNode, Vec<usize>, bool), Error> {
+    let left_len = child_left.output_schema.columns.len();
+
+    let left_required = required
+        .iter()
+        .filter(|i| **i < left_len)
+        .cloned()
+        .collect();
+    let right_required = required
+        .iter()
+        .filter(|i| **i >= left_len)
+        .map(|i| i - left_len)
+        .collect();
+
+    let left = prune_columns(child_left, left_required)?;
+    let right = prune_columns(child_right, right_required)?;
+
+    // the right columns of a semi or anti join are only read by its predicate
+    let mut kept = left.kept;
+    if !join_type.outputs_left_only() {
+        kept.extend(right.kept.iter().map(|i| i + left_len));
+    }
+
+    Ok((
+        left.plan_node,
+        right.plan_node,
+        kept,
+        left.changed || right.changed,
+    ))
+}
+
+fn select_columns(output_schema: &OutputSchema, indexes: &[usize]) -> OutputSchema {
+    OutputSchema {
+        columns: indexes
+            .iter()
+            .map(|i| output_schema.columns[*i].clone())
+            .collect(),
+    }
+}
@@ -0,0 +1,459 @@
+use sqlparser::ast::{BinaryOperator, Expr, Function, SelectItem, UnaryOperator, Value};
+
+use crate::{
+    executor::expression::ExprEvaluator,
+    planner::{binder::infer_type, JoinType, Node, OutputSchema, PlanNode, SubqueryKind},
+    types::{error::Error, field::Field, Column},
+};
+
+use super::{
+    utils::{combine_conjunction, referenced_columns, replace_columns, split_conjunction},
+    OptimizerRule, Transformed,
+};
+
+// rewrites correlated subqueries into joins so they do not run again for every row of the outer query
+// EXISTS and IN in a filter become semi joins, NOT EXISTS and NOT IN without nulls become anti joins
+// and a scalar aggregate becomes a left join with the aggregate grouped by the correlated columns
+pub struct SubqueryDecorrelation {}
+
+impl OptimizerRule for SubqueryDecorrelation {
+    fn name(&self) -> &'static str {
+        "subquery_decorrelation"
+    }
+
+    fn rewrite(&self, plan_node: PlanNode) -> Result<Transformed, Error> {
+        let rewritten = match &plan_node.node {
+            Node::Filter { filter, child } => decorrelate_filter(filter, child, &plan_node),
+            Node::Apply {
+                kind: SubqueryKind::Scalar,
+                correlated: Some(id),
+                child,
+                subquery,
+            } => decorrelate_scalar(*id, child, subquery, &plan_node.output_schema),
+            _ => None,
+        };
+
+        match rewritten {
+            Some(plan_node) => Ok(Transformed::Yes(plan_node)),
+            None => Ok(Transformed::No(plan_node)),
+        }
+    }
+}
+
+// the input of a correlated subquery is its FROM joined with the row of the outer query it runs for
+struct CorrelatedInput<'a> {
+    // the predicates of the WHERE of the subquery
+    conjuncts: Vec<Expr>,
+    // the schema the predicates are bound against, the outer columns followed by the columns of the FROM
+    schema: &'a OutputSchema,
+    from: &'a PlanNode,
+}
+
+// matches the filter over the join with the outer row that the planner builds for a correlated subquery
+fn correlated_input(plan_node: &PlanNode, id: usize) -> Option<CorrelatedInput<'_>> {
+    let Node::Filter { filter, child } = &plan_node.node else {
+        return None;
+    };
+    let Node::NestedLoopJoin {
+        child_left,
+        child_right,
+        predicate: None,
+        join_type: JoinType::Inner,
+    } = &child.node
+    else {
+        return None;
+    };
+    match child_left.node {
+        Node::WorkTableScan { id: scan_id } if scan_id == id => {}
+        _ => return None,
+    }
+
+    Some(CorrelatedInput {
+        conjuncts: split_conjunction(filter),
+        schema: &child.output_schema,
+        from: child_right,
+    })
+}
+
+// rewrites the columns of an expression bound against one schema to the names of the same columns in another
+// the columns are mapped by their index after removing the offset and every new name must resolve back to its column
+fn map_columns(expr: &Expr, from: &OutputSchema, to: &OutputSchema, offset: usize) -> Option<Expr> {
+    replace_columns(expr, &mut |name| {
+        let index = from.resolve(name).ok()?.checked_sub(offset)?;
+        let expr = to.columns.get(index)?.as_expr();
+        let name = referenced_columns(&expr)?.pop()?;
+        if to.resolve(&name).ok()? == index {
+            Some(expr)
+        } else {
+            None
+        }
+    })
+}
+
+// the columns of the schema as expressions, None if any of them cannot be referenced by its own name
+fn column_exprs(schema: &OutputSchema) -> Option<Vec<SelectItem>> {
+    let columns = schema.columns.iter().enumerate().map(|(i, column)| {
+        let expr = column.as_expr();
+        let name = referenced_columns(&expr)?.pop()?;
+        (schema.resolve(&name).ok()? == i).then_some(SelectItem::UnnamedExpr(expr))
+    });
+    columns.collect()
+}
+
+// whether the expression reads the columns of the outer row and the columns of the FROM of the subquery
+fn column_sides(expr: &Expr, schema: &OutputSchema, outer_len: usize) -> Option<(bool, bool)> {
+    let mut outer = false;
+    let mut inner = false;
+    for column in referenced_columns(expr)? {
+        if schema.resolve(&column).ok()? < outer_len {
+            outer = true;
+        } else {
+            inner = true;
+        }
+    }
+    Some((outer, inner))
+}
+
+// Filter(#subquery.value, Apply(outer, subquery)) becomes a semi join of the outer rows with the FROM of the subquery
+// on the predicates of the subquery, the result column is added back as the value it had for the rows that are kept
+fn decorrelate_filter(filter: &Expr, child: &PlanNode, plan_node: &PlanNode) -> Option<PlanNode> {
+    let Node::Apply {
+        kind,
+        correlated: Some(id),
+        child: outer,
+        subquery,
+    } = &child.node
+    else {
+        return None;
+    };
+
+    let result_column = child.output_schema.columns.last()?;
+    let result_name = referenced_columns(&result_column.as_expr())?.pop()?;
+    let is_result = |expr: &Expr| referenced_columns(expr).map(|c| c == vec![result_name.clone()]);
+
+    // the result has to be one of the conjuncts on its own and not be used by any of the others
+    let mut conjuncts = split_conjunction(filter);
+    let mut join_type = None;
+    for (i, conjunct) in conjuncts.iter().enumerate() {
+        let conjunct_type = match conjunct {
+            Expr::Identifier(_) | Expr::CompoundIdentifier(_) => JoinType::Semi,
+            Expr::UnaryOp {
+                op: UnaryOperator::Not,
+                expr,
+            } if matches!(**expr, Expr::Identifier(_) | Expr::CompoundIdentifier(_)) => {
+                JoinType::Anti
+            }
+            _ => JoinType::Inner,
+        };
+        match referenced_columns(conjunct)?.contains(&result_name) {
+            true if conjunct_type != JoinType::Inner && is_result(conjunct)? => {
+                if join_type.is_some() {
+                    return None;
+                }
+                join_type = Some((i, conjunct_type));
+            }
+            true => return None,
+            false => {}
+        }
+    }
+    let (position, join_type) = join_type?;
+    conjuncts.remove(position);
+
+    let Node::Projection {
+        select,
+        child: subquery_child,
+    } = &subquery.node
+    else {
+        return None;
+    };
+    let input = correlated_input(subquery_child, *id)?;
+    let join_schema = {
+        let mut schema = outer.output_schema.clone();
+        schema
+            .columns
+            .extend(input.from.output_schema.columns.iter().cloned());
+        schema
+    };
+    if join_schema.columns.len() != input.schema.columns.len() {
+        return None;
+    }
+
+    let mut predicates = input
+        .conjuncts
+        .iter()
+        .map(|conjunct| map_columns(conjunct, input.schema, &join_schema, 0))
+        .collect::<Option<Vec<Expr>>>()?;
+
+    if let SubqueryKind::In(expr) = kind {
+        let item = match select.as_slice() {
+            [SelectItem::UnnamedExpr(item) | SelectItem::ExprWithAlias { expr: item, .. }] => item,
+            _ => return None,
+        };
+        // a null on either side makes NOT IN unknown instead of true so it cannot be an anti join
+        if join_type == JoinType::Anti
+            && (infer_type(expr, &outer.output_schema).1 || infer_type(item, input.schema).1)
+        {
+            return None;
+        }
+        predicates.push(Expr::BinaryOp {
+            left: Box::new(map_columns(expr, &outer.output_schema, &join_schema, 0)?),
+            op: BinaryOperator::Eq,
+            right: Box::new(map_columns(item, input.schema, &join_schema, 0)?),
+        });
+    }
+
+    let join = PlanNode {
+        output_schema: outer.output_schema.clone(),
+        node: Node::NestedLoopJoin {
+            child_left: outer.clone(),
+            child_right: Box::new(input.from.clone()),
+            predicate: combine_conjunction(predicates),
+            join_type,
+        },
+    };
+
+    // the rows that are kept are the ones where the result made the conjunct true
+    let mut select = column_exprs(&outer.output_schema)?;
+    select.push(SelectItem::UnnamedExpr(Expr::Value(Value::Boolean(
+        join_type == JoinType::Semi,
+    ))));
+    let projection = PlanNode {
+        output_schema: child.output_schema.clone(),
+        node: Node::Projection {
+            select,
+            child: Box::new(join),
+        },
+    };
+
+    Some(match combine_conjunction(conjuncts) {
+        Some(filter) => PlanNode {
+            output_schema: plan_node.output_schema.clone(),
+            node: Node::Filter {
+                filter,
+                child: Box::new(projection),
+            },
+        },
+        None => projection,
+    })
+}
+
+// Apply(outer, Projection(Aggregate(Filter(inner = outer, ...)))) becomes a left join of the outer rows with the
+// aggregate grouped by the inner columns of the correlated equalities, so the aggregate runs once for all the rows
+fn decorrelate_scalar(
+    id: usize,
+    outer: &PlanNode,
+    subquery: &PlanNode,
+    output_schema: &OutputSchema,
+) -> Option<PlanNode> {
+    let Node::Projection { select, child } = &subquery.node else {
+        return None;
+    };
+    let item = match select.as_slice() {
+        [SelectItem::UnnamedExpr(item) | SelectItem::ExprWithAlias { expr: item, .. }] => item,
+        _ => return None,
+    };
+    let Node::Aggregate {
+        child: aggregate_child,
+        aggregates,
+        non_aggregates,
+        group_by,
+    } = &child.node
+    else {
+        return None;
+    };
+    if !group_by.is_empty() {
+        return None;
+    }
+    let input = correlated_input(aggregate_child, id)?;
+    let from_schema = &input.from.output_schema;
+    let outer_len = outer.output_schema.columns.len();
+    if input.schema.columns.len() != outer_len + from_schema.columns.len() {
+        return None;
+    }
+
+    // the correlated equalities become the group by of the aggregate and the predicate of the join
+    let mut outer_keys = Vec::new();
+    let mut inner_keys = Vec::new();
+    let mut inner_conjuncts = Vec::new();
+    for conjunct in input.conjuncts.iter() {
+        match column_sides(conjunct, input.schema, outer_len)? {
+            (false, _) => {
+                inner_conjuncts.push(map_columns(conjunct, input.schema, from_schema, outer_len)?)
+            }
+            (true, _) => {
+                let Expr::BinaryOp {
+                    left,
+                    op: BinaryOperator::Eq,
+                    right,
+                } = conjunct
+                else {
+                    return None;
+                };
+                let (inner, outer_expr) = match (
+                    column_sides(left, input.schema, outer_len)?,
+                    column_sides(right, input.schema, outer_len)?,
+                ) {
+                    ((false, true), (true, false)) => (left, right),
+                    ((true, false), (false, true)) => (right, left),
+                    _ => return None,
+                };
+                if !matches!(**inner, Expr::Identifier(_) | Expr::CompoundIdentifier(_)) {
+                    return None;
+                }
+                inner_keys.push(map_columns(inner, input.schema, from_schema, outer_len)?);
+                outer_keys.push(map_columns(
+                    outer_expr,
+                    input.schema,
+                    &outer.output_schema,
+                    0,
+                )?);
+            }
+        }
+    }
+    if inner_keys.is_empty() {
+        return None;
+    }
+
+    // the aggregate reads the FROM of the subquery directly so it must not use the outer row
+    let to_from = |expr: &Expr| map_columns(expr, input.schema, from_schema, outer_len);
+    let aggregates = aggregates
+        .iter()
+        .map(
+            |function| match to_from(&Expr::Function(function.clone()))? {
+                Expr::Function(function) => Some(function),
+                _ => None,
+            },
+        )
+        .collect::<Option<Vec<Function>>>()?;
+    let mut non_aggregates = non_aggregates
+        .iter()
+        .map(|item| match item {
+            SelectItem::UnnamedExpr(expr) => Some(SelectItem::UnnamedExpr(to_from(expr)?)),
+            SelectItem::ExprWithAlias { expr, alias } => Some(SelectItem::ExprWithAlias {
+                expr: to_from(expr)?,
+                alias: alias.clone(),
+            }),
+            _ => None,
+        })
+        .collect::<Option<Vec<SelectItem>>>()?;
+
+    let result_column = output_schema.columns.last()?.clone();
+    let key_column = |i: usize, column: &Column| Column {
+        label: None,
+        table: result_column.table.clone(),
+        column_name: format!("key{}", i),
+        data_type: column.data_type,
+        nullable: true,
+        hidden: true,
+    };
+
+    let mut aggregate_schema = child.output_schema.clone();
+    let mut key_columns = Vec::new();
+    for (i, key) in inner_keys.iter().enumerate() {
+        let column =
+            &from_schema.columns[from_schema.resolve(&referenced_columns(key)?.pop()?).ok()?];
+        key_columns.push(key_column(i, column));
+        aggregate_schema.columns.push(key_column(i, column));
+        non_aggregates.push(SelectItem::UnnamedExpr(key.clone()));
+    }
+
+    let filtered_from = match combine_conjunction(inner_conjuncts) {
+        Some(filter) => PlanNode {
+            output_schema: from_schema.clone(),
+            node: Node::Filter {
+                filter,
+                child: Box::new(input.from.clone()),
+            },
+        },
+        None => input.from.clone(),
+    };
+    let aggregate = PlanNode {
+        output_schema: aggregate_schema,
+        node: Node::Aggregate {
+            child: Box::new(filtered_from),
+            aggregates: aggregates.clone(),
+            non_aggregates,
+            group_by: inner_keys,
+        },
+    };
+
+    // the value of the subquery and the keys it belongs to under names the outer query cannot clash with
+    let mut grouped_select = vec![SelectItem::UnnamedExpr(item.clone())];
+    let mut grouped_schema = OutputSchema {
+        columns: vec![Column {
+            nullable: true,
+            ..result_column.clone()
+        }],
+    };
+    for column in key_columns.iter() {
+        grouped_select.push(SelectItem::UnnamedExpr(column.as_expr()));
+        grouped_schema.columns.push(column.clone());
+    }
+    let grouped = PlanNode {
+        output_schema: grouped_schema,
+        node: Node::Projection {
+            select: grouped_select,
+            child: Box::new(aggregate),
+        },
+    };
+
+    let mut join_schema = outer.output_schema.clone();
+    join_schema
+        .columns
+        .extend(grouped.output_schema.columns.iter().cloned());
+    let predicate = outer_keys
+        .into_iter()
+        .zip(key_columns.iter())
+        .map(|(outer_key, key_column)| {
+            Some(Expr::BinaryOp {
+                left: Box::new(map_columns(
+                    &outer_key,
+                    &outer.output_schema,
+                    &join_schema,
+                    0,
+                )?),
+                op: BinaryOperator::Eq,
+                right: Box::new(key_column.as_expr()),
+            })
+        })
+        .collect::<Option<Vec<Expr>>>()?;
+    let join = PlanNode {
+        output_schema: join_schema,
+        node: Node::NestedLoopJoin {
+            child_left: Box::new(outer.clone()),
+            child_right: Box::new(grouped),
+            predicate: combine_conjunction(predicate),
+            join_type: JoinType::Left,
+        },
+    };
+
+    // outer rows without a group get the value the aggregate has without rows, which is not null for count
+    let empty_value = replace_columns(item, &mut |name| {
+        let index = child.output_schema.resolve(name).ok()?;
+        match aggregates.get(index) {
+            Some(function) if function.name.to_string().to_lowercase() == "count" => {
+                Some(Expr::Value(Value::Number("0".to_string(), false)))
+            }
+            _ => Some(Expr::Value(Value::Null)),
+        }
+    })?;
+    let value = match ExprEvaluator::evaluate_scalar(&empty_value).ok()? {
+        Field::Null => result_column.as_expr(),
+        _ => Expr::Case {
+            operand: None,
+            conditions: vec![Expr::IsNull(Box::new(key_columns[0].as_expr()))],
+            results: vec![empty_value],
+            else_result: Some(Box::new(result_column.as_expr())),
+        },
+    };
+
+    let mut select = column_exprs(&outer.output_schema)?;
+    select.push(SelectItem::UnnamedExpr(value));
+    Some(PlanNode {
+        output_schema: output_schema.clone(),
+        node: Node::Projection {
+            select,
+            child: Box::new(join),
+        },
+    })
+}
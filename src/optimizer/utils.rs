@@ -0,0 +1,339 @@
+use sqlparser::ast::{BinaryOperator, Expr, FunctionArg, FunctionArgExpr};
+
+use crate::planner::OutputSchema;
+
+// splits an expression into the expressions that are combined with AND
+pub fn split_conjunction(expr: &Expr) -> Vec<Expr> {
+    match expr {
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => {
+            let mut res = split_conjunction(left);
+            res.append(&mut split_conjunction(right));
+            res
+        }
+        Expr::Nested(expr) => split_conjunction(expr),
+        _ => vec![expr.clone()],
+    }
+}
+
+// combines expressions with AND, the inverse of split_conjunction
+pub fn combine_conjunction(exprs: Vec<Expr>) -> Option<Expr> {
+    exprs.into_iter().reduce(|left, right| Expr::BinaryOp {
+        left: Box::new(left),
+        op: BinaryOperator::And,
+        right: Box::new(right),
+    })
+}
+
+// returns the names of the columns referenced by an expression or None if the expression contains something we cannot analyze
+pub fn referenced_columns(expr: &Expr) -> Option<Vec<String>> {
+    match expr {
+        Expr::Identifier(ident) => Some(vec![ident.value.clone()]),
+        Expr::CompoundIdentifier(idents) => Some(vec![idents
+            .iter()
+            .map(|i| i.value.clone())
+            .collect::<Vec<String>>()
+            .join(".")]),
+        Expr::Value(_) | Expr::TypedString { .. } | Expr::Interval(_) => Some(vec![]),
+        Expr::Nested(expr) => referenced_columns(expr),
+        Expr::UnaryOp { expr, .. }
+        | Expr::IsNull(expr)
+        | Expr::IsNotNull(expr)
+        | Expr::IsTrue(expr)
+        | Expr::IsNotTrue(expr)
+        | Expr::IsFalse(expr)
+        | Expr::IsNotFalse(expr)
+        | Expr::IsUnknown(expr)
+        | Expr::IsNotUnknown(expr)
+        | Expr::Cast { expr, .. }
+        | Expr::TryCast { expr, .. }
+        | Expr::SafeCast { expr, .. }
+        | Expr::Ceil { expr, .. }
+        | Expr::Floor { expr, .. }
+        | Expr::Extract { expr, .. } => referenced_columns(expr),
+        Expr::Trim {
+            expr, trim_what, ..
+        } => {
+            let mut res = referenced_columns(expr)?;
+            if let Some(what) = trim_what {
+                res.append(&mut referenced_columns(what)?);
+            }
+            Some(res)
+        }
+        Expr::Substring {
+            expr,
+            substring_from,
+            substring_for,
+        } => {
+            let mut res = referenced_columns(expr)?;
+            for arg in substring_from.iter().chain(substring_for.iter()) {
+                res.append(&mut referenced_columns(arg)?);
+            }
+            Some(res)
+        }
+        Expr::BinaryOp { left, right, .. }
+        | Expr::IsDistinctFrom(left, right)
+        | Expr::IsNotDistinctFrom(left, right)
+        | Expr::Like {
+            expr: left,
+            pattern: right,
+            ..
+        }
+        | Expr::ILike {
+            expr: left,
+            pattern: right,
+            ..
+        }
+        | Expr::SimilarTo {
+            expr: left,
+            pattern: right,
+            ..
+        } => {
+            let mut res = referenced_columns(left)?;
+            res.append(&mut referenced_columns(right)?);
+            Some(res)
+        }
+        Expr::Case {
+            operand,
+            conditions,
+            results,
+            else_result,
+        } => {
+            let mut res = Vec::new();
+            for expr in operand
+                .iter()
+                .chain(else_result.iter())
+                .map(|expr| expr.as_ref())
+                .chain(conditions)
+                .chain(results)
+            {
+                res.append(&mut referenced_columns(expr)?);
+            }
+            Some(res)
+        }
+        Expr::InList { expr, list, .. } => {
+            let mut res = referenced_columns(expr)?;
+            for item in list {
+                res.append(&mut referenced_columns(item)?);
+            }
+            Some(res)
+        }
+        Expr::Between {
+            expr, low, high, ..
+        } => {
+            let mut res = referenced_columns(expr)?;
+            res.append(&mut referenced_columns(low)?);
+            res.append(&mut referenced_columns(high)?);
+            Some(res)
+        }
+        Expr::Function(function) => {
+            let mut res = Vec::new();
+            for arg in &function.args {
+                match arg {
+                    FunctionArg::Named { arg, .. } | FunctionArg::Unnamed(arg) => match arg {
+                        FunctionArgExpr::Expr(expr) => res.append(&mut referenced_columns(expr)?),
+                        FunctionArgExpr::Wildcard => {}
+                        FunctionArgExpr::QualifiedWildcard(_) => return None,
+                    },
+                }
+            }
+            Some(res)
+        }
+        _ => None,
+    }
+}
+
+// checks whether every column referenced by an expression can be resolved in the output schema
+pub fn is_bound_by(expr: &Expr, output_schema: &OutputSchema) -> bool {
+    match referenced_columns(expr) {
+        Some(columns) => columns
+            .iter()
+            .all(|column| output_schema.resolve(column).is_ok()),
+        None => false,
+    }
+}
+
+// replaces every column referenced by an expression with the expression returned for it or returns None if any column cannot be replaced
+pub fn replace_columns<F>(expr: &Expr, f: &mut F) -> Option<Expr>
+where
+    F: FnMut(&str) -> Option<Expr>,
+{
+    match expr {
+        Expr::Identifier(_) | Expr::CompoundIdentifier(_) => {
+            let column = referenced_columns(expr)?.pop()?;
+            f(&column)
+        }
+        Expr::Value(_) | Expr::TypedString { .. } | Expr::Interval(_) => Some(expr.clone()),
+        Expr::Nested(expr) => Some(Expr::Nested(Box::new(replace_columns(expr, f)?))),
+        Expr::UnaryOp { op, expr } => Some(Expr::UnaryOp {
+            op: *op,
+            expr: Box::new(replace_columns(expr, f)?),
+        }),
+        Expr::BinaryOp { left, op, right } => Some(Expr::BinaryOp {
+            left: Box::new(replace_columns(left, f)?),
+            op: op.clone(),
+            right: Box::new(replace_columns(right, f)?),
+        }),
+        Expr::IsNull(expr) => Some(Expr::IsNull(Box::new(replace_columns(expr, f)?))),
+        Expr::IsNotNull(expr) => Some(Expr::IsNotNull(Box::new(replace_columns(expr, f)?))),
+        Expr::IsTrue(expr) => Some(Expr::IsTrue(Box::new(replace_columns(expr, f)?))),
+        Expr::IsNotTrue(expr) => Some(Expr::IsNotTrue(Box::new(replace_columns(expr, f)?))),
+        Expr::IsFalse(expr) => Some(Expr::IsFalse(Box::new(replace_columns(expr, f)?))),
+        Expr::IsNotFalse(expr) => Some(Expr::IsNotFalse(Box::new(replace_columns(expr, f)?))),
+        Expr::IsUnknown(expr) => Some(Expr::IsUnknown(Box::new(replace_columns(expr, f)?))),
+        Expr::IsNotUnknown(expr) => Some(Expr::IsNotUnknown(Box::new(replace_columns(expr, f)?))),
+        Expr::Cast { expr, data_type } => Some(Expr::Cast {
+            expr: Box::new(replace_columns(expr, f)?),
+            data_type: data_type.clone(),
+        }),
+        Expr::TryCast { expr, data_type } => Some(Expr::TryCast {
+            expr: Box::new(replace_columns(expr, f)?),
+            data_type: data_type.clone(),
+        }),
+        Expr::SafeCast { expr, data_type } => Some(Expr::SafeCast {
+            expr: Box::new(replace_columns(expr, f)?),
+            data_type: data_type.clone(),
+        }),
+        Expr::IsDistinctFrom(left, right) => Some(Expr::IsDistinctFrom(
+            Box::new(replace_columns(left, f)?),
+            Box::new(replace_columns(right, f)?),
+        )),
+        Expr::IsNotDistinctFrom(left, right) => Some(Expr::IsNotDistinctFrom(
+            Box::new(replace_columns(left, f)?),
+            Box::new(replace_columns(right, f)?),
+        )),
+        Expr::Ceil { expr, field } => Some(Expr::Ceil {
+            expr: Box::new(replace_columns(expr, f)?),
+            field: *field,
+        }),
+        Expr::Floor { expr, field } => Some(Expr::Floor {
+            expr: Box::new(replace_columns(expr, f)?),
+            field: *field,
+        }),
+        Expr::Extract { field, expr } => Some(Expr::Extract {
+            field: *field,
+            expr: Box::new(replace_columns(expr, f)?),
+        }),
+        Expr::Trim {
+            expr,
+            trim_where,
+            trim_what,
+        } => Some(Expr::Trim {
+            expr: Box::new(replace_columns(expr, f)?),
+            trim_where: *trim_where,
+            trim_what: match trim_what {
+                Some(what) => Some(Box::new(replace_columns(what, f)?)),
+                None => None,
+            },
+        }),
+        Expr::Substring {
+            expr,
+            substring_from,
+            substring_for,
+        } => Some(Expr::Substring {
+            expr: Box::new(replace_columns(expr, f)?),
+            substring_from: match substring_from {
+                Some(from) => Some(Box::new(replace_columns(from, f)?)),
+                None => None,
+            },
+            substring_for: match substring_for {
+                Some(count) => Some(Box::new(replace_columns(count, f)?)),
+                None => None,
+            },
+        }),
+        Expr::Like {
+            negated,
+            expr,
+            pattern,
+            escape_char,
+        } => Some(Expr::Like {
+            negated: *negated,
+            expr: Box::new(replace_columns(expr, f)?),
+            pattern: Box::new(replace_columns(pattern, f)?),
+            escape_char: *escape_char,
+        }),
+        Expr::ILike {
+            negated,
+            expr,
+            pattern,
+            escape_char,
+        } => Some(Expr::ILike {
+            negated: *negated,
+            expr: Box::new(replace_columns(expr, f)?),
+            pattern: Box::new(replace_columns(pattern, f)?),
+            escape_char: *escape_char,
+        }),
+        Expr::SimilarTo {
+            negated,
+            expr,
+            pattern,
+            escape_char,
+        } => Some(Expr::SimilarTo {
+            negated: *negated,
+            expr: Box::new(replace_columns(expr, f)?),
+            pattern: Box::new(replace_columns(pattern, f)?),
+            escape_char: *escape_char,
+        }),
+        Expr::Case {
+            operand,
+            conditions,
+            results,
+            else_result,
+        } => Some(Expr::Case {
+            operand: match operand {
+                Some(operand) => Some(Box::new(replace_columns(operand, f)?)),
+                None => None,
+            },
+            conditions: replace_all(conditions, f)?,
+            results: replace_all(results, f)?,
+            else_result: match else_result {
+                Some(else_result) => Some(Box::new(replace_columns(else_result, f)?)),
+                None => None,
+            },
+        }),
+        Expr::InList {
+            expr,
+            list,
+            negated,
+        } => Some(Expr::InList {
+            expr: Box::new(replace_columns(expr, f)?),
+            list: replace_all(list, f)?,
+            negated: *negated,
+        }),
+        Expr::Between {
+            expr,
+            negated,
+            low,
+            high,
+        } => Some(Expr::Between {
+            expr: Box::new(replace_columns(expr, f)?),
+            negated: *negated,
+            low: Box::new(replace_columns(low, f)?),
+            high: Box::new(replace_columns(high, f)?),
+        }),
+        Expr::Function(function) => {
+            let mut function = function.clone();
+            for arg in function.args.iter_mut() {
+                match arg {
+                    FunctionArg::Named { arg, .. } | FunctionArg::Unnamed(arg) => match arg {
+                        FunctionArgExpr::Expr(expr) => *expr = replace_columns(expr, f)?,
+                        FunctionArgExpr::Wildcard => {}
+                        FunctionArgExpr::QualifiedWildcard(_) => return None,
+                    },
+                }
+            }
+            Some(Expr::Function(function))
+        }
+        _ => None,
+    }
+}
+
+fn replace_all<F>(exprs: &[Expr], f: &mut F) -> Option<Vec<Expr>>
+where
+    F: FnMut(&str) -> Option<Expr>,
+{
+    exprs.iter().map(|expr| replace_columns(expr, f)).collect()
+}
@@ -0,0 +1,1046 @@
+use sqlparser::ast::{
+    BinaryOperator, Expr, Function, FunctionArg, FunctionArgExpr, Ident, SelectItem, SetOperator,
+    UnaryOperator, Value,
+};
+
+use crate::{
+    executor::{expression::ExprEvaluator, function::ScalarFunction},
+    types::{coercion, data_type::DataType, error::Error, field::Field, Column},
+};
+
+use super::{JoinType, Node, OutputSchema, PlanNode, SubqueryKind};
+
+const AGGREGATE_FUNCTIONS: [&str; 5] = ["count", "sum", "min", "max", "avg"];
+
+// other functions are evaluated for every row
+pub fn is_aggregate(function: &Function) -> bool {
+    AGGREGATE_FUNCTIONS.contains(&function.name.to_string().as_str())
+}
+
+// checks every expression in the plan against the output schema of the node it reads from
+// this rejects unknown columns, operands of the wrong types and unsupported functions before any data is read
+pub fn bind(plan_node: &PlanNode) -> Result<(), Error> {
+    match &plan_node.node {
+        Node::Scan { filter, .. } => {
+            if let Some(filter) = filter {
+                bind_predicate(filter, &plan_node.output_schema)?;
+            }
+        }
+        Node::NestedLoopJoin {
+            child_left,
+            child_right,
+            predicate,
+            join_type,
+        } => {
+            bind(child_left)?;
+            bind(child_right)?;
+            if let Some(predicate) = predicate {
+                bind_predicate(
+                    predicate,
+                    &join_predicate_schema(
+                        &child_left.output_schema,
+                        &child_right.output_schema,
+                        &plan_node.output_schema,
+                        *join_type,
+                    ),
+                )?;
+            }
+        }
+        Node::HashJoin {
+            child_left,
+            child_right,
+            left_keys,
+            right_keys,
+            predicate,
+            join_type,
+            ..
+        } => {
+            bind(child_left)?;
+            bind(child_right)?;
+            for key in left_keys {
+                bind_expr(key, &child_left.output_schema)?;
+            }
+            for key in right_keys {
+                bind_expr(key, &child_right.output_schema)?;
+            }
+            if let Some(predicate) = predicate {
+                bind_predicate(
+                    predicate,
+                    &join_predicate_schema(
+                        &child_left.output_schema,
+                        &child_right.output_schema,
+                        &plan_node.output_schema,
+                        *join_type,
+                    ),
+                )?;
+            }
+        }
+        Node::Filter { filter, child } => {
+            bind(child)?;
+            bind_predicate(filter, &child.output_schema)?;
+        }
+        Node::Projection { select, child } => {
+            bind(child)?;
+            bind_select_items(select, &child.output_schema)?;
+        }
+        Node::Aggregate {
+            child,
+            aggregates,
+            non_aggregates,
+            group_by,
+        } => {
+            bind(child)?;
+            for function in aggregates {
+                bind_aggregate(function, &child.output_schema)?;
+            }
+            for expr in group_by {
+                bind_expr(expr, &child.output_schema)?;
+            }
+            bind_select_items(non_aggregates, &child.output_schema)?;
+        }
+        Node::Sort { order_by, child } => {
+            bind(child)?;
+            for item in order_by {
+                bind_expr(&item.expr, &child.output_schema)?;
+            }
+        }
+        Node::Distinct { on, child } => {
+            bind(child)?;
+            for expr in on {
+                bind_expr(expr, &child.output_schema)?;
+            }
+        }
+        Node::SetOperation { left, right, .. } => {
+            bind(left)?;
+            bind(right)?;
+        }
+        Node::Limit { child, .. } | Node::SubqueryAlias { child, .. } => bind(child)?,
+        Node::MaterializedCte { cte, child, .. } => {
+            bind(cte)?;
+            bind(child)?;
+        }
+        Node::RecursiveCte {
+            anchor, recursive, ..
+        } => {
+            bind(anchor)?;
+            bind(recursive)?;
+        }
+        Node::Apply {
+            kind,
+            child,
+            subquery,
+            ..
+        } => {
+            bind(child)?;
+            bind(subquery)?;
+            if let SubqueryKind::In(expr) = kind {
+                let (data_type, _) = bind_expr(expr, &child.output_schema)?;
+                let subquery_type = subquery.output_schema.columns[0].data_type;
+                let data_type = literal_type(expr, data_type, subquery_type);
+                bind_comparison(data_type, subquery_type, expr)?;
+            }
+        }
+        Node::WorkTableScan { .. } | Node::Empty {} => {}
+    }
+
+    Ok(())
+}
+
+// conditions of WHERE, HAVING and joins have to be true, false or null
+fn bind_predicate(predicate: &Expr, schema: &OutputSchema) -> Result<(), Error> {
+    match bind_expr(predicate, schema)?.0 {
+        DataType::Boolean | DataType::Null | DataType::Unknown => Ok(()),
+        data_type => {
+            // a HAVING clause on a single aggregate is named by the aggregate rather than its internal column
+            let name = match predicate {
+                Expr::Identifier(ident) => schema
+                    .resolve(&ident.value)
+                    .ok()
+                    .and_then(|index| schema.columns[index].label.clone()),
+                _ => None,
+            };
+            Err(Error::Planner(format!(
+                "Condition must be BOOLEAN but {} is {}",
+                name.unwrap_or(predicate.to_string()),
+                data_type
+            )))
+        }
+    }
+}
+
+fn bind_select_items(select: &[SelectItem], schema: &OutputSchema) -> Result<(), Error> {
+    for item in select {
+        match item {
+            SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => {
+                bind_expr(expr, schema)?;
+            }
+            SelectItem::Wildcard(_) => {}
+            _ => return Err(Error::Planner(format!("{} not supported", item))),
+        }
+    }
+    Ok(())
+}
+
+// returns the type of the values the expression produces and whether they can be null
+pub fn bind_expr(expr: &Expr, schema: &OutputSchema) -> Result<(DataType, bool), Error> {
+    match expr {
+        Expr::Identifier(ident) => bind_identifier(ident, schema),
+        Expr::CompoundIdentifier(idents) => bind_identifier(
+            &Ident::new(
+                idents
+                    .iter()
+                    .map(|i| i.value.clone())
+                    .collect::<Vec<String>>()
+                    .join("."),
+            ),
+            schema,
+        ),
+        Expr::Value(value) => {
+            let field = ExprEvaluator::evaluate_value(value)
+                .map_err(|_| Error::Planner(format!("Unsupported value: {}", value)))?;
+            let data_type = DataType::of_field(&field);
+            Ok((data_type, data_type == DataType::Null))
+        }
+        Expr::TypedString { data_type, value } => match DataType::from_sql(data_type) {
+            Some(target) if DataType::String.can_cast_to(target) => Ok((target, false)),
+            _ => Err(Error::Planner(format!(
+                "Unsupported literal of type {}: '{}'",
+                data_type, value
+            ))),
+        },
+        Expr::Interval(interval) => {
+            bind_expr(&interval.value, schema)?;
+            Ok((DataType::Interval, false))
+        }
+        Expr::Nested(expr) => bind_expr(expr, schema),
+        Expr::UnaryOp { op, expr } => {
+            let (data_type, nullable) = bind_expr(expr, schema)?;
+            match op {
+                UnaryOperator::Not if is_boolean_operand(data_type) => {
+                    Ok((DataType::Boolean, nullable))
+                }
+                UnaryOperator::Plus | UnaryOperator::Minus if is_numeric_operand(data_type) => {
+                    Ok((data_type.arithmetic_type(), nullable))
+                }
+                UnaryOperator::Minus if data_type == DataType::Interval => {
+                    Ok((DataType::Interval, nullable))
+                }
+                UnaryOperator::Not | UnaryOperator::Plus | UnaryOperator::Minus => {
+                    Err(Error::Planner(format!(
+                        "Cannot apply unary operator {} to {}: {}",
+                        op, data_type, expr
+                    )))
+                }
+                _ => Err(Error::Planner(format!(
+                    "Unary operation {} not supported",
+                    op
+                ))),
+            }
+        }
+        Expr::BinaryOp { left, op, right } => {
+            let (left_type, left_nullable) = bind_expr(left, schema)?;
+            let (right_type, right_nullable) = bind_expr(right, schema)?;
+            let nullable = left_nullable || right_nullable;
+            let (left_type, right_type) = match op {
+                BinaryOperator::Eq
+                | BinaryOperator::NotEq
+                | BinaryOperator::Lt
+                | BinaryOperator::LtEq
+                | BinaryOperator::Gt
+                | BinaryOperator::GtEq => comparison_types(left, left_type, right, right_type),
+                _ => (left_type, right_type),
+            };
+
+            bind_binary_op(left_type, op, right_type)
+                .map(|data_type| (data_type, nullable))
+                .map_err(|e| match e {
+                    Error::Planner(message) => Error::Planner(format!("{}: {}", message, expr)),
+                    e => e,
+                })
+        }
+        Expr::IsNull(expr)
+        | Expr::IsNotNull(expr)
+        | Expr::IsTrue(expr)
+        | Expr::IsNotTrue(expr)
+        | Expr::IsFalse(expr)
+        | Expr::IsNotFalse(expr)
+        | Expr::IsUnknown(expr)
+        | Expr::IsNotUnknown(expr) => {
+            bind_expr(expr, schema)?;
+            Ok((DataType::Boolean, false))
+        }
+        Expr::IsDistinctFrom(left, right) | Expr::IsNotDistinctFrom(left, right) => {
+            let (left_type, _) = bind_expr(left, schema)?;
+            let (right_type, _) = bind_expr(right, schema)?;
+            let (left_type, right_type) = comparison_types(left, left_type, right, right_type);
+            bind_comparison(left_type, right_type, expr)?;
+            Ok((DataType::Boolean, false))
+        }
+        Expr::Cast {
+            expr: inner,
+            data_type,
+        }
+        | Expr::TryCast {
+            expr: inner,
+            data_type,
+        }
+        | Expr::SafeCast {
+            expr: inner,
+            data_type,
+        } => {
+            let (source, nullable) = bind_expr(inner, schema)?;
+            let Some(target) = DataType::from_sql(data_type) else {
+                return Err(Error::Planner(format!(
+                    "Unsupported cast data type: {}",
+                    data_type
+                )));
+            };
+            if !source.can_cast_to(target) {
+                return Err(Error::Planner(format!(
+                    "Cannot cast {} to {}: {}",
+                    source, target, expr
+                )));
+            }
+
+            // a value that cannot be cast is null with TRY_CAST
+            Ok((target, nullable || !matches!(expr, Expr::Cast { .. })))
+        }
+        Expr::Case {
+            operand,
+            conditions,
+            results,
+            else_result,
+        } => {
+            let operand_type = match operand {
+                Some(operand) => Some((operand, bind_expr(operand, schema)?.0)),
+                None => None,
+            };
+            for condition in conditions {
+                let (condition_type, _) = bind_expr(condition, schema)?;
+                if let Some((operand, operand_type)) = operand_type {
+                    let (operand_type, condition_type) =
+                        comparison_types(operand, operand_type, condition, condition_type);
+                    bind_comparison(operand_type, condition_type, expr)?;
+                }
+            }
+
+            let branches: Vec<&Expr> = results.iter().chain(else_result.as_deref()).collect();
+            let (data_type, nullables) = bind_common_type(&branches, schema, "Results", expr)?;
+
+            // rows that match no branch are null when there is no else
+            Ok((
+                data_type,
+                else_result.is_none() || nullables.iter().any(|nullable| *nullable),
+            ))
+        }
+        Expr::InList {
+            expr: inner, list, ..
+        } => {
+            let (data_type, mut nullable) = bind_expr(inner, schema)?;
+            for item in list {
+                let (item_type, item_nullable) = bind_expr(item, schema)?;
+                let (data_type, item_type) = comparison_types(inner, data_type, item, item_type);
+                bind_comparison(data_type, item_type, expr)?;
+                nullable |= item_nullable;
+            }
+            Ok((DataType::Boolean, nullable))
+        }
+        Expr::Between {
+            expr: inner,
+            low,
+            high,
+            ..
+        } => {
+            let (data_type, nullable) = bind_expr(inner, schema)?;
+            let (low_type, low_nullable) = bind_expr(low, schema)?;
+            let (high_type, high_nullable) = bind_expr(high, schema)?;
+            for (bound, bound_type) in [(low, low_type), (high, high_type)] {
+                let (data_type, bound_type) = comparison_types(inner, data_type, bound, bound_type);
+                bind_comparison(data_type, bound_type, expr)?;
+            }
+            Ok((DataType::Boolean, nullable || low_nullable || high_nullable))
+        }
+        Expr::Like {
+            expr: inner,
+            pattern,
+            ..
+        }
+        | Expr::ILike {
+            expr: inner,
+            pattern,
+            ..
+        }
+        | Expr::SimilarTo {
+            expr: inner,
+            pattern,
+            ..
+        } => {
+            let (data_type, nullable) = bind_expr(inner, schema)?;
+            let (pattern_type, pattern_nullable) = bind_expr(pattern, schema)?;
+            if !is_string_operand(data_type) || !is_string_operand(pattern_type) {
+                return Err(Error::Planner(format!(
+                    "Cannot match {} with a {} pattern: {}",
+                    data_type, pattern_type, expr
+                )));
+            }
+            Ok((DataType::Boolean, nullable || pattern_nullable))
+        }
+        Expr::Function(function)
+            if matches!(
+                function.name.to_string().to_lowercase().as_str(),
+                "regexp_matches" | "regexp_replace"
+            ) =>
+        {
+            let args = function_args(function)?;
+            let replaces = function.name.to_string().to_lowercase() == "regexp_replace";
+            let count = if replaces { 3 } else { 2 };
+            if args.len() != count && args.len() != count + 1 {
+                return Err(Error::Planner(format!(
+                    "Function {} expects {} or {} arguments but got {}",
+                    function.name,
+                    count,
+                    count + 1,
+                    args.len()
+                )));
+            }
+
+            let mut nullable = false;
+            for arg in &args {
+                let (arg_type, arg_nullable) = bind_expr(arg, schema)?;
+                if !is_string_operand(arg_type) {
+                    return Err(Error::Planner(format!(
+                        "Function {} expects VARCHAR arguments but got {}: {}",
+                        function.name, arg_type, expr
+                    )));
+                }
+                nullable |= arg_nullable;
+            }
+
+            if replaces {
+                Ok((DataType::String, nullable))
+            } else {
+                Ok((DataType::Boolean, nullable))
+            }
+        }
+        Expr::Function(function) if function.name.to_string().to_lowercase() == "coalesce" => {
+            let args = function_args(function)?;
+            let (data_type, nullables) = bind_common_type(
+                &args.iter().collect::<Vec<&Expr>>(),
+                schema,
+                "Arguments",
+                expr,
+            )?;
+
+            Ok((data_type, nullables.iter().all(|nullable| *nullable)))
+        }
+        Expr::Function(function) if function.name.to_string().to_lowercase() == "nullif" => {
+            let args = function_args(function)?;
+            if args.len() != 2 {
+                return Err(Error::Planner(format!(
+                    "Function {} expects 2 arguments but got {}",
+                    function.name,
+                    args.len()
+                )));
+            }
+
+            let (data_type, _) = bind_expr(&args[0], schema)?;
+            let (other_type, _) = bind_expr(&args[1], schema)?;
+            let (data_type, other_type) =
+                comparison_types(&args[0], data_type, &args[1], other_type);
+            bind_comparison(data_type, other_type, expr)?;
+
+            // the result is null when the arguments are equal
+            Ok((data_type, true))
+        }
+        Expr::Function(function) => match ScalarFunction::from_name(&function.name.to_string()) {
+            Some(scalar_function) => {
+                bind_scalar_function(scalar_function, &function_args(function)?, schema, expr)
+            }
+            None => Err(Error::Planner(format!(
+                "Unsupported function: {}",
+                function.name
+            ))),
+        },
+        Expr::Ceil { .. }
+        | Expr::Floor { .. }
+        | Expr::Trim { .. }
+        | Expr::Substring { .. }
+        | Expr::Extract { .. } => match ScalarFunction::from_expr(expr) {
+            Some((scalar_function, args)) => {
+                bind_scalar_function(scalar_function, &args, schema, expr)
+            }
+            None => Err(Error::Planner(format!("Unsupported expression: {}", expr))),
+        },
+        _ => Err(Error::Planner(format!("Unsupported expression: {}", expr))),
+    }
+}
+
+fn bind_scalar_function(
+    function: ScalarFunction,
+    args: &[Expr],
+    schema: &OutputSchema,
+    expr: &Expr,
+) -> Result<(DataType, bool), Error> {
+    let expected = match function.arity() {
+        (min, Some(max)) if args.len() < min || args.len() > max => Some(match max - min {
+            0 if min == 1 => "1 argument".to_string(),
+            0 => format!("{} arguments", min),
+            1 => format!("{} or {} arguments", min, max),
+            _ => format!("{} to {} arguments", min, max),
+        }),
+        (min, None) if args.len() < min => Some(format!("at least {} arguments", min)),
+        _ => None,
+    };
+    if let Some(expected) = expected {
+        return Err(Error::Planner(format!(
+            "Function {} expects {} but got {}",
+            function.name(),
+            expected,
+            args.len()
+        )));
+    }
+
+    let mut arg_types = Vec::new();
+    let mut nullables = Vec::new();
+    for arg in args {
+        let (arg_type, nullable) = bind_expr(arg, schema)?;
+        arg_types.push(arg_type);
+        nullables.push(nullable);
+    }
+
+    match function.return_type(&arg_types) {
+        Some(data_type) => Ok((data_type, function.nullable(&nullables))),
+        None => Err(Error::Planner(format!(
+            "Function {} does not support {} arguments: {}",
+            function.name(),
+            arg_types
+                .iter()
+                .map(|arg_type| arg_type.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+            expr
+        ))),
+    }
+}
+
+fn bind_comparison(left: DataType, right: DataType, expr: &Expr) -> Result<(), Error> {
+    match common_type(left, right) {
+        Some(_) => Ok(()),
+        None => Err(Error::Planner(format!(
+            "Cannot compare {} with {}: {}",
+            left, right, expr
+        ))),
+    }
+}
+
+// the type the values of all the expressions can be used as and whether each of them can be null
+fn bind_common_type(
+    exprs: &[&Expr],
+    schema: &OutputSchema,
+    description: &str,
+    expr: &Expr,
+) -> Result<(DataType, Vec<bool>), Error> {
+    let mut types = Vec::new();
+    let mut nullables = Vec::new();
+    for arg in exprs {
+        let (arg_type, nullable) = bind_expr(arg, schema)?;
+        types.push(arg_type);
+        nullables.push(nullable);
+    }
+
+    // string literals are converted to the type of the other values so they are combined last
+    let (literals, others): (Vec<_>, Vec<_>) = exprs
+        .iter()
+        .zip(types)
+        .partition(|(arg, _)| is_string_literal(arg));
+    let mut data_type = DataType::Null;
+    for (arg, arg_type) in others.into_iter().chain(literals) {
+        let arg_type = literal_type(arg, arg_type, data_type);
+        data_type = match common_type(data_type, arg_type) {
+            Some(common) => common,
+            None => {
+                return Err(Error::Planner(format!(
+                    "{} of {} have incompatible types {} and {}",
+                    description, expr, data_type, arg_type
+                )))
+            }
+        };
+    }
+
+    Ok((data_type, nullables))
+}
+
+// returns the type of the result of an aggregate function over the rows of the schema
+pub fn bind_aggregate(
+    function: &Function,
+    schema: &OutputSchema,
+) -> Result<(DataType, bool), Error> {
+    let name = function.name.to_string();
+    if !AGGREGATE_FUNCTIONS.contains(&name.as_str()) {
+        return Err(Error::Planner(format!("Unsupported function: {}", name)));
+    }
+
+    if function.args.len() != 1 {
+        return Err(Error::Planner(format!(
+            "Function {} expects 1 argument but got {}",
+            name,
+            function.args.len()
+        )));
+    }
+
+    let arg_type = match &function.args[0] {
+        FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) => {
+            if contains_aggregate(expr) {
+                return Err(Error::Planner(format!(
+                    "Aggregate functions cannot be nested: {}",
+                    function
+                )));
+            }
+            bind_expr(expr, schema)?.0
+        }
+        // rows are not compared so the distinct rows cannot be counted
+        FunctionArg::Unnamed(FunctionArgExpr::Wildcard) if function.distinct => {
+            return Err(Error::Planner(format!(
+                "DISTINCT * is not supported for function {}",
+                name
+            )))
+        }
+        FunctionArg::Unnamed(FunctionArgExpr::Wildcard) if name == "count" => DataType::Unknown,
+        arg => {
+            return Err(Error::Planner(format!(
+                "Unsupported argument {} for function {}",
+                arg, name
+            )))
+        }
+    };
+
+    match name.as_str() {
+        "count" => Ok((DataType::Int, false)),
+        "sum" | "avg" if !is_numeric_operand(arg_type) => Err(Error::Planner(format!(
+            "Function {} does not support {} arguments",
+            name, arg_type
+        ))),
+        "avg" => Ok((DataType::Float, true)),
+        // integers are summed as bigints
+        "sum" if arg_type.is_integer() => Ok((DataType::Long, true)),
+        "sum" => Ok((arg_type.arithmetic_type(), true)),
+        // the aggregates of no rows are null
+        _ => Ok((arg_type, true)),
+    }
+}
+
+// the type of an expression while the plan is being built, errors are reported when the plan is bound
+pub fn infer_type(expr: &Expr, schema: &OutputSchema) -> (DataType, bool) {
+    bind_expr(expr, schema).unwrap_or((DataType::Unknown, true))
+}
+
+pub fn infer_aggregate_type(function: &Function, schema: &OutputSchema) -> (DataType, bool) {
+    bind_aggregate(function, schema).unwrap_or((DataType::Unknown, true))
+}
+
+// marks the columns that are padded with nulls by an outer join as nullable
+pub fn join_output_schema(
+    left: &OutputSchema,
+    right: &OutputSchema,
+    join_type: JoinType,
+) -> OutputSchema {
+    if join_type.outputs_left_only() {
+        return left.clone();
+    }
+
+    let mut output_schema = OutputSchema::new();
+
+    for column in &left.columns {
+        let mut column = column.clone();
+        column.nullable |= join_type.preserves_right();
+        output_schema.columns.push(column);
+    }
+    for column in &right.columns {
+        let mut column = column.clone();
+        column.nullable |= join_type.preserves_left();
+        output_schema.columns.push(column);
+    }
+
+    output_schema
+}
+
+// the columns a join predicate is evaluated on, a semi or anti join only outputs the left columns but its predicate also reads the right ones
+pub fn join_predicate_schema(
+    left: &OutputSchema,
+    right: &OutputSchema,
+    output_schema: &OutputSchema,
+    join_type: JoinType,
+) -> OutputSchema {
+    if !join_type.outputs_left_only() {
+        return output_schema.clone();
+    }
+
+    let mut predicate_schema = left.clone();
+    predicate_schema
+        .columns
+        .extend(right.columns.iter().cloned());
+    predicate_schema
+}
+
+// the columns of a set operation are named after the left input and have the type both inputs can be used as
+pub fn set_operation_output_schema(
+    left: &OutputSchema,
+    right: &OutputSchema,
+    op: &SetOperator,
+) -> Result<OutputSchema, Error> {
+    if left.columns.len() != right.columns.len() {
+        return Err(Error::Planner(format!(
+            "Each {} query must have the same number of columns",
+            op
+        )));
+    }
+
+    let mut output_schema = OutputSchema::new();
+    for (left, right) in left.columns.iter().zip(right.columns.iter()) {
+        let Some(data_type) = common_type(left.data_type, right.data_type) else {
+            return Err(Error::Planner(format!(
+                "{} types {} and {} cannot be matched",
+                op, left.data_type, right.data_type
+            )));
+        };
+
+        let name = left.output_name();
+        output_schema.add_column(Column {
+            label: Some(name.clone()),
+            table: None,
+            column_name: name,
+            data_type,
+            nullable: left.nullable || right.nullable,
+            hidden: false,
+        })?;
+    }
+
+    Ok(output_schema)
+}
+
+fn bind_identifier(ident: &Ident, schema: &OutputSchema) -> Result<(DataType, bool), Error> {
+    let index = schema.resolve(&ident.value)?;
+    Ok((
+        schema.columns[index].data_type,
+        schema.columns[index].nullable,
+    ))
+}
+
+fn bind_binary_op(left: DataType, op: &BinaryOperator, right: DataType) -> Result<DataType, Error> {
+    match op {
+        BinaryOperator::And | BinaryOperator::Or | BinaryOperator::Xor => {
+            if is_boolean_operand(left) && is_boolean_operand(right) {
+                Ok(DataType::Boolean)
+            } else {
+                Err(Error::Planner(format!(
+                    "Cannot apply operator {} to {} and {}",
+                    op, left, right
+                )))
+            }
+        }
+        BinaryOperator::Eq
+        | BinaryOperator::NotEq
+        | BinaryOperator::Lt
+        | BinaryOperator::LtEq
+        | BinaryOperator::Gt
+        | BinaryOperator::GtEq => match common_type(left, right) {
+            Some(_) => Ok(DataType::Boolean),
+            None => Err(Error::Planner(format!(
+                "Cannot compare {} with {}",
+                left, right
+            ))),
+        },
+        BinaryOperator::PGRegexMatch
+        | BinaryOperator::PGRegexIMatch
+        | BinaryOperator::PGRegexNotMatch
+        | BinaryOperator::PGRegexNotIMatch => {
+            if is_string_operand(left) && is_string_operand(right) {
+                Ok(DataType::Boolean)
+            } else {
+                Err(Error::Planner(format!(
+                    "Cannot apply operator {} to {} and {}",
+                    op, left, right
+                )))
+            }
+        }
+        BinaryOperator::Plus
+        | BinaryOperator::Minus
+        | BinaryOperator::Multiply
+        | BinaryOperator::Divide
+        | BinaryOperator::Modulo => match bind_temporal_arithmetic(left, op, right) {
+            Some(data_type) => Ok(data_type),
+            None => match common_type(left, right) {
+                Some(DataType::String) if *op == BinaryOperator::Plus => Ok(DataType::String),
+                Some(data_type) if is_numeric_operand(data_type) => Ok(data_type.arithmetic_type()),
+                _ => Err(Error::Planner(format!(
+                    "Cannot apply operator {} to {} and {}",
+                    op, left, right
+                ))),
+            },
+        },
+        _ => Err(Error::Planner(format!(
+            "Binary operation {} not supported",
+            op
+        ))),
+    }
+}
+
+// the type of arithmetic with dates, timestamps and intervals, none when the operands are not of those types
+fn bind_temporal_arithmetic(
+    left: DataType,
+    op: &BinaryOperator,
+    right: DataType,
+) -> Option<DataType> {
+    let is_days = |data_type: DataType| data_type.is_integer() || data_type == DataType::Null;
+    let is_time = |data_type: DataType| matches!(data_type, DataType::Date | DataType::Timestamp);
+    let is_interval =
+        |data_type: DataType| matches!(data_type, DataType::Interval | DataType::Null);
+
+    match op {
+        BinaryOperator::Plus | BinaryOperator::Minus
+            if left == DataType::Date && is_days(right) =>
+        {
+            Some(DataType::Date)
+        }
+        BinaryOperator::Plus if is_days(left) && right == DataType::Date => Some(DataType::Date),
+        BinaryOperator::Minus if left == DataType::Date && right == DataType::Date => {
+            Some(DataType::Int)
+        }
+        BinaryOperator::Minus if is_time(left) && is_time(right) => Some(DataType::Interval),
+        BinaryOperator::Plus | BinaryOperator::Minus if is_time(left) && is_interval(right) => {
+            Some(DataType::Timestamp)
+        }
+        BinaryOperator::Plus if is_interval(left) && is_time(right) => Some(DataType::Timestamp),
+        BinaryOperator::Plus | BinaryOperator::Minus
+            if is_interval(left)
+                && is_interval(right)
+                && (left == DataType::Interval || right == DataType::Interval) =>
+        {
+            Some(DataType::Interval)
+        }
+        BinaryOperator::Multiply
+            if (left == DataType::Interval && is_days(right))
+                || (is_days(left) && right == DataType::Interval) =>
+        {
+            Some(DataType::Interval)
+        }
+        _ => None,
+    }
+}
+
+// string literals can be compared with numbers, dates and timestamps when they are valid values of the type
+// and are then used as that type, such as '2011' compared with an int, other strings are only compared with strings
+fn comparison_types(
+    left: &Expr,
+    left_type: DataType,
+    right: &Expr,
+    right_type: DataType,
+) -> (DataType, DataType) {
+    (
+        literal_type(left, left_type, right_type),
+        literal_type(right, right_type, left_type),
+    )
+}
+
+// the type of a string literal used with a value of the other type
+fn literal_type(expr: &Expr, data_type: DataType, other: DataType) -> DataType {
+    match expr {
+        Expr::Nested(expr) => literal_type(expr, data_type, other),
+        Expr::Value(Value::SingleQuotedString(s))
+            if other != DataType::String
+                && coercion::coerce(&Field::Str(s.clone()), other).is_some() =>
+        {
+            other
+        }
+        _ => data_type,
+    }
+}
+
+fn is_string_literal(expr: &Expr) -> bool {
+    match expr {
+        Expr::Nested(expr) => is_string_literal(expr),
+        expr => matches!(expr, Expr::Value(Value::SingleQuotedString(_))),
+    }
+}
+
+// the type both values can be used as, null can be used as any type and unknown types are not checked
+pub fn common_type(left: DataType, right: DataType) -> Option<DataType> {
+    match (left, right) {
+        (DataType::Unknown, _) | (_, DataType::Unknown) => Some(DataType::Unknown),
+        (DataType::Null, other) | (other, DataType::Null) => Some(other),
+        (left, right) if left == right => Some(left),
+        (left, right) => {
+            DataType::wider_numeric(left, right).or_else(|| DataType::wider_temporal(left, right))
+        }
+    }
+}
+
+fn is_numeric_operand(data_type: DataType) -> bool {
+    data_type.is_numeric() || matches!(data_type, DataType::Null | DataType::Unknown)
+}
+
+fn is_boolean_operand(data_type: DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Boolean | DataType::Null | DataType::Unknown
+    )
+}
+
+fn is_string_operand(data_type: DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::String | DataType::Null | DataType::Unknown
+    )
+}
+
+fn contains_aggregate(expr: &Expr) -> bool {
+    match expr {
+        Expr::Function(function) if is_aggregate(function) => true,
+        expr => child_exprs(expr).into_iter().any(contains_aggregate),
+    }
+}
+
+// returns the first column of the expression that is neither grouped nor inside an aggregate, the columns of the outer
+// query and the internal columns of subqueries are the same for every row of a group
+pub fn ungrouped_column<'a>(
+    expr: &'a Expr,
+    group_by: &[Expr],
+    schema: &OutputSchema,
+) -> Option<&'a Expr> {
+    if group_by
+        .iter()
+        .any(|group| group == expr || same_column(group, expr, schema))
+    {
+        return None;
+    }
+
+    match expr {
+        Expr::Identifier(_) | Expr::CompoundIdentifier(_) => column_index(expr, schema)
+            .filter(|index| {
+                let column = &schema.columns[*index];
+                !column.hidden && !column.column_name.starts_with('#')
+            })
+            .map(|_| expr),
+        Expr::Function(function) if is_aggregate(function) => None,
+        expr => child_exprs(expr)
+            .into_iter()
+            .find_map(|child| ungrouped_column(child, group_by, schema)),
+    }
+}
+
+fn same_column(left: &Expr, right: &Expr, schema: &OutputSchema) -> bool {
+    match (column_index(left, schema), column_index(right, schema)) {
+        (Some(left), Some(right)) => left == right,
+        _ => false,
+    }
+}
+
+fn column_index(expr: &Expr, schema: &OutputSchema) -> Option<usize> {
+    match expr {
+        Expr::Identifier(ident) => schema.resolve(&ident.value).ok(),
+        Expr::CompoundIdentifier(idents) => schema
+            .resolve(
+                &idents
+                    .iter()
+                    .map(|i| i.value.clone())
+                    .collect::<Vec<String>>()
+                    .join("."),
+            )
+            .ok(),
+        _ => None,
+    }
+}
+
+// the expressions an expression is computed from, subqueries are planned on their own and are not part of them
+fn child_exprs(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::Function(function) => function
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) => Some(expr),
+                _ => None,
+            })
+            .collect(),
+        Expr::Nested(expr)
+        | Expr::UnaryOp { expr, .. }
+        | Expr::IsNull(expr)
+        | Expr::IsNotNull(expr)
+        | Expr::IsTrue(expr)
+        | Expr::IsNotTrue(expr)
+        | Expr::IsFalse(expr)
+        | Expr::IsNotFalse(expr)
+        | Expr::IsUnknown(expr)
+        | Expr::IsNotUnknown(expr)
+        | Expr::Cast { expr, .. }
+        | Expr::TryCast { expr, .. }
+        | Expr::SafeCast { expr, .. }
+        | Expr::Ceil { expr, .. }
+        | Expr::Floor { expr, .. }
+        | Expr::Extract { expr, .. } => vec![expr],
+        Expr::Trim {
+            expr, trim_what, ..
+        } => std::iter::once(expr.as_ref())
+            .chain(trim_what.iter().map(|what| what.as_ref()))
+            .collect(),
+        Expr::Substring {
+            expr,
+            substring_from,
+            substring_for,
+        } => std::iter::once(expr.as_ref())
+            .chain(substring_from.iter().map(|arg| arg.as_ref()))
+            .chain(substring_for.iter().map(|arg| arg.as_ref()))
+            .collect(),
+        Expr::BinaryOp { left, right, .. }
+        | Expr::IsDistinctFrom(left, right)
+        | Expr::IsNotDistinctFrom(left, right)
+        | Expr::Like {
+            expr: left,
+            pattern: right,
+            ..
+        }
+        | Expr::ILike {
+            expr: left,
+            pattern: right,
+            ..
+        }
+        | Expr::SimilarTo {
+            expr: left,
+            pattern: right,
+            ..
+        } => vec![left, right],
+        Expr::Case {
+            operand,
+            conditions,
+            results,
+            else_result,
+        } => operand
+            .iter()
+            .chain(else_result.iter())
+            .map(|expr| expr.as_ref())
+            .chain(conditions.iter())
+            .chain(results.iter())
+            .collect(),
+        Expr::InList { expr, list, .. } => {
+            std::iter::once(expr.as_ref()).chain(list.iter()).collect()
+        }
+        Expr::Between {
+            expr, low, high, ..
+        } => vec![expr, low, high],
+        _ => vec![],
+    }
+}
+
+fn function_args(function: &Function) -> Result<Vec<Expr>, Error> {
+    function
+        .args
+        .iter()
+        .map(|arg| match arg {
+            FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) => Ok(expr.clone()),
+            _ => Err(Error::Planner(format!(
+                "Unsupported argument {} for function {}",
+                arg, function.name
+            ))),
+        })
+        .collect()
+}
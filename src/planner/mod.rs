@@ -1,15 +1,27 @@
-use std::collections::HashSet;
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashSet,
+};
 
 use sqlparser::ast::{
-    Expr, Function, Ident, Query, Select, SelectItem, SetExpr, Statement, TableFactor,
-    TableWithJoins,
+    BinaryOperator, Cte, Distinct, Expr, Fetch, Function, FunctionArg, FunctionArgExpr, Ident,
+    ObjectName, Offset, OrderByExpr, Query, Select, SelectItem, SetExpr, SetOperator,
+    SetQuantifier, Statement, TableAlias, TableFactor, TableWithJoins, UnaryOperator, With,
 };
 
 use crate::{
+    executor::expression::ExprEvaluator,
+    optimizer::utils::{combine_conjunction, split_conjunction},
     storage::{get_table_path, parquet::ParquetReader},
-    types::{error::Error, parse_identifer, Column},
+    types::{data_type::DataType, error::Error, field::Field, parse_identifer, Column},
+};
+
+use self::binder::{
+    infer_aggregate_type, infer_type, join_output_schema, set_operation_output_schema,
 };
 
+pub(crate) mod binder;
+
 #[derive(Debug, Default, Clone)]
 pub struct OutputSchema {
     pub columns: Vec<Column>,
@@ -44,9 +56,32 @@ impl OutputSchema {
     pub fn resolve(&self, name: &str) -> Result<usize, Error> {
         let (field_name, table_name) = parse_identifer(name)?;
 
+        // an unqualified name only finds a hidden column when no other column has the name, a subquery finds the columns of the outer query this way
+        let mut result_index = self.find_column(name, &field_name, &table_name, false)?;
+        if result_index.is_none() && table_name.is_none() {
+            result_index = self.find_column(name, &field_name, &table_name, true)?;
+        }
+
+        match result_index {
+            Some(index) => Ok(index),
+            None => Err(Error::Planner(format!("Field not found: {}", name))),
+        }
+    }
+
+    fn find_column(
+        &self,
+        name: &str,
+        field_name: &str,
+        table_name: &Option<String>,
+        hidden: bool,
+    ) -> Result<Option<usize>, Error> {
         let mut result_index = None;
 
         for (i, column) in self.columns.iter().enumerate() {
+            if table_name.is_none() && column.hidden != hidden {
+                continue;
+            }
+
             if table_name.is_some()
                 && (column.table.is_none()
                     || table_name.as_ref().unwrap() != column.table.as_ref().unwrap())
@@ -65,30 +100,78 @@ impl OutputSchema {
             result_index = Some(i);
         }
 
-        if result_index.is_none() {
-            return Err(Error::Planner(format!("Field not found: {}", name)));
-        }
+        Ok(result_index)
+    }
+}
 
-        Ok(result_index.unwrap())
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JoinType {
+    Inner,
+    Left,
+    Right,
+    Full,
+    // the left rows that have a match, each of them once
+    Semi,
+    // the left rows that do not have a match
+    Anti,
+}
+
+impl JoinType {
+    // whether rows from the left side without a match are kept
+    pub fn preserves_left(&self) -> bool {
+        matches!(self, JoinType::Left | JoinType::Full | JoinType::Anti)
+    }
+
+    // whether rows from the right side without a match are kept
+    pub fn preserves_right(&self) -> bool {
+        matches!(self, JoinType::Right | JoinType::Full)
     }
+
+    // whether the output only has the columns of the left side
+    pub fn outputs_left_only(&self) -> bool {
+        matches!(self, JoinType::Semi | JoinType::Anti)
+    }
+}
+
+// what an expression does with the rows of a subquery
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubqueryKind {
+    // the value of the single row, null when there are no rows
+    Scalar,
+    // whether there are any rows
+    Exists,
+    // whether the value of the expression is one of the values of the subquery
+    In(Expr),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PlanNode {
     pub output_schema: OutputSchema,
     pub node: Node,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Node {
     Scan {
         table_name: String,
         filter: Option<Expr>,
+        // the indexes of the columns in the table that are read, all of them if there is none
+        projection: Option<Vec<usize>>,
     },
     NestedLoopJoin {
         child_left: Box<PlanNode>,
         child_right: Box<PlanNode>,
         predicate: Option<Expr>,
+        join_type: JoinType,
+    },
+    HashJoin {
+        child_left: Box<PlanNode>,
+        child_right: Box<PlanNode>,
+        left_keys: Vec<Expr>,
+        right_keys: Vec<Expr>,
+        predicate: Option<Expr>,
+        join_type: JoinType,
+        build_left: bool,
     },
     Filter {
         filter: Expr,
@@ -104,29 +187,261 @@ pub enum Node {
         non_aggregates: Vec<SelectItem>,
         group_by: Vec<Expr>,
     },
+    Sort {
+        order_by: Vec<OrderByExpr>,
+        child: Box<PlanNode>,
+    },
+    // UNION, INTERSECT or EXCEPT of two inputs with the same number of columns, duplicates are removed without ALL
+    SetOperation {
+        op: SetOperator,
+        all: bool,
+        left: Box<PlanNode>,
+        right: Box<PlanNode>,
+    },
+    // keeps the first row of every distinct value of the expressions, or of all the columns when there are none
+    Distinct {
+        on: Vec<Expr>,
+        child: Box<PlanNode>,
+    },
     Limit {
-        limit: u64,
+        limit: Option<u64>,
+        offset: u64,
+        child: Box<PlanNode>,
+    },
+    // a subquery in FROM, the rows are passed through and the columns are renamed and qualified with the alias
+    SubqueryAlias {
+        alias: String,
+        child: Box<PlanNode>,
+    },
+    // computes the rows of a common table expression once into a work table that the work table scans in the child read
+    MaterializedCte {
+        id: usize,
+        cte: Box<PlanNode>,
+        child: Box<PlanNode>,
+    },
+    // the recursive term is run on the rows it produced in the previous iteration, starting with the anchor, until there are no new rows
+    RecursiveCte {
+        id: usize,
+        all: bool,
+        anchor: Box<PlanNode>,
+        recursive: Box<PlanNode>,
+    },
+    // reads the rows of a materialized common table expression or the previous iteration of a recursive one
+    WorkTableScan {
+        id: usize,
+    },
+    // adds the result of a subquery of an expression as the last column of the rows of the child
+    // a correlated subquery is run again for every row which it reads from the work table, the others are run once
+    Apply {
+        kind: SubqueryKind,
+        correlated: Option<usize>,
         child: Box<PlanNode>,
+        subquery: Box<PlanNode>,
     },
     Empty {},
 }
 
+impl PlanNode {
+    // rebuilds the node with each of its children replaced by the result of the function
+    pub fn map_children<F>(self, mut f: F) -> Result<PlanNode, Error>
+    where
+        F: FnMut(PlanNode) -> Result<PlanNode, Error>,
+    {
+        let node = match self.node {
+            Node::NestedLoopJoin {
+                child_left,
+                child_right,
+                predicate,
+                join_type,
+            } => Node::NestedLoopJoin {
+                child_left: Box::new(f(*child_left)?),
+                child_right: Box::new(f(*child_right)?),
+                predicate,
+                join_type,
+            },
+            Node::HashJoin {
+                child_left,
+                child_right,
+                left_keys,
+                right_keys,
+                predicate,
+                join_type,
+                build_left,
+            } => Node::HashJoin {
+                child_left: Box::new(f(*child_left)?),
+                child_right: Box::new(f(*child_right)?),
+                left_keys,
+                right_keys,
+                predicate,
+                join_type,
+                build_left,
+            },
+            Node::Filter { filter, child } => Node::Filter {
+                filter,
+                child: Box::new(f(*child)?),
+            },
+            Node::Projection { select, child } => Node::Projection {
+                select,
+                child: Box::new(f(*child)?),
+            },
+            Node::Aggregate {
+                child,
+                aggregates,
+                non_aggregates,
+                group_by,
+            } => Node::Aggregate {
+                child: Box::new(f(*child)?),
+                aggregates,
+                non_aggregates,
+                group_by,
+            },
+            Node::Sort { order_by, child } => Node::Sort {
+                order_by,
+                child: Box::new(f(*child)?),
+            },
+            Node::Distinct { on, child } => Node::Distinct {
+                on,
+                child: Box::new(f(*child)?),
+            },
+            Node::SetOperation {
+                op,
+                all,
+                left,
+                right,
+            } => Node::SetOperation {
+                op,
+                all,
+                left: Box::new(f(*left)?),
+                right: Box::new(f(*right)?),
+            },
+            Node::Limit {
+                limit,
+                offset,
+                child,
+            } => Node::Limit {
+                limit,
+                offset,
+                child: Box::new(f(*child)?),
+            },
+            Node::SubqueryAlias { alias, child } => Node::SubqueryAlias {
+                alias,
+                child: Box::new(f(*child)?),
+            },
+            Node::MaterializedCte { id, cte, child } => Node::MaterializedCte {
+                id,
+                cte: Box::new(f(*cte)?),
+                child: Box::new(f(*child)?),
+            },
+            Node::RecursiveCte {
+                id,
+                all,
+                anchor,
+                recursive,
+            } => Node::RecursiveCte {
+                id,
+                all,
+                anchor: Box::new(f(*anchor)?),
+                recursive: Box::new(f(*recursive)?),
+            },
+            Node::Apply {
+                kind,
+                correlated,
+                child,
+                subquery,
+            } => Node::Apply {
+                kind,
+                correlated,
+                child: Box::new(f(*child)?),
+                subquery: Box::new(f(*subquery)?),
+            },
+            node @ (Node::Scan { .. } | Node::WorkTableScan { .. } | Node::Empty {}) => node,
+        };
+
+        Ok(PlanNode {
+            output_schema: self.output_schema,
+            node,
+        })
+    }
+}
+
 pub struct Plan {
     pub root: PlanNode,
 }
 
-pub struct Planner {}
+// how many times the recursive term of a query is planned to find the types of its columns
+const MAX_RECURSIVE_TYPE_PASSES: usize = 4;
+
+// a common table expression of a WITH clause that is in scope
+struct CteDefinition {
+    cte: Cte,
+    recursive: bool,
+    // set while the query of the common table expression is planned
+    planning: bool,
+    // the work table the recursive term reads while it is planned
+    work_table: Option<RecursiveReference>,
+    // the plan of the query when common table expressions are materialized, it is planned on the first reference
+    materialized: Option<MaterializedCte>,
+}
+
+struct RecursiveReference {
+    id: usize,
+    output_schema: OutputSchema,
+    references: usize,
+}
+
+struct MaterializedCte {
+    id: usize,
+    plan: PlanNode,
+}
+
+// a subquery taken out of an expression, the expression reads its result from the value column of the name
+struct ExtractedSubquery {
+    kind: SubqueryKind,
+    query: Query,
+    name: String,
+}
+
+// the row of the outer query that a correlated subquery reads from a work table
+struct OuterScope {
+    id: usize,
+    output_schema: OutputSchema,
+}
+
+pub struct Planner {
+    // the common table expressions that can be referenced, the innermost ones are last
+    ctes: RefCell<Vec<CteDefinition>>,
+    next_work_table: Cell<usize>,
+    next_subquery: Cell<usize>,
+    // set while a correlated subquery is planned until its FROM clause is built
+    outer_scope: RefCell<Option<OuterScope>>,
+    // whether common table expressions are computed once or planned again at every reference
+    materialize_ctes: bool,
+}
 
 impl Planner {
     pub fn new() -> Planner {
-        Planner {}
+        Planner {
+            ctes: RefCell::new(Vec::new()),
+            next_work_table: Cell::new(0),
+            next_subquery: Cell::new(0),
+            outer_scope: RefCell::new(None),
+            materialize_ctes: false,
+        }
+    }
+
+    pub fn set_materialize_ctes(&mut self, materialize: bool) {
+        self.materialize_ctes = materialize;
     }
 
-    pub fn build_statements(&self, statements: &Vec<Statement>) -> Result<Plan, Error> {
+    pub fn build_statements(
+        &self,
+        statements: &Vec<Statement>,
+        params: &[Field],
+    ) -> Result<Plan, Error> {
         let mut plans: Vec<Plan> = Vec::new();
 
         for statement in statements {
-            plans.push(self.build_statement(statement)?);
+            plans.push(self.build_statement(statement, params)?);
         }
 
         if plans.is_empty() {
@@ -137,75 +452,249 @@ impl Planner {
         Ok(plans.pop().unwrap())
     }
 
-    fn build_statement(&self, statement: &Statement) -> Result<Plan, Error> {
+    fn build_statement(&self, statement: &Statement, params: &[Field]) -> Result<Plan, Error> {
         match statement {
             Statement::Query(query) => {
-                let Query {
-                    ref body,
-                    ref limit,
-                    ..
-                } = **query;
-
-                match &**body {
-                    SetExpr::Select(select) => {
-                        let Select {
-                            from,
-                            projection,
-                            selection,
-                            group_by,
-                            having,
-                            ..
-                        } = &**select;
-
-                        // Build FROM
-                        let node = self.build_from_clause(from)?;
-
-                        // Build WHERE
-                        let node = self.build_where_clause(selection, node)?;
-
-                        // Build PROJECTION
-                        let mut select_items = projection.clone();
-                        let mut having_items = having.clone();
-
-                        // We extract the aggregates and the select items and the having clause
-                        let (all_aggregates, non_aggregate_projections) =
-                            self.extract_aggregates(&mut select_items, &mut having_items)?;
-
-                        let node = if !all_aggregates.is_empty() || !(*group_by).is_empty() {
-                            self.build_aggregate_statement(
-                                node,
-                                &select_items.clone(),
-                                &non_aggregate_projections,
-                                &all_aggregates,
-                                group_by,
-                                &having_items,
-                            )?
-                        } else {
-                            if having.is_some() {
-                                return Err(Error::Planner(
-                                    "HAVING clause without aggregates not supported".to_string(),
-                                ));
-                            }
-
-                            self.build_non_aggregate_statement(node, &select_items)?
-                        };
+                let node = self.build_query(query, params)?;
+
+                // every expression is checked once the whole plan is built
+                binder::bind(&node)?;
+
+                Ok(Plan { root: node })
+            }
+            _ => Err(Error::Planner(
+                "Only Query operations are supported".to_string(),
+            )),
+        }
+    }
+
+    fn build_query(&self, query: &Query, params: &[Field]) -> Result<PlanNode, Error> {
+        let scope = self.ctes.borrow().len();
+
+        let node = self
+            .build_with_clause(&query.with)
+            .and_then(|_| self.build_query_body(query, params));
+
+        // the common table expressions go out of scope even when planning fails
+        let ctes = self.ctes.borrow_mut().split_off(scope);
+        let mut node = node?;
+
+        // a materialized common table expression is computed before the ones planned after it since they can read it
+        let mut materialized = ctes
+            .into_iter()
+            .filter_map(|definition| definition.materialized)
+            .collect::<Vec<MaterializedCte>>();
+        materialized.sort_by_key(|materialized| materialized.id);
+
+        for materialized in materialized.into_iter().rev() {
+            node = PlanNode {
+                output_schema: node.output_schema.clone(),
+                node: Node::MaterializedCte {
+                    id: materialized.id,
+                    cte: Box::new(materialized.plan),
+                    child: Box::new(node),
+                },
+            };
+        }
+
+        Ok(node)
+    }
+
+    fn build_with_clause(&self, with: &Option<With>) -> Result<(), Error> {
+        let Some(with) = with else {
+            return Ok(());
+        };
+
+        let mut names = HashSet::new();
+        for cte in &with.cte_tables {
+            if !names.insert(cte.alias.name.value.clone()) {
+                return Err(Error::Planner(format!(
+                    "WITH query name {} specified more than once",
+                    cte.alias.name.value
+                )));
+            }
+
+            self.ctes.borrow_mut().push(CteDefinition {
+                cte: cte.clone(),
+                recursive: with.recursive,
+                planning: false,
+                work_table: None,
+                materialized: None,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn build_query_body(&self, query: &Query, params: &[Field]) -> Result<PlanNode, Error> {
+        let Query {
+            body,
+            order_by,
+            limit,
+            offset,
+            fetch,
+            ..
+        } = query;
+
+        // a select sorts before its projection, the result of a set operation is sorted by its output columns
+        let node = match &**body {
+            SetExpr::Select(select) => self.build_select(select, order_by, params)?,
+            body => {
+                let node = self.build_set_expr(body, params)?;
+                let order_by = self.resolve_set_order_by(order_by, &node)?;
+                self.build_order_by_clause(node, &order_by)?
+            }
+        };
+
+        // Build OFFSET and LIMIT
+        self.build_limit_clause(node, limit, offset, fetch, params)
+    }
+
+    fn build_set_expr(&self, set_expr: &SetExpr, params: &[Field]) -> Result<PlanNode, Error> {
+        match set_expr {
+            SetExpr::Select(select) => self.build_select(select, &[], params),
+            SetExpr::Query(query) => self.build_query(query, params),
+            SetExpr::SetOperation {
+                op,
+                set_quantifier,
+                left,
+                right,
+            } => {
+                let left = self.build_set_expr(left, params)?;
+                let right = self.build_set_expr(right, params)?;
+
+                Ok(PlanNode {
+                    output_schema: set_operation_output_schema(
+                        &left.output_schema,
+                        &right.output_schema,
+                        op,
+                    )?,
+                    node: Node::SetOperation {
+                        op: *op,
+                        all: *set_quantifier == SetQuantifier::All,
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    },
+                })
+            }
+            _ => Err(Error::Planner("Only SELECT is supported".to_string())),
+        }
+    }
+
+    fn build_select(
+        &self,
+        select: &Select,
+        order_by: &[OrderByExpr],
+        params: &[Field],
+    ) -> Result<PlanNode, Error> {
+        let Select {
+            distinct,
+            from,
+            projection,
+            selection,
+            group_by,
+            having,
+            ..
+        } = select;
+
+        // the outer row of a correlated subquery is only in scope of its own FROM clause and not of the queries inside it
+        let outer_scope = self.outer_scope.borrow_mut().take();
+
+        // Build FROM
+        let node = self.build_from_clause(from, params)?;
+        let node = match outer_scope {
+            Some(outer_scope) => self.build_outer_scope_join(outer_scope, node),
+            None => node,
+        };
 
-                        // Build ORDER BY
+        // Build WHERE
+        let node = match selection {
+            Some(selection) => self.build_filter(selection.clone(), node, params)?,
+            None => node,
+        };
 
-                        // Build OFFSET
+        // ORDER BY can reference select items by alias or position so we resolve those first
+        let mut order_by_items = self.resolve_order_by(order_by, projection, &node)?;
+        if let Some(Distinct::Distinct) = distinct {
+            self.check_distinct_order_by(&order_by_items, projection, &node)?;
+        }
+        let mut distinct_on = self.resolve_distinct_on(distinct, projection, &node)?;
 
-                        // Build LIMIT
-                        let node = self.build_limit_clause(node, limit.clone())?;
+        // Build PROJECTION
+        let mut select_items = projection.clone();
+        let mut having_items = having.clone();
 
-                        Ok(Plan { root: node })
+        // the subqueries of the select items are run for the rows of the FROM clause, an unnamed item keeps the text of its expression
+        let mut subqueries = Vec::new();
+        for item in select_items.iter_mut() {
+            match item {
+                SelectItem::UnnamedExpr(expr) => {
+                    let original = expr.clone();
+                    self.extract_subqueries_from_expr(expr, &mut subqueries);
+                    if *expr != original {
+                        *item = SelectItem::ExprWithAlias {
+                            expr: expr.clone(),
+                            alias: Ident::new(original.to_string()),
+                        };
                     }
-                    _ => Err(Error::Planner("Only SELECT is supported".to_string())),
                 }
+                SelectItem::ExprWithAlias { expr, .. } => {
+                    self.extract_subqueries_from_expr(expr, &mut subqueries)
+                }
+                _ => {}
             }
-            _ => Err(Error::Planner(
-                "Only Query operations are supported".to_string(),
-            )),
         }
+        for item in order_by_items.iter_mut() {
+            self.extract_subqueries_from_expr(&mut item.expr, &mut subqueries);
+        }
+        for expr in distinct_on.iter_mut() {
+            self.extract_subqueries_from_expr(expr, &mut subqueries);
+        }
+        let node = self.build_subqueries(node, subqueries, params)?;
+
+        // We extract the aggregates and the select items, the having clause and the order by clause
+        let (all_aggregates, non_aggregate_projections) = self.extract_aggregates(
+            &mut select_items,
+            &mut having_items,
+            &mut order_by_items,
+            &mut distinct_on,
+        )?;
+
+        let node = if !all_aggregates.is_empty() || !(*group_by).is_empty() {
+            self.build_aggregate_statement(
+                node,
+                &select_items.clone(),
+                &non_aggregate_projections,
+                &all_aggregates,
+                group_by,
+                &having_items,
+                &order_by_items,
+                &distinct_on,
+                params,
+            )?
+        } else {
+            if having.is_some() {
+                return Err(Error::Planner(
+                    "HAVING clause without aggregates not supported".to_string(),
+                ));
+            }
+
+            self.build_non_aggregate_statement(node, &select_items, &order_by_items, &distinct_on)?
+        };
+
+        // the rows of SELECT DISTINCT are compared after the projection
+        let node = match distinct {
+            Some(Distinct::Distinct) => PlanNode {
+                output_schema: node.output_schema.clone(),
+                node: Node::Distinct {
+                    on: vec![],
+                    child: Box::new(node),
+                },
+            },
+            _ => node,
+        };
+
+        Ok(node)
     }
 
     // this function returns the aggregate functions and the select items without aggregates if aggregates are found or else None
@@ -214,6 +703,8 @@ impl Planner {
         &self,
         select_items: &mut [SelectItem],
         having: &mut Option<Expr>,
+        order_by: &mut [OrderByExpr],
+        distinct_on: &mut [Expr],
     ) -> Result<(Vec<Function>, Vec<SelectItem>), Error> {
         // we need to extract the aggregate functions and handle those separately and extract the identifiers in the select items with aggregate functions
         // this allows to to get all the values we need to perform the aggregate functions and projections
@@ -278,6 +769,23 @@ impl Planner {
             }
         }
 
+        for item in order_by.iter_mut() {
+            let mut aggregates =
+                Self::extract_aggregates_from_expr(&mut item.expr, &mut total_aggregates)?;
+            all_aggregates.append(&mut aggregates);
+            non_aggregate_projections.append(&mut Self::extract_identifiers_as_select_items(
+                &item.expr, &mut seen,
+            ));
+        }
+
+        for expr in distinct_on.iter_mut() {
+            let mut aggregates = Self::extract_aggregates_from_expr(expr, &mut total_aggregates)?;
+            all_aggregates.append(&mut aggregates);
+            non_aggregate_projections.append(&mut Self::extract_identifiers_as_select_items(
+                expr, &mut seen,
+            ));
+        }
+
         Ok((all_aggregates, non_aggregate_projections))
     }
 
@@ -285,12 +793,18 @@ impl Planner {
     fn build_non_aggregate_statement(
         &self,
         child: PlanNode,
-        end_projection: &Vec<SelectItem>,
+        end_projection: &[SelectItem],
+        order_by: &[OrderByExpr],
+        distinct_on: &[Expr],
     ) -> Result<PlanNode, Error> {
+        // we sort before the projection so we can sort by columns that are not selected
+        let child = self.build_order_by_clause(child, order_by)?;
+        let child = self.build_distinct_on_clause(child, distinct_on);
+
         let node = PlanNode {
             output_schema: self.get_output_schema_from_projection(end_projection, &child)?,
             node: Node::Projection {
-                select: end_projection.clone(),
+                select: end_projection.to_vec(),
                 child: Box::new(child),
             },
         };
@@ -298,26 +812,55 @@ impl Planner {
     }
 
     // resolves the aggregates, group by and having
+    #[allow(clippy::too_many_arguments)]
     fn build_aggregate_statement(
         &self,
         child: PlanNode,
-        end_projection: &Vec<SelectItem>,
-        non_aggregate_projections: &Vec<SelectItem>,
-        aggregates: &Vec<Function>,
+        end_projection: &[SelectItem],
+        non_aggregate_projections: &[SelectItem],
+        aggregates: &[Function],
         group_by: &[Expr],
         having: &Option<Expr>,
+        order_by: &[OrderByExpr],
+        distinct_on: &[Expr],
+        params: &[Field],
     ) -> Result<PlanNode, Error> {
         assert!(!aggregates.is_empty() || !group_by.is_empty());
 
+        // every column outside the aggregates has to be grouped to have a single value per group
+        let exprs = end_projection
+            .iter()
+            .filter_map(|item| match item {
+                SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => {
+                    Some(expr)
+                }
+                _ => None,
+            })
+            .chain(having.iter())
+            .chain(order_by.iter().map(|item| &item.expr))
+            .chain(distinct_on.iter());
+        for expr in exprs {
+            if let Some(column) = binder::ungrouped_column(expr, group_by, &child.output_schema) {
+                return Err(Error::Planner(format!(
+                    "Column {} must appear in the GROUP BY clause or be used in an aggregate function",
+                    column
+                )));
+            }
+        }
+
         // aggregates functions (#agg0, #agg1, etc.) followed by group by followed by non-aggregates we need
         let mut first_projection_with_aggregates_output_schema = OutputSchema::new();
 
         // add aggregates to the output schema
         for (i, item) in aggregates.iter().enumerate() {
+            let (data_type, nullable) = infer_aggregate_type(item, &child.output_schema);
             first_projection_with_aggregates_output_schema.add_column(Column {
                 label: Some(item.to_string()),
                 table: None,
                 column_name: format!("#agg{}", i),
+                data_type,
+                nullable,
+                hidden: false,
             })?;
         }
 
@@ -330,28 +873,26 @@ impl Planner {
             output_schema: first_projection_with_aggregates_output_schema,
             node: Node::Aggregate {
                 child: Box::new(child),
-                aggregates: aggregates.clone(),
+                aggregates: aggregates.to_vec(),
                 group_by: group_by.to_vec(),
-                non_aggregates: non_aggregate_projections.clone(),
+                non_aggregates: non_aggregate_projections.to_vec(),
             },
         };
 
         // plan a filter based on having clause
-        if having.is_some() {
-            node = PlanNode {
-                output_schema: node.output_schema.clone(),
-                node: Node::Filter {
-                    filter: having.as_ref().unwrap().clone(),
-                    child: Box::new(node),
-                },
-            };
+        if let Some(having) = having {
+            node = self.build_filter(having.clone(), node, params)?;
         }
 
+        // plan a sort before the final projection so we can sort by aggregates that are not selected
+        node = self.build_order_by_clause(node, order_by)?;
+        node = self.build_distinct_on_clause(node, distinct_on);
+
         // plan a projection to get to the original projection
         node = PlanNode {
             output_schema: self.get_output_schema_from_projection(end_projection, &node)?,
             node: Node::Projection {
-                select: end_projection.clone(),
+                select: end_projection.to_vec(),
                 child: Box::new(node),
             },
         };
@@ -361,7 +902,7 @@ impl Planner {
 
     fn get_output_schema_from_projection(
         &self,
-        projection: &Vec<SelectItem>,
+        projection: &[SelectItem],
         child: &PlanNode,
     ) -> Result<OutputSchema, Error> {
         let mut output_schema = OutputSchema::new();
@@ -369,51 +910,69 @@ impl Planner {
         for item in projection {
             match item {
                 SelectItem::UnnamedExpr(expr) => {
+                    let (data_type, nullable) = infer_type(expr, &child.output_schema);
                     match expr {
                         // we only add the name if it is an identifier or compound identifier
                         Expr::Identifier(_) => {
                             output_schema.add_column(Column::new(
                                 Some(expr.to_string()),
                                 expr.to_string(),
+                                data_type,
+                                nullable,
                             )?)?;
                         }
                         Expr::CompoundIdentifier(_) => {
                             output_schema.add_column(Column::new(
                                 Some(expr.to_string()),
                                 expr.to_string(),
+                                data_type,
+                                nullable,
                             )?)?;
                         }
                         _ => {
-                            output_schema
-                                .add_column(Column::new(Some(expr.to_string()), "".to_string())?)?;
+                            output_schema.add_column(Column::new(
+                                Some(expr.to_string()),
+                                "".to_string(),
+                                data_type,
+                                nullable,
+                            )?)?;
                         }
                     }
                 }
                 SelectItem::ExprWithAlias { expr, alias } => {
+                    let (data_type, nullable) = infer_type(expr, &child.output_schema);
                     match expr {
                         // we only add the name if it is an identifier or compound identifier
                         Expr::Identifier(_) => {
                             output_schema.add_column(Column::new(
                                 Some(alias.value.clone()),
                                 expr.to_string(),
+                                data_type,
+                                nullable,
                             )?)?;
                         }
                         Expr::CompoundIdentifier(_) => {
                             output_schema.add_column(Column::new(
                                 Some(alias.value.clone()),
                                 expr.to_string(),
+                                data_type,
+                                nullable,
                             )?)?;
                         }
                         _ => {
                             output_schema.add_column(Column::new(
                                 Some(alias.value.clone()),
                                 "".to_string(),
+                                data_type,
+                                nullable,
                             )?)?;
                         }
                     }
                 }
                 SelectItem::Wildcard(_) => {
-                    output_schema.append(&child.output_schema.clone())?;
+                    for column in child.output_schema.columns.iter().filter(|c| !c.hidden) {
+                        output_schema.add_column(column.clone())?;
+                    }
                 }
                 _ => return Err(Error::Planner(format!("{} not supported", item))),
             }
@@ -422,7 +981,6 @@ impl Planner {
         Ok(output_schema)
     }
 
-    #[allow(dead_code)]
     fn replace_wildcards(
         &self,
         projection: Vec<SelectItem>,
@@ -434,7 +992,7 @@ impl Planner {
                 SelectItem::UnnamedExpr(_) => res.push(item.clone()),
                 SelectItem::ExprWithAlias { .. } => res.push(item.clone()),
                 SelectItem::Wildcard(_) => {
-                    for column in &child.output_schema.columns {
+                    for column in child.output_schema.columns.iter().filter(|c| !c.hidden) {
                         res.push(column.as_select_item());
                     }
                 }
@@ -445,7 +1003,11 @@ impl Planner {
         Ok(res)
     }
 
-    fn build_from_clause(&self, from: &Vec<TableWithJoins>) -> Result<PlanNode, Error> {
+    fn build_from_clause(
+        &self,
+        from: &[TableWithJoins],
+        params: &[Field],
+    ) -> Result<PlanNode, Error> {
         if from.is_empty() {
             return Ok(PlanNode {
                 output_schema: OutputSchema::new(),
@@ -453,10 +1015,10 @@ impl Planner {
             });
         }
 
-        let mut node = self.build_table_with_joins(&from[0])?;
+        let mut node = self.build_table_with_joins(&from[0], params)?;
 
         for table in &from[1..] {
-            let right = self.build_table_with_joins(table)?;
+            let right = self.build_table_with_joins(table, params)?;
 
             let mut output_schema = node.output_schema.clone();
             output_schema.append(&right.output_schema)?;
@@ -467,6 +1029,7 @@ impl Planner {
                     child_left: Box::new(node),
                     child_right: Box::new(right),
                     predicate: None,
+                    join_type: JoinType::Inner,
                 },
             };
         }
@@ -474,40 +1037,239 @@ impl Planner {
         Ok(node)
     }
 
-    fn build_table_with_joins(&self, table: &TableWithJoins) -> Result<PlanNode, Error> {
-        let mut node = self.build_table_factor(&table.relation)?;
+    fn build_table_with_joins(
+        &self,
+        table: &TableWithJoins,
+        params: &[Field],
+    ) -> Result<PlanNode, Error> {
+        let mut node = self.build_table_factor(&table.relation, params)?;
 
         for join in &table.joins {
-            let right = self.build_table_factor(&join.relation)?;
-
-            let mut output_schema = node.output_schema.clone();
-            output_schema.append(&right.output_schema)?;
+            let right = self.build_table_factor(&join.relation, params)?;
 
-            match &join.join_operator {
+            let (join_type, join_constraint) = match &join.join_operator {
                 sqlparser::ast::JoinOperator::Inner(join_constraint) => {
-                    node = PlanNode {
-                        output_schema,
-                        node: Node::NestedLoopJoin {
-                            child_left: Box::new(node),
-                            child_right: Box::new(right),
-                            predicate: match &join_constraint {
-                                sqlparser::ast::JoinConstraint::On(ref expr) => Some(expr.clone()),
-                                sqlparser::ast::JoinConstraint::None => None,
-                                _ => return Err(Error::Planner("Only ON supported".to_string())),
-                            },
-                        },
-                    };
+                    (JoinType::Inner, join_constraint)
                 }
-                _ => return Err(Error::Planner("Only INNER JOIN supported".to_string())),
-            }
+                sqlparser::ast::JoinOperator::LeftOuter(join_constraint) => {
+                    (JoinType::Left, join_constraint)
+                }
+                sqlparser::ast::JoinOperator::RightOuter(join_constraint) => {
+                    (JoinType::Right, join_constraint)
+                }
+                sqlparser::ast::JoinOperator::FullOuter(join_constraint) => {
+                    (JoinType::Full, join_constraint)
+                }
+                _ => {
+                    return Err(Error::Planner(
+                        "Only INNER, LEFT, RIGHT and FULL JOIN supported".to_string(),
+                    ))
+                }
+            };
+
+            let output_schema =
+                join_output_schema(&node.output_schema, &right.output_schema, join_type);
+
+            node = match &join_constraint {
+                sqlparser::ast::JoinConstraint::On(ref expr) => PlanNode {
+                    output_schema,
+                    node: Node::NestedLoopJoin {
+                        child_left: Box::new(node),
+                        child_right: Box::new(right),
+                        predicate: Some(expr.clone()),
+                        join_type,
+                    },
+                },
+                sqlparser::ast::JoinConstraint::None => PlanNode {
+                    output_schema,
+                    node: Node::NestedLoopJoin {
+                        child_left: Box::new(node),
+                        child_right: Box::new(right),
+                        predicate: None,
+                        join_type,
+                    },
+                },
+                sqlparser::ast::JoinConstraint::Using(idents) => {
+                    let using_columns = idents
+                        .iter()
+                        .map(|ident| ident.value.clone())
+                        .collect::<Vec<String>>();
+                    self.build_using_join(node, right, join_type, &using_columns)?
+                }
+                sqlparser::ast::JoinConstraint::Natural => {
+                    // a natural join is a join using all the columns with the same name in both sides
+                    let using_columns = node
+                        .output_schema
+                        .columns
+                        .iter()
+                        .filter(|c| !c.hidden)
+                        .map(|c| c.column_name.clone())
+                        .filter(|name| {
+                            right
+                                .output_schema
+                                .columns
+                                .iter()
+                                .any(|c| !c.hidden && c.column_name == *name)
+                        })
+                        .collect::<Vec<String>>();
+                    self.build_using_join(node, right, join_type, &using_columns)?
+                }
+            };
         }
 
         Ok(node)
     }
 
-    fn build_table_factor(&self, table: &TableFactor) -> Result<PlanNode, Error> {
+    // plans a join with equality conditions on the using columns followed by a projection that merges each pair of using columns into one
+    fn build_using_join(
+        &self,
+        left: PlanNode,
+        right: PlanNode,
+        join_type: JoinType,
+        using_columns: &[String],
+    ) -> Result<PlanNode, Error> {
+        let mut join_output_schema =
+            join_output_schema(&left.output_schema, &right.output_schema, join_type);
+
+        // merged columns get an internal table name so later joins can reference them unambiguously
+        let mut qualifier_number = 0;
+        while join_output_schema
+            .columns
+            .iter()
+            .any(|c| c.table == Some(format!("#using{}", qualifier_number)))
+        {
+            qualifier_number += 1;
+        }
+        let qualifier = format!("#using{}", qualifier_number);
+
+        let mut conditions = Vec::new();
+        let mut select = Vec::new();
+        let mut output_schema = OutputSchema::new();
+
+        for name in using_columns {
+            let left_index = Self::resolve_using_column(&left.output_schema, name, "left")?;
+            let right_index = Self::resolve_using_column(&right.output_schema, name, "right")?;
+
+            let left_expr = left.output_schema.columns[left_index].as_expr();
+            let right_expr = right.output_schema.columns[right_index].as_expr();
+
+            conditions.push(Expr::BinaryOp {
+                left: Box::new(left_expr.clone()),
+                op: BinaryOperator::Eq,
+                right: Box::new(right_expr.clone()),
+            });
+
+            // the merged column takes the value from the side that is preserved by the join
+            let merged_expr = match join_type {
+                JoinType::Inner | JoinType::Left | JoinType::Semi | JoinType::Anti => left_expr,
+                JoinType::Right => right_expr,
+                JoinType::Full => Expr::Function(Function {
+                    name: ObjectName(vec![Ident::new("coalesce")]),
+                    args: vec![
+                        FunctionArg::Unnamed(FunctionArgExpr::Expr(left_expr)),
+                        FunctionArg::Unnamed(FunctionArgExpr::Expr(right_expr)),
+                    ],
+                    over: None,
+                    distinct: false,
+                    special: false,
+                    order_by: vec![],
+                }),
+            };
+
+            // the merged column is only null when the columns it is taken from are null
+            let left_column = &left.output_schema.columns[left_index];
+            let right_column = &right.output_schema.columns[right_index];
+            let (data_type, nullable) = match join_type {
+                JoinType::Inner | JoinType::Left | JoinType::Semi | JoinType::Anti => {
+                    (left_column.data_type, left_column.nullable)
+                }
+                JoinType::Right => (right_column.data_type, right_column.nullable),
+                JoinType::Full => (
+                    left_column.data_type,
+                    left_column.nullable && right_column.nullable,
+                ),
+            };
+
+            select.push(SelectItem::UnnamedExpr(merged_expr));
+            output_schema.add_column(Column {
+                label: None,
+                table: Some(qualifier.clone()),
+                column_name: name.clone(),
+                data_type,
+                nullable,
+                hidden: false,
+            })?;
+
+            join_output_schema.columns[left_index].hidden = true;
+            join_output_schema.columns[left.output_schema.columns.len() + right_index].hidden =
+                true;
+        }
+
+        // the columns of both sides are still available after the merged columns
+        for column in &join_output_schema.columns {
+            select.push(SelectItem::UnnamedExpr(column.as_expr()));
+            output_schema.add_column(column.clone())?;
+        }
+
+        let predicate = conditions.into_iter().reduce(|left, right| Expr::BinaryOp {
+            left: Box::new(left),
+            op: BinaryOperator::And,
+            right: Box::new(right),
+        });
+
+        let node = PlanNode {
+            output_schema: join_output_schema,
+            node: Node::NestedLoopJoin {
+                child_left: Box::new(left),
+                child_right: Box::new(right),
+                predicate,
+                join_type,
+            },
+        };
+
+        Ok(PlanNode {
+            output_schema,
+            node: Node::Projection {
+                select,
+                child: Box::new(node),
+            },
+        })
+    }
+
+    // finds the column with the name in one side of a join, qualified names are not considered
+    fn resolve_using_column(
+        output_schema: &OutputSchema,
+        name: &str,
+        side: &str,
+    ) -> Result<usize, Error> {
+        let indexes = output_schema
+            .columns
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| !c.hidden && c.column_name == name)
+            .map(|(i, _)| i)
+            .collect::<Vec<usize>>();
+
+        match indexes[..] {
+            [index] => Ok(index),
+            [] => Err(Error::Planner(format!(
+                "Column {} in USING clause not found in {} table",
+                name, side
+            ))),
+            _ => Err(Error::Planner(format!(
+                "Column {} in USING clause is ambiguous in {} table",
+                name, side
+            ))),
+        }
+    }
+
+    fn build_table_factor(&self, table: &TableFactor, params: &[Field]) -> Result<PlanNode, Error> {
         match table {
             sqlparser::ast::TableFactor::Table { name, alias, .. } => {
+                if let Some(index) = self.find_cte(name) {
+                    return self.build_cte_reference(index, alias, params);
+                }
+
                 let table_name = name.to_string();
                 let table_path = get_table_path(&table_name);
 
@@ -527,29 +1289,608 @@ impl Planner {
                     node: Node::Scan {
                         table_name,
                         filter: None,
+                        projection: None,
                     },
                 })
             }
+            sqlparser::ast::TableFactor::Derived {
+                lateral,
+                subquery,
+                alias,
+            } => {
+                if *lateral {
+                    return Err(Error::Planner("LATERAL not supported".to_string()));
+                }
+
+                let Some(alias) = alias else {
+                    return Err(Error::Planner(
+                        "Subquery in FROM must have an alias".to_string(),
+                    ));
+                };
+
+                let node = self.build_query(subquery, params)?;
+                self.build_subquery_alias(node, alias)
+            }
             _ => Err(Error::Planner("JOIN not supported".to_string())),
         }
     }
 
-    fn build_where_clause(
+    // returns the index of the innermost common table expression with the name
+    fn find_cte(&self, name: &ObjectName) -> Option<usize> {
+        // quoted names are file paths
+        let [ident] = &name.0[..] else {
+            return None;
+        };
+        if ident.quote_style == Some('\'') {
+            return None;
+        }
+
+        // a common table expression is not in scope in its own query unless it is recursive
+        self.ctes.borrow().iter().rposition(|definition| {
+            definition.cte.alias.name.value == ident.value
+                && (!definition.planning || definition.recursive)
+        })
+    }
+
+    fn build_cte_reference(
         &self,
-        selection: &Option<Expr>,
-        child: PlanNode,
+        index: usize,
+        alias: &Option<TableAlias>,
+        params: &[Field],
     ) -> Result<PlanNode, Error> {
-        if selection.is_some() {
-            let filter = selection.as_ref().unwrap();
-            Ok(PlanNode {
-                output_schema: child.output_schema.clone(),
-                node: Node::Filter {
-                    filter: filter.clone(),
-                    child: Box::new(child),
+        let (cte_alias, planning) = {
+            let ctes = self.ctes.borrow();
+            (ctes[index].cte.alias.clone(), ctes[index].planning)
+        };
+
+        let node = if planning {
+            // a reference in the recursive term reads the rows of the previous iteration
+            let mut ctes = self.ctes.borrow_mut();
+            let Some(work_table) = ctes[index].work_table.as_mut() else {
+                return Err(Error::Planner(format!(
+                    "Recursive reference to query {} must not appear within its non-recursive term",
+                    cte_alias.name.value
+                )));
+            };
+            work_table.references += 1;
+
+            PlanNode {
+                output_schema: work_table.output_schema.clone(),
+                node: Node::WorkTableScan { id: work_table.id },
+            }
+        } else if self.materialize_ctes {
+            let materialized = self.ctes.borrow()[index]
+                .materialized
+                .as_ref()
+                .map(|materialized| (materialized.id, materialized.plan.output_schema.clone()));
+
+            match materialized {
+                Some((id, output_schema)) => PlanNode {
+                    output_schema,
+                    node: Node::WorkTableScan { id },
                 },
-            })
+                None => {
+                    let plan = self.build_cte_query(index, params)?;
+
+                    // a recursive query already keeps its rows in a work table and is read lazily so a limit can stop it
+                    if let Node::RecursiveCte { .. } = plan.node {
+                        plan
+                    } else {
+                        let id = self.next_work_table_id();
+                        let output_schema = plan.output_schema.clone();
+                        self.ctes.borrow_mut()[index].materialized =
+                            Some(MaterializedCte { id, plan });
+
+                        PlanNode {
+                            output_schema,
+                            node: Node::WorkTableScan { id },
+                        }
+                    }
+                }
+            }
+        } else {
+            self.build_cte_query(index, params)?
+        };
+
+        // the columns named by the reference replace the names given by the common table expression
+        let mut columns = cte_alias.columns.clone();
+        if let Some(alias) = alias {
+            for (i, column) in alias.columns.iter().enumerate() {
+                match columns.get_mut(i) {
+                    Some(name) => *name = column.clone(),
+                    None => columns.push(column.clone()),
+                }
+            }
+        }
+
+        let alias = TableAlias {
+            name: alias
+                .as_ref()
+                .map_or(cte_alias.name.clone(), |alias| alias.name.clone()),
+            columns,
+        };
+        self.build_subquery_alias(node, &alias)
+    }
+
+    // plans the query of a common table expression where only the common table expressions defined before it are in scope
+    fn build_cte_query(&self, index: usize, params: &[Field]) -> Result<PlanNode, Error> {
+        let hidden = self.ctes.borrow_mut().split_off(index + 1);
+        self.ctes.borrow_mut()[index].planning = true;
+
+        let node = self.build_cte_body(index, params);
+
+        let mut ctes = self.ctes.borrow_mut();
+        ctes[index].planning = false;
+        ctes[index].work_table = None;
+        ctes.extend(hidden);
+
+        node
+    }
+
+    fn build_cte_body(&self, index: usize, params: &[Field]) -> Result<PlanNode, Error> {
+        let (query, recursive) = {
+            let ctes = self.ctes.borrow();
+            (ctes[index].cte.query.clone(), ctes[index].recursive)
+        };
+
+        // a recursive query is a union of an anchor and a recursive term
+        let recursive_union = match &*query.body {
+            SetExpr::SetOperation {
+                op: SetOperator::Union,
+                set_quantifier,
+                left,
+                right,
+            } if recursive
+                && query.with.is_none()
+                && query.order_by.is_empty()
+                && query.limit.is_none()
+                && query.offset.is_none()
+                && query.fetch.is_none() =>
+            {
+                Some((*set_quantifier == SetQuantifier::All, left, right))
+            }
+            _ => None,
+        };
+
+        let Some((all, left, right)) = recursive_union else {
+            return self.build_query(&query, params);
+        };
+
+        let anchor = self.build_set_expr(left, params)?;
+
+        // the recursive term can add nulls to any column
+        let mut work_table_schema = anchor.output_schema.clone();
+        for column in &mut work_table_schema.columns {
+            column.nullable = true;
+        }
+
+        let id = self.next_work_table_id();
+
+        // the recursive term is planned again with wider types while it returns wider types than the rows it reads
+        for _ in 0..MAX_RECURSIVE_TYPE_PASSES {
+            self.ctes.borrow_mut()[index].work_table = Some(RecursiveReference {
+                id,
+                output_schema: work_table_schema.clone(),
+                references: 0,
+            });
+
+            let recursive = self.build_set_expr(right, params)?;
+
+            let output_schema = set_operation_output_schema(
+                &work_table_schema,
+                &recursive.output_schema,
+                &SetOperator::Union,
+            )?;
+
+            let references = self.ctes.borrow()[index]
+                .work_table
+                .as_ref()
+                .map_or(0, |work_table| work_table.references);
+
+            // without a reference to itself it is a plain union
+            if references == 0 {
+                return Ok(PlanNode {
+                    output_schema: set_operation_output_schema(
+                        &anchor.output_schema,
+                        &recursive.output_schema,
+                        &SetOperator::Union,
+                    )?,
+                    node: Node::SetOperation {
+                        op: SetOperator::Union,
+                        all,
+                        left: Box::new(anchor),
+                        right: Box::new(recursive),
+                    },
+                });
+            }
+
+            let widened = output_schema
+                .columns
+                .iter()
+                .zip(work_table_schema.columns.iter())
+                .any(|(column, work_table_column)| column.data_type != work_table_column.data_type);
+
+            if !widened {
+                // the names and nullability come from the anchor
+                let output_schema = set_operation_output_schema(
+                    &anchor.output_schema,
+                    &output_schema,
+                    &SetOperator::Union,
+                )?;
+
+                return Ok(PlanNode {
+                    output_schema,
+                    node: Node::RecursiveCte {
+                        id,
+                        all,
+                        anchor: Box::new(anchor),
+                        recursive: Box::new(recursive),
+                    },
+                });
+            }
+
+            for (work_table_column, column) in work_table_schema
+                .columns
+                .iter_mut()
+                .zip(output_schema.columns.iter())
+            {
+                work_table_column.data_type = column.data_type;
+            }
+        }
+
+        Err(Error::Planner(format!(
+            "Could not find the types of recursive query {}",
+            self.ctes.borrow()[index].cte.alias.name.value
+        )))
+    }
+
+    fn next_work_table_id(&self) -> usize {
+        let id = self.next_work_table.get();
+        self.next_work_table.set(id + 1);
+        id
+    }
+
+    // renames the output columns of a subquery with the names of the alias and qualifies them with the alias
+    fn build_subquery_alias(&self, child: PlanNode, alias: &TableAlias) -> Result<PlanNode, Error> {
+        let columns = &child.output_schema.columns;
+
+        if alias.columns.len() > columns.len() {
+            return Err(Error::Planner(format!(
+                "Table {} has {} columns available but {} columns specified",
+                alias.name.value,
+                columns.len(),
+                alias.columns.len()
+            )));
+        }
+
+        let mut output_schema = OutputSchema::new();
+        for (i, column) in columns.iter().enumerate() {
+            // columns without a name in the alias keep the name they have in the subquery
+            let column_name = match alias.columns.get(i) {
+                Some(name) => name.value.clone(),
+                None => column.output_name(),
+            };
+
+            output_schema.add_column(Column {
+                label: None,
+                table: Some(alias.name.value.clone()),
+                column_name,
+                data_type: column.data_type,
+                nullable: column.nullable,
+                hidden: false,
+            })?;
+        }
+
+        Ok(PlanNode {
+            output_schema,
+            node: Node::SubqueryAlias {
+                alias: alias.name.value.clone(),
+                child: Box::new(child),
+            },
+        })
+    }
+
+    // plans a WHERE or HAVING filter, the conjunctions with subqueries are filtered last, each right above its own subqueries
+    fn build_filter(
+        &self,
+        filter: Expr,
+        child: PlanNode,
+        params: &[Field],
+    ) -> Result<PlanNode, Error> {
+        let mut conjuncts = Vec::new();
+        let mut conjuncts_with_subqueries = Vec::new();
+
+        for mut conjunct in split_conjunction(&filter) {
+            let mut subqueries = Vec::new();
+            self.extract_subqueries_from_expr(&mut conjunct, &mut subqueries);
+            if subqueries.is_empty() {
+                conjuncts.push(conjunct);
+            } else {
+                conjuncts_with_subqueries.push((conjunct, subqueries));
+            }
+        }
+
+        if conjuncts_with_subqueries.is_empty() {
+            return Ok(Self::with_filter(child, filter));
+        }
+
+        let mut node = match combine_conjunction(conjuncts) {
+            Some(filter) => Self::with_filter(child, filter),
+            None => child,
+        };
+        for (conjunct, subqueries) in conjuncts_with_subqueries {
+            node = self.build_subqueries(node, subqueries, params)?;
+            node = Self::with_filter(node, conjunct);
+        }
+
+        Ok(node)
+    }
+
+    fn with_filter(child: PlanNode, filter: Expr) -> PlanNode {
+        PlanNode {
+            output_schema: child.output_schema.clone(),
+            node: Node::Filter {
+                filter,
+                child: Box::new(child),
+            },
+        }
+    }
+
+    // replaces every subquery of the expression with the column its result is added in, equal subqueries share a column
+    fn extract_subqueries_from_expr(
+        &self,
+        item: &mut Expr,
+        subqueries: &mut Vec<ExtractedSubquery>,
+    ) {
+        let (kind, query, negated) = match item {
+            Expr::Subquery(query) => (SubqueryKind::Scalar, query.clone(), false),
+            Expr::Exists { subquery, negated } => {
+                (SubqueryKind::Exists, subquery.clone(), *negated)
+            }
+            Expr::InSubquery {
+                expr,
+                subquery,
+                negated,
+            } => {
+                self.extract_subqueries_from_expr(expr, subqueries);
+                (SubqueryKind::In(*expr.clone()), subquery.clone(), *negated)
+            }
+            Expr::Nested(expr)
+            | Expr::UnaryOp { op: _, expr }
+            | Expr::IsNull(expr)
+            | Expr::IsNotNull(expr)
+            | Expr::IsTrue(expr)
+            | Expr::IsNotTrue(expr)
+            | Expr::IsFalse(expr)
+            | Expr::IsNotFalse(expr)
+            | Expr::IsUnknown(expr)
+            | Expr::IsNotUnknown(expr)
+            | Expr::Cast { expr, .. }
+            | Expr::TryCast { expr, .. }
+            | Expr::SafeCast { expr, .. }
+            | Expr::Ceil { expr, .. }
+            | Expr::Floor { expr, .. }
+            | Expr::Extract { expr, .. } => {
+                return self.extract_subqueries_from_expr(expr, subqueries);
+            }
+            Expr::Trim {
+                expr, trim_what, ..
+            } => {
+                self.extract_subqueries_from_expr(expr, subqueries);
+                if let Some(what) = trim_what {
+                    self.extract_subqueries_from_expr(what, subqueries);
+                }
+                return;
+            }
+            Expr::Substring {
+                expr,
+                substring_from,
+                substring_for,
+            } => {
+                self.extract_subqueries_from_expr(expr, subqueries);
+                for arg in substring_from.iter_mut().chain(substring_for.iter_mut()) {
+                    self.extract_subqueries_from_expr(arg, subqueries);
+                }
+                return;
+            }
+            Expr::BinaryOp { left, op: _, right }
+            | Expr::IsDistinctFrom(left, right)
+            | Expr::IsNotDistinctFrom(left, right)
+            | Expr::Like {
+                expr: left,
+                pattern: right,
+                ..
+            }
+            | Expr::ILike {
+                expr: left,
+                pattern: right,
+                ..
+            }
+            | Expr::SimilarTo {
+                expr: left,
+                pattern: right,
+                ..
+            } => {
+                self.extract_subqueries_from_expr(left, subqueries);
+                self.extract_subqueries_from_expr(right, subqueries);
+                return;
+            }
+            Expr::Function(function) => {
+                for arg in function.args.iter_mut() {
+                    if let FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) = arg {
+                        self.extract_subqueries_from_expr(expr, subqueries);
+                    }
+                }
+                return;
+            }
+            Expr::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => {
+                for expr in operand
+                    .iter_mut()
+                    .chain(else_result.iter_mut())
+                    .map(|expr| expr.as_mut())
+                    .chain(conditions.iter_mut())
+                    .chain(results.iter_mut())
+                {
+                    self.extract_subqueries_from_expr(expr, subqueries);
+                }
+                return;
+            }
+            Expr::InList { expr, list, .. } => {
+                self.extract_subqueries_from_expr(expr, subqueries);
+                for item in list.iter_mut() {
+                    self.extract_subqueries_from_expr(item, subqueries);
+                }
+                return;
+            }
+            Expr::Between {
+                expr, low, high, ..
+            } => {
+                self.extract_subqueries_from_expr(expr, subqueries);
+                self.extract_subqueries_from_expr(low, subqueries);
+                self.extract_subqueries_from_expr(high, subqueries);
+                return;
+            }
+            _ => return,
+        };
+
+        let name = match subqueries
+            .iter()
+            .find(|subquery| subquery.kind == kind && subquery.query == *query)
+        {
+            Some(subquery) => subquery.name.clone(),
+            None => {
+                let name = format!("#subquery{}", self.next_subquery.get());
+                self.next_subquery.set(self.next_subquery.get() + 1);
+                subqueries.push(ExtractedSubquery {
+                    kind,
+                    query: *query,
+                    name: name.clone(),
+                });
+                name
+            }
+        };
+
+        let column = Expr::Identifier(Ident::new(format!("{}.value", name)));
+        *item = if negated {
+            Expr::UnaryOp {
+                op: UnaryOperator::Not,
+                expr: Box::new(column),
+            }
         } else {
-            Ok(child)
+            column
+        };
+    }
+
+    // adds the result of each subquery as a hidden column of the node so the expressions above can read it
+    fn build_subqueries(
+        &self,
+        child: PlanNode,
+        subqueries: Vec<ExtractedSubquery>,
+        params: &[Field],
+    ) -> Result<PlanNode, Error> {
+        let mut node = child;
+
+        for subquery in subqueries {
+            let (plan, correlated) = self.build_subquery(&subquery.query, &node, params)?;
+
+            let columns = &plan.output_schema.columns;
+            if subquery.kind != SubqueryKind::Exists && columns.len() != 1 {
+                return Err(Error::Planner(
+                    "Subquery must return only one column".to_string(),
+                ));
+            }
+
+            // a scalar subquery is null without rows and an IN is null when a compared value is null
+            let (data_type, nullable) = match &subquery.kind {
+                SubqueryKind::Scalar => (columns[0].data_type, true),
+                SubqueryKind::Exists => (DataType::Boolean, false),
+                SubqueryKind::In(expr) => (
+                    DataType::Boolean,
+                    infer_type(expr, &node.output_schema).1 || columns[0].nullable,
+                ),
+            };
+
+            let mut output_schema = node.output_schema.clone();
+            output_schema.add_column(Column {
+                label: None,
+                table: Some(subquery.name),
+                column_name: "value".to_string(),
+                data_type,
+                nullable,
+                hidden: true,
+            })?;
+
+            node = PlanNode {
+                output_schema,
+                node: Node::Apply {
+                    kind: subquery.kind,
+                    correlated,
+                    child: Box::new(node),
+                    subquery: Box::new(plan),
+                },
+            };
+        }
+
+        Ok(node)
+    }
+
+    // plans a subquery of an expression, it is correlated with the outer row only when it cannot be planned without its columns
+    fn build_subquery(
+        &self,
+        query: &Query,
+        outer: &PlanNode,
+        params: &[Field],
+    ) -> Result<(PlanNode, Option<usize>), Error> {
+        let uncorrelated = self
+            .build_query(query, params)
+            .and_then(|node| binder::bind(&node).map(|_| node));
+        if let Ok(node) = uncorrelated {
+            return Ok((node, None));
+        }
+
+        // the outer columns are hidden so they are left out of wildcards and the columns of the subquery are found first
+        let mut output_schema = outer.output_schema.clone();
+        for column in &mut output_schema.columns {
+            column.hidden = true;
+        }
+
+        let id = self.next_work_table_id();
+        *self.outer_scope.borrow_mut() = Some(OuterScope { id, output_schema });
+
+        let node = self.build_query(query, params);
+
+        // the scope is not taken when planning fails before the FROM clause
+        self.outer_scope.borrow_mut().take();
+
+        let node = node?;
+        binder::bind(&node)?;
+        Ok((node, Some(id)))
+    }
+
+    // joins the outer row of a correlated subquery with every row of its FROM clause
+    fn build_outer_scope_join(&self, outer_scope: OuterScope, child: PlanNode) -> PlanNode {
+        let outer = PlanNode {
+            output_schema: outer_scope.output_schema,
+            node: Node::WorkTableScan { id: outer_scope.id },
+        };
+
+        PlanNode {
+            output_schema: join_output_schema(
+                &outer.output_schema,
+                &child.output_schema,
+                JoinType::Inner,
+            ),
+            node: Node::NestedLoopJoin {
+                child_left: Box::new(outer),
+                child_right: Box::new(child),
+                predicate: None,
+                join_type: JoinType::Inner,
+            },
         }
     }
 
@@ -559,7 +1900,7 @@ impl Planner {
         next_aggregate_number: &mut i32,
     ) -> Result<Vec<Function>, Error> {
         match item {
-            Expr::Function(function) => {
+            Expr::Function(function) if binder::is_aggregate(function) => {
                 // TODO(Dylan): verify that there are no nested aggregates
                 let function = function.clone();
                 // we replace the function with a new identifier
@@ -567,13 +1908,69 @@ impl Planner {
                 *next_aggregate_number += 1;
                 Ok(vec![function])
             }
-            Expr::UnaryOp { op: _op, expr } => {
+            Expr::Nested(expr)
+            | Expr::UnaryOp { op: _, expr }
+            | Expr::IsNull(expr)
+            | Expr::IsNotNull(expr)
+            | Expr::IsTrue(expr)
+            | Expr::IsNotTrue(expr)
+            | Expr::IsFalse(expr)
+            | Expr::IsNotFalse(expr)
+            | Expr::IsUnknown(expr)
+            | Expr::IsNotUnknown(expr)
+            | Expr::Cast { expr, .. }
+            | Expr::TryCast { expr, .. }
+            | Expr::SafeCast { expr, .. }
+            | Expr::Ceil { expr, .. }
+            | Expr::Floor { expr, .. }
+            | Expr::Extract { expr, .. }
+            // the subquery has its own aggregates
+            | Expr::InSubquery { expr, .. } => {
                 Self::extract_aggregates_from_expr(expr, next_aggregate_number)
             }
-            Expr::BinaryOp {
-                left,
-                op: _op,
-                right,
+            Expr::Trim {
+                expr, trim_what, ..
+            } => {
+                let mut res = Self::extract_aggregates_from_expr(expr, next_aggregate_number)?;
+                if let Some(what) = trim_what {
+                    res.append(&mut Self::extract_aggregates_from_expr(
+                        what,
+                        next_aggregate_number,
+                    )?);
+                }
+                Ok(res)
+            }
+            Expr::Substring {
+                expr,
+                substring_from,
+                substring_for,
+            } => {
+                let mut res = Self::extract_aggregates_from_expr(expr, next_aggregate_number)?;
+                for arg in substring_from.iter_mut().chain(substring_for.iter_mut()) {
+                    res.append(&mut Self::extract_aggregates_from_expr(
+                        arg,
+                        next_aggregate_number,
+                    )?);
+                }
+                Ok(res)
+            }
+            Expr::BinaryOp { left, op: _, right }
+            | Expr::IsDistinctFrom(left, right)
+            | Expr::IsNotDistinctFrom(left, right)
+            | Expr::Like {
+                expr: left,
+                pattern: right,
+                ..
+            }
+            | Expr::ILike {
+                expr: left,
+                pattern: right,
+                ..
+            }
+            | Expr::SimilarTo {
+                expr: left,
+                pattern: right,
+                ..
             } => {
                 let mut l = Self::extract_aggregates_from_expr(left, next_aggregate_number)?;
                 let mut r = Self::extract_aggregates_from_expr(right, next_aggregate_number)?;
@@ -581,6 +1978,64 @@ impl Planner {
                 l.append(&mut r);
                 Ok(l)
             }
+            // scalar functions are evaluated after the aggregation so only their arguments can contain aggregates
+            Expr::Function(function) => {
+                let mut res = Vec::new();
+                for arg in function.args.iter_mut() {
+                    if let FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) = arg {
+                        res.append(&mut Self::extract_aggregates_from_expr(
+                            expr,
+                            next_aggregate_number,
+                        )?);
+                    }
+                }
+                Ok(res)
+            }
+            Expr::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => {
+                let mut res = Vec::new();
+                for expr in operand
+                    .iter_mut()
+                    .chain(else_result.iter_mut())
+                    .map(|expr| expr.as_mut())
+                    .chain(conditions.iter_mut())
+                    .chain(results.iter_mut())
+                {
+                    res.append(&mut Self::extract_aggregates_from_expr(
+                        expr,
+                        next_aggregate_number,
+                    )?);
+                }
+                Ok(res)
+            }
+            Expr::InList { expr, list, .. } => {
+                let mut res = Self::extract_aggregates_from_expr(expr, next_aggregate_number)?;
+                for item in list.iter_mut() {
+                    res.append(&mut Self::extract_aggregates_from_expr(
+                        item,
+                        next_aggregate_number,
+                    )?);
+                }
+                Ok(res)
+            }
+            Expr::Between {
+                expr, low, high, ..
+            } => {
+                let mut res = Self::extract_aggregates_from_expr(expr, next_aggregate_number)?;
+                res.append(&mut Self::extract_aggregates_from_expr(
+                    low,
+                    next_aggregate_number,
+                )?);
+                res.append(&mut Self::extract_aggregates_from_expr(
+                    high,
+                    next_aggregate_number,
+                )?);
+                Ok(res)
+            }
             _ => Ok(vec![]),
         }
     }
@@ -593,30 +2048,110 @@ impl Planner {
         let mut literals = Vec::new();
 
         match expr {
-            Expr::Identifier(ident) => {
-                // TODO(Dylan): This is a hack since we do not want to include the aggregates here
-                if ident.value.starts_with("#agg") {
-                    return literals;
+            // TODO(Dylan): This is a hack since we do not want to include the aggregates here
+            Expr::Identifier(ident) if ident.value.starts_with("#agg") => {}
+            // This removes duplicates identifiers
+            Expr::Identifier(ident) if !seen.contains(&ident.value) => {
+                literals.push(SelectItem::UnnamedExpr(Expr::Identifier(ident.clone())));
+                seen.insert(ident.value.clone());
+            }
+            Expr::CompoundIdentifier(..) if !seen.contains(expr.to_string().as_str()) => {
+                literals.push(SelectItem::UnnamedExpr(expr.clone()));
+                seen.insert(expr.to_string());
+            }
+            Expr::BinaryOp { left, op: _, right }
+            | Expr::IsDistinctFrom(left, right)
+            | Expr::IsNotDistinctFrom(left, right)
+            | Expr::Like {
+                expr: left,
+                pattern: right,
+                ..
+            }
+            | Expr::ILike {
+                expr: left,
+                pattern: right,
+                ..
+            }
+            | Expr::SimilarTo {
+                expr: left,
+                pattern: right,
+                ..
+            } => {
+                literals.append(&mut Self::extract_identifiers_as_select_items(left, seen));
+                literals.append(&mut Self::extract_identifiers_as_select_items(right, seen));
+            }
+            Expr::Nested(expr)
+            | Expr::UnaryOp { op: _, expr }
+            | Expr::IsNull(expr)
+            | Expr::IsNotNull(expr)
+            | Expr::IsTrue(expr)
+            | Expr::IsNotTrue(expr)
+            | Expr::IsFalse(expr)
+            | Expr::IsNotFalse(expr)
+            | Expr::IsUnknown(expr)
+            | Expr::IsNotUnknown(expr)
+            | Expr::Cast { expr, .. }
+            | Expr::TryCast { expr, .. }
+            | Expr::SafeCast { expr, .. }
+            | Expr::Ceil { expr, .. }
+            | Expr::Floor { expr, .. }
+            | Expr::Extract { expr, .. }
+            | Expr::InSubquery { expr, .. } => {
+                literals.append(&mut Self::extract_identifiers_as_select_items(expr, seen));
+            }
+            Expr::Trim {
+                expr, trim_what, ..
+            } => {
+                literals.append(&mut Self::extract_identifiers_as_select_items(expr, seen));
+                if let Some(what) = trim_what {
+                    literals.append(&mut Self::extract_identifiers_as_select_items(what, seen));
                 }
-
-                // This removes duplicates identifiers
-                if !seen.contains(&ident.value) {
-                    literals.push(SelectItem::UnnamedExpr(Expr::Identifier(ident.clone())));
-                    seen.insert(ident.value.clone());
+            }
+            Expr::Substring {
+                expr,
+                substring_from,
+                substring_for,
+            } => {
+                literals.append(&mut Self::extract_identifiers_as_select_items(expr, seen));
+                for arg in substring_from.iter().chain(substring_for.iter()) {
+                    literals.append(&mut Self::extract_identifiers_as_select_items(arg, seen));
+                }
+            }
+            Expr::Function(function) => {
+                for arg in &function.args {
+                    if let FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) = arg {
+                        literals.append(&mut Self::extract_identifiers_as_select_items(expr, seen));
+                    }
                 }
             }
-            Expr::CompoundIdentifier(..) => {
-                if !seen.contains(expr.to_string().as_str()) {
-                    literals.push(SelectItem::UnnamedExpr(expr.clone()));
-                    seen.insert(expr.to_string());
+            Expr::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => {
+                for expr in operand
+                    .iter()
+                    .chain(else_result.iter())
+                    .map(|expr| expr.as_ref())
+                    .chain(conditions)
+                    .chain(results)
+                {
+                    literals.append(&mut Self::extract_identifiers_as_select_items(expr, seen));
                 }
             }
-            Expr::BinaryOp { left, op: _, right } => {
-                literals.append(&mut Self::extract_identifiers_as_select_items(left, seen));
-                literals.append(&mut Self::extract_identifiers_as_select_items(right, seen));
+            Expr::InList { expr, list, .. } => {
+                literals.append(&mut Self::extract_identifiers_as_select_items(expr, seen));
+                for item in list {
+                    literals.append(&mut Self::extract_identifiers_as_select_items(item, seen));
+                }
             }
-            Expr::UnaryOp { op: _, expr } => {
+            Expr::Between {
+                expr, low, high, ..
+            } => {
                 literals.append(&mut Self::extract_identifiers_as_select_items(expr, seen));
+                literals.append(&mut Self::extract_identifiers_as_select_items(low, seen));
+                literals.append(&mut Self::extract_identifiers_as_select_items(high, seen));
             }
             _ => {}
         }
@@ -624,27 +2159,301 @@ impl Planner {
         literals
     }
 
-    fn build_limit_clause(&self, child: PlanNode, limit: Option<Expr>) -> Result<PlanNode, Error> {
-        if limit.is_some() {
-            let limit = limit.as_ref().unwrap();
-            let limit = match limit {
-                Expr::Value(sqlparser::ast::Value::Number(n, _)) => n.parse::<u64>().unwrap(),
-                _ => {
-                    return Err(Error::Planner(
-                        "Only numbers supported for limit clause".to_string(),
-                    ))
-                }
-            };
+    // replaces positions and aliases in the order by clause with the select item expressions they reference
+    fn resolve_order_by(
+        &self,
+        order_by: &[OrderByExpr],
+        projection: &[SelectItem],
+        child: &PlanNode,
+    ) -> Result<Vec<OrderByExpr>, Error> {
+        let select_items = self.replace_wildcards(projection.to_vec(), child)?;
 
-            Ok(PlanNode {
-                output_schema: child.output_schema.clone(),
-                node: Node::Limit {
-                    limit,
-                    child: Box::new(child),
-                },
+        let mut res = Vec::new();
+
+        for item in order_by {
+            res.push(OrderByExpr {
+                expr: Self::resolve_select_reference(&item.expr, &select_items, "ORDER BY")?,
+                asc: item.asc,
+                nulls_first: item.nulls_first,
+            });
+        }
+
+        Ok(res)
+    }
+
+    // the output columns of a set operation can be referenced by name or position
+    fn resolve_set_order_by(
+        &self,
+        order_by: &[OrderByExpr],
+        child: &PlanNode,
+    ) -> Result<Vec<OrderByExpr>, Error> {
+        let select_items = child
+            .output_schema
+            .columns
+            .iter()
+            .map(|column| SelectItem::UnnamedExpr(column.as_expr()))
+            .collect::<Vec<SelectItem>>();
+
+        order_by
+            .iter()
+            .map(|item| {
+                Ok(OrderByExpr {
+                    expr: Self::resolve_select_reference(&item.expr, &select_items, "ORDER BY")?,
+                    asc: item.asc,
+                    nulls_first: item.nulls_first,
+                })
             })
-        } else {
-            Ok(child)
+            .collect()
+    }
+
+    // DISTINCT ON can reference select items by alias or position like ORDER BY
+    fn resolve_distinct_on(
+        &self,
+        distinct: &Option<Distinct>,
+        projection: &[SelectItem],
+        child: &PlanNode,
+    ) -> Result<Vec<Expr>, Error> {
+        let Some(Distinct::On(exprs)) = distinct else {
+            return Ok(vec![]);
+        };
+        let select_items = self.replace_wildcards(projection.to_vec(), child)?;
+
+        exprs
+            .iter()
+            .map(|expr| Self::resolve_select_reference(expr, &select_items, "DISTINCT ON"))
+            .collect()
+    }
+
+    // replaces a position or the alias of a select item with the expression of the item
+    fn resolve_select_reference(
+        expr: &Expr,
+        select_items: &[SelectItem],
+        clause: &str,
+    ) -> Result<Expr, Error> {
+        match expr {
+            Expr::Value(sqlparser::ast::Value::Number(n, _)) => {
+                let position = match n.parse::<usize>() {
+                    Ok(position) if position >= 1 && position <= select_items.len() => position,
+                    _ => {
+                        return Err(Error::Planner(format!(
+                            "{} position {} is not in select list",
+                            clause, n
+                        )))
+                    }
+                };
+
+                match &select_items[position - 1] {
+                    SelectItem::UnnamedExpr(expr) => Ok(expr.clone()),
+                    SelectItem::ExprWithAlias { expr, .. } => Ok(expr.clone()),
+                    item => Err(Error::Planner(format!("{} not supported", item))),
+                }
+            }
+            Expr::Identifier(ident) => Ok(select_items
+                .iter()
+                .find_map(|select_item| match select_item {
+                    SelectItem::ExprWithAlias { expr, alias } if alias.value == ident.value => {
+                        Some(expr.clone())
+                    }
+                    _ => None,
+                })
+                .unwrap_or(expr.clone())),
+            _ => Ok(expr.clone()),
+        }
+    }
+
+    // the rows of SELECT DISTINCT are only ordered when every sort expression is one of the distinct columns
+    fn check_distinct_order_by(
+        &self,
+        order_by: &[OrderByExpr],
+        projection: &[SelectItem],
+        child: &PlanNode,
+    ) -> Result<(), Error> {
+        let select_items = self.replace_wildcards(projection.to_vec(), child)?;
+        for item in order_by {
+            let selected = select_items.iter().any(|select_item| match select_item {
+                SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => {
+                    *expr == item.expr
+                }
+                _ => false,
+            });
+            if !selected {
+                return Err(Error::Planner(format!(
+                    "For SELECT DISTINCT, ORDER BY expressions must appear in select list: {}",
+                    item.expr
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // keeps the first row of every distinct value of the expressions
+    fn build_distinct_on_clause(&self, child: PlanNode, on: &[Expr]) -> PlanNode {
+        if on.is_empty() {
+            return child;
+        }
+
+        PlanNode {
+            output_schema: child.output_schema.clone(),
+            node: Node::Distinct {
+                on: on.to_vec(),
+                child: Box::new(child),
+            },
+        }
+    }
+
+    fn build_order_by_clause(
+        &self,
+        child: PlanNode,
+        order_by: &[OrderByExpr],
+    ) -> Result<PlanNode, Error> {
+        if order_by.is_empty() {
+            return Ok(child);
+        }
+
+        Ok(PlanNode {
+            output_schema: child.output_schema.clone(),
+            node: Node::Sort {
+                order_by: order_by.to_vec(),
+                child: Box::new(child),
+            },
+        })
+    }
+
+    fn build_limit_clause(
+        &self,
+        child: PlanNode,
+        limit: &Option<Expr>,
+        offset: &Option<Offset>,
+        fetch: &Option<Fetch>,
+        params: &[Field],
+    ) -> Result<PlanNode, Error> {
+        if limit.is_some() && fetch.is_some() {
+            return Err(Error::Planner(
+                "LIMIT and FETCH cannot be used together".to_string(),
+            ));
+        }
+
+        let limit = match (limit, fetch) {
+            (Some(limit), _) => Some(self.evaluate_row_count(limit, params, "limit")?),
+            (_, Some(fetch)) => {
+                if fetch.with_ties || fetch.percent {
+                    return Err(Error::Planner(format!("{} not supported", fetch)));
+                }
+                // FETCH FIRST ROW ONLY fetches a single row
+                match &fetch.quantity {
+                    Some(quantity) => Some(self.evaluate_row_count(quantity, params, "fetch")?),
+                    None => Some(1),
+                }
+            }
+            _ => None,
+        };
+
+        let offset = match offset {
+            Some(offset) => self.evaluate_row_count(&offset.value, params, "offset")?,
+            None => 0,
+        };
+
+        if limit.is_none() && offset == 0 {
+            return Ok(child);
+        }
+
+        Ok(PlanNode {
+            output_schema: child.output_schema.clone(),
+            node: Node::Limit {
+                limit,
+                offset,
+                child: Box::new(child),
+            },
+        })
+    }
+
+    // evaluates a constant expression used as a row count in a limit, fetch or offset clause
+    fn evaluate_row_count(
+        &self,
+        expr: &Expr,
+        params: &[Field],
+        clause: &str,
+    ) -> Result<u64, Error> {
+        let expr = Self::bind_parameters(expr, params)?;
+
+        let value = ExprEvaluator::evaluate_scalar(&expr)?;
+
+        let count = match value {
+            Field::Byte(n) => n as i64,
+            Field::Short(n) => n as i64,
+            Field::Int(n) => n as i64,
+            Field::Long(n) => n,
+            _ => {
+                return Err(Error::Planner(format!(
+                    "Only numbers supported for {} clause",
+                    clause
+                )))
+            }
+        };
+
+        if count < 0 {
+            return Err(Error::Planner(format!(
+                "{} clause must not be negative",
+                clause.to_uppercase()
+            )));
+        }
+
+        Ok(count as u64)
+    }
+
+    // replaces numbered placeholders ($1, $2, ...) with the values of the bound parameters
+    fn bind_parameters(expr: &Expr, params: &[Field]) -> Result<Expr, Error> {
+        match expr {
+            Expr::Value(sqlparser::ast::Value::Placeholder(placeholder)) => {
+                let Some(index) = placeholder
+                    .strip_prefix('$')
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .and_then(|n| n.checked_sub(1))
+                else {
+                    return Err(Error::Planner(format!(
+                        "Unsupported parameter: {}",
+                        placeholder
+                    )));
+                };
+
+                let Some(param) = params.get(index) else {
+                    return Err(Error::Planner(format!(
+                        "No value bound for parameter {}",
+                        placeholder
+                    )));
+                };
+
+                let value = match param {
+                    Field::Null => sqlparser::ast::Value::Null,
+                    Field::Bool(b) => sqlparser::ast::Value::Boolean(*b),
+                    Field::Str(s) => sqlparser::ast::Value::SingleQuotedString(s.clone()),
+                    Field::Byte(_)
+                    | Field::Short(_)
+                    | Field::Int(_)
+                    | Field::Long(_)
+                    | Field::Float(_)
+                    | Field::Double(_) => sqlparser::ast::Value::Number(param.to_string(), false),
+                    _ => {
+                        return Err(Error::Planner(format!(
+                            "Unsupported parameter value: {}",
+                            param
+                        )))
+                    }
+                };
+
+                Ok(Expr::Value(value))
+            }
+            Expr::Nested(expr) => Ok(Expr::Nested(Box::new(Self::bind_parameters(expr, params)?))),
+            Expr::UnaryOp { op, expr } => Ok(Expr::UnaryOp {
+                op: *op,
+                expr: Box::new(Self::bind_parameters(expr, params)?),
+            }),
+            Expr::BinaryOp { left, op, right } => Ok(Expr::BinaryOp {
+                left: Box::new(Self::bind_parameters(left, params)?),
+                op: op.clone(),
+                right: Box::new(Self::bind_parameters(right, params)?),
+            }),
+            _ => Ok(expr.clone()),
         }
     }
 }
@@ -1,6 +1,7 @@
 use crate::types::{error::Error, Chunk};
 
 pub mod parquet;
+mod statistics;
 
 pub trait StorageReader {
     fn next_chunk(&mut self) -> Result<Chunk, Error>;
@@ -1,49 +1,132 @@
 use parquet::{
-    file::reader::{FileReader, SerializedFileReader},
-    record::reader::RowIter,
+    basic::ConvertedType,
+    column::reader::{ColumnReader, ColumnReaderImpl},
+    data_type::{ByteArray, DataType as ParquetType},
+    file::{
+        reader::{FileReader, SerializedFileReader},
+        serialized_reader::ReadOptionsBuilder,
+    },
+    record::Field as ParquetField,
+    schema::types::{ColumnDescPtr, SchemaDescriptor},
 };
-use std::{fs::File, path::Path};
+use sqlparser::ast::Expr;
+use std::{cell::Cell, fs::File, path::Path, rc::Rc};
 
 use crate::{
     planner::OutputSchema,
-    types::{error::Error, Chunk, Column, TupleValue},
+    types::{
+        data_type::DataType,
+        error::Error,
+        field::Field,
+        vector::{Bitmap, Vector, VectorData},
+        Chunk, Column,
+    },
 };
 
-use super::StorageReader;
+use super::{statistics::can_skip_row_group, StorageReader};
 
 pub struct ParquetReader {
-    iter: RowIter<'static>,
+    reader: SerializedFileReader<File>,
+    // the leaf columns that are read and the types of their values
+    columns: Vec<(usize, ColumnDescPtr, DataType)>,
+    next_row_group: usize,
+    // the readers of the column chunks of the current row group and the number of its rows that are not read yet
+    column_readers: Vec<ColumnReader>,
+    remaining_rows: usize,
+    row_groups_pruned: u64,
 }
 
 impl StorageReader for ParquetReader {
     fn next_chunk(&mut self) -> Result<Chunk, Error> {
-        let mut chunk = Chunk::default();
-
-        for record in self.iter.by_ref() {
-            let row = record
-                .get_column_iter()
-                .map(|x| TupleValue { value: x.1.clone() })
-                .collect::<Vec<TupleValue>>();
-            chunk.add_row(row);
-
-            // TODO add an exeuction context with this information
-            if chunk.size() >= 1024 {
-                break;
+        // row groups without rows are skipped so an empty chunk is only returned after the last row group
+        while self.remaining_rows == 0 {
+            if self.next_row_group == self.reader.num_row_groups() {
+                return Ok(Chunk::default());
             }
+
+            let row_group = self
+                .reader
+                .get_row_group(self.next_row_group)
+                .map_err(|e| Error::Storage(e.to_string()))?;
+            self.column_readers = self
+                .columns
+                .iter()
+                .map(|(index, ..)| row_group.get_column_reader(*index))
+                .collect::<Result<Vec<ColumnReader>, _>>()
+                .map_err(|e| Error::Storage(e.to_string()))?;
+            self.remaining_rows = row_group.metadata().num_rows() as usize;
+            self.next_row_group += 1;
         }
 
-        Ok(chunk)
+        // TODO add an exeuction context with this information
+        let rows = self.remaining_rows.min(1024);
+        let columns = self
+            .column_readers
+            .iter_mut()
+            .zip(self.columns.iter())
+            .map(|(reader, (_, column, data_type))| {
+                Self::read_column(reader, column, *data_type, rows)
+            })
+            .collect::<Result<Vec<Vector>, Error>>()?;
+        self.remaining_rows -= rows;
+
+        Ok(Chunk::from_columns(columns, rows))
     }
 }
 
 impl ParquetReader {
-    pub fn new(table: String) -> Result<ParquetReader, Error> {
+    // only the columns at the indexes in the projection are read when there is one and row groups
+    // that cannot contain rows passing the filter are skipped
+    pub fn new(
+        table: String,
+        projection: Option<Vec<usize>>,
+        filter: Option<&Expr>,
+    ) -> Result<ParquetReader, Error> {
         let path = Path::new(table.as_str());
 
         if let Ok(file) = File::open(path) {
-            let reader = SerializedFileReader::new(file).unwrap();
+            let row_groups_pruned = Rc::new(Cell::new(0));
+
+            let mut options = ReadOptionsBuilder::new();
+            if let Some(filter) = filter {
+                let filter = filter.clone();
+                let row_groups_pruned = row_groups_pruned.clone();
+                options = options.with_predicate(Box::new(move |row_group, _| {
+                    let skip = can_skip_row_group(&filter, row_group);
+                    if skip {
+                        row_groups_pruned.set(row_groups_pruned.get() + 1);
+                    }
+                    !skip
+                }));
+            }
+
+            let reader = match SerializedFileReader::new_with_options(file, options.build()) {
+                Ok(reader) => reader,
+                Err(e) => return Err(Error::Storage(e.to_string())),
+            };
+
+            // the projection has the indexes of the columns of the table, nested fields are not part of the table
+            let schema_descr = reader.metadata().file_metadata().schema_descr();
+            let flat_columns = Self::flat_columns(schema_descr);
+            let columns = match projection {
+                Some(projection) => projection.iter().map(|i| flat_columns[*i]).collect(),
+                None => flat_columns,
+            }
+            .into_iter()
+            .map(|i| {
+                let column = schema_descr.column(i);
+                let data_type = DataType::from_parquet(&column);
+                (i, column, data_type)
+            })
+            .collect();
+
             Ok(ParquetReader {
-                iter: reader.into_iter(),
+                reader,
+                columns,
+                next_row_group: 0,
+                column_readers: Vec::new(),
+                remaining_rows: 0,
+                row_groups_pruned: row_groups_pruned.get(),
             })
         } else {
             Err(Error::Storage(
@@ -52,22 +135,254 @@ impl ParquetReader {
         }
     }
 
+    // reads the next rows of a column chunk into a vector of the type of the column
+    fn read_column(
+        reader: &mut ColumnReader,
+        column: &ColumnDescPtr,
+        data_type: DataType,
+        rows: usize,
+    ) -> Result<Vector, Error> {
+        let (data, validity) = match reader {
+            ColumnReader::BoolColumnReader(reader) => {
+                let (values, validity) = Self::read_values(reader, column, rows)?;
+                (VectorData::Bool(values), validity)
+            }
+            ColumnReader::Int32ColumnReader(reader) => {
+                let (values, validity) = Self::read_values(reader, column, rows)?;
+                let data = match data_type {
+                    DataType::Byte => VectorData::Byte(values.iter().map(|v| *v as i8).collect()),
+                    DataType::Short => {
+                        VectorData::Short(values.iter().map(|v| *v as i16).collect())
+                    }
+                    DataType::Int => VectorData::Int(values),
+                    DataType::Date => VectorData::Date(values),
+                    DataType::Decimal => VectorData::Decimal(
+                        values.iter().map(|v| *v as i128).collect(),
+                        column.type_precision(),
+                        column.type_scale(),
+                    ),
+                    _ => {
+                        return Ok(Self::convert_values(
+                            values,
+                            &validity,
+                            column,
+                            data_type,
+                            |v| ParquetField::convert_int32(column, v),
+                        ))
+                    }
+                };
+                (data, validity)
+            }
+            ColumnReader::Int64ColumnReader(reader) => {
+                let (values, validity) = Self::read_values(reader, column, rows)?;
+                let data = match data_type {
+                    DataType::Long => VectorData::Long(values),
+                    DataType::Timestamp
+                        if column.converted_type() == ConvertedType::TIMESTAMP_MILLIS =>
+                    {
+                        VectorData::Timestamp(
+                            values.iter().map(|v| v.saturating_mul(1000)).collect(),
+                        )
+                    }
+                    DataType::Timestamp => VectorData::Timestamp(values),
+                    DataType::Decimal => VectorData::Decimal(
+                        values.iter().map(|v| *v as i128).collect(),
+                        column.type_precision(),
+                        column.type_scale(),
+                    ),
+                    _ => {
+                        return Ok(Self::convert_values(
+                            values,
+                            &validity,
+                            column,
+                            data_type,
+                            |v| ParquetField::convert_int64(column, v),
+                        ))
+                    }
+                };
+                (data, validity)
+            }
+            // the legacy timestamps are read in milliseconds like the records of the parquet reader
+            ColumnReader::Int96ColumnReader(reader) => {
+                let (values, validity) = Self::read_values(reader, column, rows)?;
+                let values = values
+                    .iter()
+                    .map(|v| v.to_i64().saturating_mul(1000))
+                    .collect();
+                (VectorData::Timestamp(values), validity)
+            }
+            ColumnReader::FloatColumnReader(reader) => {
+                let (values, validity) = Self::read_values(reader, column, rows)?;
+                (VectorData::Float(values), validity)
+            }
+            ColumnReader::DoubleColumnReader(reader) => {
+                let (values, validity) = Self::read_values(reader, column, rows)?;
+                (VectorData::Double(values), validity)
+            }
+            ColumnReader::ByteArrayColumnReader(reader) => {
+                let (values, validity) = Self::read_values(reader, column, rows)?;
+                match data_type {
+                    DataType::String => {
+                        let values = values
+                            .into_iter()
+                            .zip(validity.iter())
+                            .map(|(value, valid)| match valid {
+                                true => String::from_utf8(value.data().to_vec())
+                                    .map_err(|e| Error::Storage(e.to_string())),
+                                false => Ok(String::new()),
+                            })
+                            .collect::<Result<Vec<String>, Error>>()?;
+                        (VectorData::Str(values), validity)
+                    }
+                    _ => {
+                        return Ok(Self::convert_values(
+                            values,
+                            &validity,
+                            column,
+                            data_type,
+                            |v| ParquetField::convert_byte_array(column, v),
+                        ))
+                    }
+                }
+            }
+            ColumnReader::FixedLenByteArrayColumnReader(reader) => {
+                let (values, validity) = Self::read_values(reader, column, rows)?;
+                return Ok(Self::convert_values(
+                    values,
+                    &validity,
+                    column,
+                    data_type,
+                    |v| ParquetField::convert_byte_array(column, ByteArray::from(v)),
+                ));
+            }
+        };
+
+        Ok(Vector::new(data, validity))
+    }
+
+    // reads the values of the next rows of a column chunk with a bitmap of the rows that are not null, the rows
+    // that are null have default values
+    fn read_values<T: ParquetType>(
+        reader: &mut ColumnReaderImpl<T>,
+        column: &ColumnDescPtr,
+        rows: usize,
+    ) -> Result<(Vec<T::T>, Bitmap), Error> {
+        let mut values = vec![T::T::default(); rows];
+        let mut levels = vec![0; rows];
+        let (rows_read, values_read, _) = reader
+            .read_records(
+                rows,
+                Some(levels.as_mut_slice()),
+                None,
+                values.as_mut_slice(),
+            )
+            .map_err(|e| Error::Storage(e.to_string()))?;
+        if rows_read != rows {
+            return Err(Error::Storage(format!(
+                "Expected {} values of column {} but read {}",
+                rows,
+                column.name(),
+                rows_read
+            )));
+        }
+
+        if column.max_def_level() == 0 {
+            return Ok((values, Bitmap::new(rows, true)));
+        }
+
+        // the values that are not null are read to the start and are moved to their rows from the last one
+        let validity: Bitmap = levels
+            .iter()
+            .map(|level| *level == column.max_def_level())
+            .collect();
+        let mut next = values_read;
+        for i in (0..rows).rev() {
+            if validity.get(i) {
+                next -= 1;
+                values.swap(i, next);
+            }
+        }
+
+        Ok((values, validity))
+    }
+
+    // the values that are not stored as they are read are converted the way parquet converts the fields of records
+    fn convert_values<T>(
+        values: Vec<T>,
+        validity: &Bitmap,
+        column: &ColumnDescPtr,
+        data_type: DataType,
+        convert: impl Fn(T) -> ParquetField,
+    ) -> Vector {
+        // the decimals of a column all have its precision and scale
+        let data = match data_type {
+            DataType::Decimal => {
+                VectorData::Decimal(Vec::new(), column.type_precision(), column.type_scale())
+            }
+            data_type => VectorData::for_type(data_type),
+        };
+
+        let mut vector = Vector::new(data, Bitmap::default());
+        for (value, valid) in values.into_iter().zip(validity.iter()) {
+            vector.push(match valid {
+                true => Field::from(convert(value)),
+                false => Field::Null,
+            });
+        }
+        vector
+    }
+
+    // the indexes of the leaf columns that are fields of the schema, the columns of nested fields are not read
+    fn flat_columns(schema_descr: &SchemaDescriptor) -> Vec<usize> {
+        (0..schema_descr.num_columns())
+            .filter(|i| schema_descr.column(*i).path().parts().len() == 1)
+            .collect()
+    }
+
+    // the number of row groups that were skipped using their statistics
+    pub fn row_groups_pruned(&self) -> u64 {
+        self.row_groups_pruned
+    }
+
+    pub fn read_row_count(table: &str) -> Result<u64, Error> {
+        let path = Path::new(table);
+
+        if let Ok(file) = File::open(path) {
+            let reader = match SerializedFileReader::new(file) {
+                Ok(reader) => reader,
+                Err(e) => return Err(Error::Storage(e.to_string())),
+            };
+            Ok(reader.metadata().file_metadata().num_rows() as u64)
+        } else {
+            Err(Error::Storage(
+                "Could not open file to read table metadata".to_string(),
+            ))
+        }
+    }
+
     pub fn read_metadata(table: &str) -> Result<OutputSchema, Error> {
         let path = Path::new(table);
 
         let mut output_schema = OutputSchema::new();
 
         if let Ok(file) = File::open(path) {
-            let reader = SerializedFileReader::new(file).unwrap();
+            let reader = match SerializedFileReader::new(file) {
+                Ok(reader) => reader,
+                Err(e) => return Err(Error::Storage(e.to_string())),
+            };
 
-            for column in reader
-                .metadata()
-                .file_metadata()
-                .schema_descr()
-                .columns()
-                .iter()
+            let schema_descr = reader.metadata().file_metadata().schema_descr();
+
+            for column in Self::flat_columns(schema_descr)
+                .into_iter()
+                .map(|i| schema_descr.column(i))
             {
-                output_schema.add_column(Column::new(None, column.name().to_string())?)?;
+                output_schema.add_column(Column::new(
+                    None,
+                    column.name().to_string(),
+                    DataType::from_parquet(&column),
+                    DataType::is_nullable(&column),
+                )?)?;
             }
 
             Ok(output_schema)
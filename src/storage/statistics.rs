@@ -0,0 +1,163 @@
+use std::cmp::Ordering;
+
+use parquet::{
+    basic::ConvertedType,
+    file::{
+        metadata::{ColumnChunkMetaData, RowGroupMetaData},
+        statistics::Statistics,
+    },
+};
+use sqlparser::ast::{BinaryOperator, Expr};
+
+use crate::{
+    executor::expression::ExprEvaluator,
+    types::{coercion, field::Field, parse_identifer},
+};
+
+// checks whether the min and max values of the columns in a row group show that no row in it can pass the filter
+pub fn can_skip_row_group(filter: &Expr, row_group: &RowGroupMetaData) -> bool {
+    match filter {
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => can_skip_row_group(left, row_group) || can_skip_row_group(right, row_group),
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::Or,
+            right,
+        } => can_skip_row_group(left, row_group) && can_skip_row_group(right, row_group),
+        Expr::Nested(expr) => can_skip_row_group(expr, row_group),
+        Expr::BinaryOp { left, op, right } => match (left.as_ref(), right.as_ref()) {
+            (column, Expr::Value(value)) => can_skip_comparison(column, op, value, row_group),
+            (Expr::Value(value), column) => match flip_operator(op) {
+                Some(op) => can_skip_comparison(column, &op, value, row_group),
+                None => false,
+            },
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+// checks a comparison between a column and a value such as year > 2010
+fn can_skip_comparison(
+    column: &Expr,
+    op: &BinaryOperator,
+    value: &sqlparser::ast::Value,
+    row_group: &RowGroupMetaData,
+) -> bool {
+    if !matches!(
+        op,
+        BinaryOperator::Eq
+            | BinaryOperator::Lt
+            | BinaryOperator::LtEq
+            | BinaryOperator::Gt
+            | BinaryOperator::GtEq
+    ) {
+        return false;
+    }
+
+    let Some(column) = find_column(column, row_group) else {
+        return false;
+    };
+    let Some(statistics) = column.statistics() else {
+        return false;
+    };
+
+    // a comparison with null is never true so a row group with only nulls never matches
+    if statistics.null_count() == row_group.num_rows() as u64 {
+        return true;
+    }
+
+    let Ok(value) = ExprEvaluator::evaluate_value(value) else {
+        return false;
+    };
+    let Some((min, max)) = get_min_max(column, statistics) else {
+        return false;
+    };
+    let (Some(value_min), Some(value_max)) = (
+        coercion::compare(&value, &min),
+        coercion::compare(&value, &max),
+    ) else {
+        return false;
+    };
+
+    match op {
+        BinaryOperator::Eq => value_min == Ordering::Less || value_max == Ordering::Greater,
+        BinaryOperator::Lt => value_min != Ordering::Greater,
+        BinaryOperator::LtEq => value_min == Ordering::Less,
+        BinaryOperator::Gt => value_max != Ordering::Less,
+        BinaryOperator::GtEq => value_max == Ordering::Greater,
+        _ => false,
+    }
+}
+
+// the operator to use when the value and the column are swapped, 5 < year is the same as year > 5
+fn flip_operator(op: &BinaryOperator) -> Option<BinaryOperator> {
+    match op {
+        BinaryOperator::Eq => Some(BinaryOperator::Eq),
+        BinaryOperator::Lt => Some(BinaryOperator::Gt),
+        BinaryOperator::LtEq => Some(BinaryOperator::GtEq),
+        BinaryOperator::Gt => Some(BinaryOperator::Lt),
+        BinaryOperator::GtEq => Some(BinaryOperator::LtEq),
+        _ => None,
+    }
+}
+
+fn find_column<'a>(
+    expr: &Expr,
+    row_group: &'a RowGroupMetaData,
+) -> Option<&'a ColumnChunkMetaData> {
+    let name = match expr {
+        Expr::Identifier(ident) => parse_identifer(&ident.value).ok()?.0,
+        Expr::CompoundIdentifier(idents) => idents.last()?.value.clone(),
+        _ => return None,
+    };
+
+    row_group
+        .columns()
+        .iter()
+        .find(|column| column.column_path().string() == name)
+}
+
+// converts the statistics to the same values the reader produces for the column
+fn get_min_max(column: &ColumnChunkMetaData, statistics: &Statistics) -> Option<(Field, Field)> {
+    if !statistics.has_min_max_set() {
+        return None;
+    }
+
+    let converted_type = column.column_descr().converted_type();
+
+    match statistics {
+        Statistics::Boolean(s) => Some((Field::Bool(*s.min()), Field::Bool(*s.max()))),
+        Statistics::Int32(s)
+            if matches!(converted_type, ConvertedType::NONE | ConvertedType::INT_32) =>
+        {
+            Some((Field::Int(*s.min()), Field::Int(*s.max())))
+        }
+        Statistics::Int32(s) if converted_type == ConvertedType::INT_8 => {
+            Some((Field::Byte(*s.min() as i8), Field::Byte(*s.max() as i8)))
+        }
+        Statistics::Int32(s) if converted_type == ConvertedType::INT_16 => {
+            Some((Field::Short(*s.min() as i16), Field::Short(*s.max() as i16)))
+        }
+        Statistics::Int64(s)
+            if matches!(converted_type, ConvertedType::NONE | ConvertedType::INT_64) =>
+        {
+            Some((Field::Long(*s.min()), Field::Long(*s.max())))
+        }
+        Statistics::Float(s) => Some((Field::Float(*s.min()), Field::Float(*s.max()))),
+        Statistics::Double(s) => Some((Field::Double(*s.min()), Field::Double(*s.max()))),
+        // older writers sorted strings as signed bytes so their min and max cannot be trusted
+        Statistics::ByteArray(s)
+            if converted_type == ConvertedType::UTF8 && !statistics.is_min_max_deprecated() =>
+        {
+            Some((
+                Field::Str(s.min().as_utf8().ok()?.to_string()),
+                Field::Str(s.max().as_utf8().ok()?.to_string()),
+            ))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,216 @@
+use std::{borrow::Cow, cmp::Ordering};
+
+use parquet::data_type::Decimal;
+
+use super::{data_type::DataType, datetime, decimal, field::Field, vector::VectorData};
+
+// converts two numbers of different types to the type they are combined in, an int and a double become two doubles
+// dates that are combined with timestamps are converted to timestamps in microseconds
+// strings that are combined with numbers, dates or timestamps are converted to their type
+pub fn promote(left: &Field, right: &Field) -> Option<(Field, Field)> {
+    let data_type = promoted_type(DataType::of_field(left), DataType::of_field(right))?;
+
+    Some((coerce(left, data_type)?, coerce(right, data_type)?))
+}
+
+// the type values of the two types are converted to before they are combined or compared
+pub fn promoted_type(left: DataType, right: DataType) -> Option<DataType> {
+    match (left, right) {
+        (DataType::String, other) | (other, DataType::String) if is_parsed(other) => {
+            Some(other.arithmetic_type())
+        }
+        _ => match DataType::wider_numeric(left, right) {
+            Some(data_type) => Some(data_type.arithmetic_type()),
+            None => DataType::wider_temporal(left, right),
+        },
+    }
+}
+
+// whether strings can be converted to values of the type by parsing them
+fn is_parsed(data_type: DataType) -> bool {
+    data_type.is_numeric() || matches!(data_type, DataType::Date | DataType::Timestamp)
+}
+
+// converts a value to a type that it can be used as without a cast, such as a number to a wider type
+pub fn coerce(field: &Field, data_type: DataType) -> Option<Field> {
+    match data_type {
+        DataType::Date => match field {
+            Field::Date(d) => Some(Field::Date(*d)),
+            Field::Str(s) => datetime::parse_date(s).map(Field::Date),
+            _ => None,
+        },
+        DataType::Timestamp => datetime::to_timestamp(field).map(Field::TimestampMicros),
+        data_type => match field {
+            Field::Str(s) if data_type.is_numeric() => parse_number(s.trim(), data_type),
+            field => cast_numeric(field, data_type),
+        },
+    }
+}
+
+fn parse_number(s: &str, data_type: DataType) -> Option<Field> {
+    match data_type {
+        DataType::Byte => s.parse().ok().map(Field::Byte),
+        DataType::Short => s.parse().ok().map(Field::Short),
+        DataType::Int => s.parse().ok().map(Field::Int),
+        DataType::Long => s.parse().ok().map(Field::Long),
+        DataType::Float => s.parse().ok().map(Field::Float),
+        DataType::Double => s.parse().ok().map(Field::Double),
+        DataType::Decimal => decimal::from_str(s),
+        _ => None,
+    }
+}
+
+// converts a number to a numeric type that is at least as wide as its own
+pub fn cast_numeric(field: &Field, data_type: DataType) -> Option<Field> {
+    match data_type {
+        DataType::Byte => match field {
+            Field::Byte(b) => Some(Field::Byte(*b)),
+            _ => None,
+        },
+        DataType::Short => match field {
+            Field::Byte(b) => Some(Field::Short(*b as i16)),
+            Field::Short(s) => Some(Field::Short(*s)),
+            _ => None,
+        },
+        DataType::Int => match field {
+            Field::Byte(b) => Some(Field::Int(*b as i32)),
+            Field::Short(s) => Some(Field::Int(*s as i32)),
+            Field::Int(i) => Some(Field::Int(*i)),
+            _ => None,
+        },
+        DataType::Long => as_integer(field).map(Field::Long),
+        DataType::Float => match field {
+            Field::Float(f) => Some(Field::Float(*f)),
+            _ => as_double(field).map(|d| Field::Float(d as f32)),
+        },
+        DataType::Double => as_double(field).map(Field::Double),
+        // integers become decimals with as many digits as their type can have
+        DataType::Decimal => match field {
+            Field::Byte(b) => Some(decimal::from_integer(*b as i64, 3)),
+            Field::Short(s) => Some(decimal::from_integer(*s as i64, 5)),
+            Field::Int(i) => Some(decimal::from_integer(*i as i64, 10)),
+            Field::Long(l) => Some(decimal::from_integer(*l, 19)),
+            Field::Decimal(d) => Some(Field::Decimal(d.clone())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// orders two values of the same type or two numbers of any type
+pub fn compare(left: &Field, right: &Field) -> Option<Ordering> {
+    let promoted = promote(left, right);
+    let (left, right) = match &promoted {
+        Some((left, right)) => (left, right),
+        None => (left, right),
+    };
+
+    match (left, right) {
+        (Field::Int(l), Field::Int(r)) => Some(l.cmp(r)),
+        (Field::Long(l), Field::Long(r)) => Some(l.cmp(r)),
+        (Field::Float(l), Field::Float(r)) => l.partial_cmp(r),
+        (Field::Double(l), Field::Double(r)) => l.partial_cmp(r),
+        (Field::Decimal(l), Field::Decimal(r)) => Some(decimal::compare(l, r)),
+        (Field::Str(l), Field::Str(r)) => Some(l.cmp(r)),
+        (Field::Bool(l), Field::Bool(r)) => Some(l.cmp(r)),
+        (Field::Date(l), Field::Date(r)) => Some(l.cmp(r)),
+        (Field::TimestampMicros(l), Field::TimestampMicros(r)) => Some(l.cmp(r)),
+        (Field::Interval(l), Field::Interval(r)) => {
+            Some(l.approximate_micros().cmp(&r.approximate_micros()))
+        }
+        _ => None,
+    }
+}
+
+// converts two numeric vectors of different types to the type they are combined in
+pub fn promote_vectors<'a>(
+    left: &'a VectorData,
+    right: &'a VectorData,
+) -> Option<(Cow<'a, VectorData>, Cow<'a, VectorData>)> {
+    let (left_type, right_type) = (vector_type(left)?, vector_type(right)?);
+    let data_type = DataType::wider_numeric(left_type, right_type)?.arithmetic_type();
+    if left_type == data_type && right_type == data_type {
+        return None;
+    }
+
+    Some((
+        cast_vector(left, data_type)?,
+        cast_vector(right, data_type)?,
+    ))
+}
+
+fn vector_type(data: &VectorData) -> Option<DataType> {
+    match data {
+        VectorData::Byte(_) => Some(DataType::Byte),
+        VectorData::Short(_) => Some(DataType::Short),
+        VectorData::Int(_) => Some(DataType::Int),
+        VectorData::Long(_) => Some(DataType::Long),
+        VectorData::Float(_) => Some(DataType::Float),
+        VectorData::Double(_) => Some(DataType::Double),
+        _ => None,
+    }
+}
+
+// converts the values of a numeric vector to a wider type
+macro_rules! widen {
+    ($values:expr, $data_type:expr) => {
+        match $data_type {
+            DataType::Int => VectorData::Int($values.iter().map(|x| *x as i32).collect()),
+            DataType::Long => VectorData::Long($values.iter().map(|x| *x as i64).collect()),
+            DataType::Float => VectorData::Float($values.iter().map(|x| *x as f32).collect()),
+            DataType::Double => VectorData::Double($values.iter().map(|x| *x as f64).collect()),
+            _ => return None,
+        }
+    };
+}
+
+fn cast_vector(data: &VectorData, data_type: DataType) -> Option<Cow<'_, VectorData>> {
+    if vector_type(data) == Some(data_type) {
+        return Some(Cow::Borrowed(data));
+    }
+
+    let cast = match data {
+        VectorData::Byte(v) => widen!(v, data_type),
+        VectorData::Short(v) => widen!(v, data_type),
+        VectorData::Int(v) => widen!(v, data_type),
+        VectorData::Long(v) => widen!(v, data_type),
+        VectorData::Float(v) => widen!(v, data_type),
+        _ => return None,
+    };
+    Some(Cow::Owned(cast))
+}
+
+fn as_integer(field: &Field) -> Option<i64> {
+    match field {
+        Field::Byte(v) => Some(*v as i64),
+        Field::Short(v) => Some(*v as i64),
+        Field::Int(v) => Some(*v as i64),
+        Field::Long(v) => Some(*v),
+        _ => None,
+    }
+}
+
+pub fn as_double(field: &Field) -> Option<f64> {
+    match field {
+        Field::Float(v) => Some(*v as f64),
+        Field::Double(v) => Some(*v),
+        Field::Decimal(d) => Some(decimal_to_double(d)),
+        _ => as_integer(field).map(|v| v as f64),
+    }
+}
+
+fn decimal_to_double(decimal: &Decimal) -> f64 {
+    decimal_unscaled(decimal) as f64 / 10f64.powi(decimal.scale())
+}
+
+// the unscaled value of a decimal is stored as a big endian two's complement integer
+pub fn decimal_unscaled(decimal: &Decimal) -> i128 {
+    let data = decimal.data();
+    let sign = if data.first().is_some_and(|b| b & 0x80 != 0) {
+        -1
+    } else {
+        0
+    };
+    data.iter()
+        .fold(sign as i128, |acc, b| (acc << 8) | *b as i128)
+}
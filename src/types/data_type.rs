@@ -0,0 +1,260 @@
+use std::fmt;
+
+use parquet::{
+    basic::{ConvertedType, Repetition, Type as PhysicalType},
+    schema::types::ColumnDescriptor,
+};
+use sqlparser::ast::ExactNumberInfo;
+
+use super::field::Field;
+
+// the precision and scale of a decimal without them, such as CAST(x AS DECIMAL)
+const DEFAULT_DECIMAL_PRECISION: i32 = 18;
+const DEFAULT_DECIMAL_SCALE: i32 = 3;
+// the unscaled values of decimals are computed as i128
+const MAX_DECIMAL_PRECISION: i32 = 38;
+
+// the logical type of the values in a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataType {
+    Boolean,
+    Byte,
+    Short,
+    Int,
+    Long,
+    Float,
+    Double,
+    Decimal,
+    String,
+    Date,
+    Timestamp,
+    Interval,
+    // the type of a null literal, it can be used where any other type is expected
+    Null,
+    // values of types the engine does not know how to work with such as raw bytes
+    Unknown,
+}
+
+impl DataType {
+    // the type of the values the parquet reader produces for the column
+    pub fn from_parquet(column: &ColumnDescriptor) -> DataType {
+        let converted_type = column.converted_type();
+
+        match column.physical_type() {
+            PhysicalType::BOOLEAN => DataType::Boolean,
+            PhysicalType::INT32 if converted_type == ConvertedType::INT_8 => DataType::Byte,
+            PhysicalType::INT32 if converted_type == ConvertedType::INT_16 => DataType::Short,
+            PhysicalType::INT32 if converted_type == ConvertedType::DATE => DataType::Date,
+            PhysicalType::INT64
+                if matches!(
+                    converted_type,
+                    ConvertedType::TIMESTAMP_MILLIS | ConvertedType::TIMESTAMP_MICROS
+                ) =>
+            {
+                DataType::Timestamp
+            }
+            // the reader converts the legacy INT96 timestamps to milliseconds
+            PhysicalType::INT96 => DataType::Timestamp,
+            PhysicalType::INT32
+                if matches!(converted_type, ConvertedType::NONE | ConvertedType::INT_32) =>
+            {
+                DataType::Int
+            }
+            PhysicalType::INT64
+                if matches!(converted_type, ConvertedType::NONE | ConvertedType::INT_64) =>
+            {
+                DataType::Long
+            }
+            PhysicalType::FLOAT => DataType::Float,
+            PhysicalType::DOUBLE => DataType::Double,
+            PhysicalType::INT32
+            | PhysicalType::INT64
+            | PhysicalType::BYTE_ARRAY
+            | PhysicalType::FIXED_LEN_BYTE_ARRAY
+                if converted_type == ConvertedType::DECIMAL =>
+            {
+                DataType::Decimal
+            }
+            PhysicalType::BYTE_ARRAY
+                if matches!(
+                    converted_type,
+                    ConvertedType::UTF8 | ConvertedType::ENUM | ConvertedType::JSON
+                ) =>
+            {
+                DataType::String
+            }
+            _ => DataType::Unknown,
+        }
+    }
+
+    // whether the column can contain nulls
+    pub fn is_nullable(column: &ColumnDescriptor) -> bool {
+        column.self_type().get_basic_info().repetition() != Repetition::REQUIRED
+    }
+
+    pub fn of_field(field: &Field) -> DataType {
+        match field {
+            Field::Null => DataType::Null,
+            Field::Bool(_) => DataType::Boolean,
+            Field::Byte(_) => DataType::Byte,
+            Field::Short(_) => DataType::Short,
+            Field::Int(_) => DataType::Int,
+            Field::Long(_) => DataType::Long,
+            Field::Float(_) => DataType::Float,
+            Field::Double(_) => DataType::Double,
+            Field::Decimal(_) => DataType::Decimal,
+            Field::Str(_) => DataType::String,
+            Field::Date(_) => DataType::Date,
+            Field::TimestampMillis(_) | Field::TimestampMicros(_) => DataType::Timestamp,
+            Field::Interval(_) => DataType::Interval,
+            _ => DataType::Unknown,
+        }
+    }
+
+    // the type of the values of a sql type such as VARCHAR(10), none if the engine cannot store values of the type
+    pub fn from_sql(data_type: &sqlparser::ast::DataType) -> Option<DataType> {
+        use sqlparser::ast::DataType as SqlType;
+
+        match data_type {
+            SqlType::Boolean => Some(DataType::Boolean),
+            SqlType::TinyInt(_) => Some(DataType::Byte),
+            SqlType::SmallInt(_) => Some(DataType::Short),
+            SqlType::Int(_) | SqlType::Integer(_) => Some(DataType::Int),
+            SqlType::BigInt(_) => Some(DataType::Long),
+            SqlType::Float(_) | SqlType::Real => Some(DataType::Float),
+            SqlType::Double | SqlType::DoublePrecision => Some(DataType::Double),
+            SqlType::Decimal(info) | SqlType::Numeric(info) | SqlType::Dec(info) => {
+                Self::decimal_precision_and_scale(info).map(|_| DataType::Decimal)
+            }
+            SqlType::Char(_)
+            | SqlType::Character(_)
+            | SqlType::Varchar(_)
+            | SqlType::CharVarying(_)
+            | SqlType::CharacterVarying(_)
+            | SqlType::Text
+            | SqlType::String => Some(DataType::String),
+            SqlType::Date => Some(DataType::Date),
+            SqlType::Timestamp(_, _) | SqlType::Datetime(_) => Some(DataType::Timestamp),
+            SqlType::Interval => Some(DataType::Interval),
+            _ => None,
+        }
+    }
+
+    // the precision and scale of a decimal type, none if decimals of that size are not supported
+    pub fn decimal_precision_and_scale(info: &ExactNumberInfo) -> Option<(i32, i32)> {
+        let (precision, scale) = match info {
+            ExactNumberInfo::None => (DEFAULT_DECIMAL_PRECISION, DEFAULT_DECIMAL_SCALE),
+            ExactNumberInfo::Precision(precision) => (i32::try_from(*precision).ok()?, 0),
+            ExactNumberInfo::PrecisionAndScale(precision, scale) => {
+                (i32::try_from(*precision).ok()?, i32::try_from(*scale).ok()?)
+            }
+        };
+
+        if !(1..=MAX_DECIMAL_PRECISION).contains(&precision) || scale >= precision {
+            return None;
+        }
+        Some((precision, scale))
+    }
+
+    // whether values of this type can be cast to the other type, strings can be cast to and from any type
+    pub fn can_cast_to(&self, target: DataType) -> bool {
+        match (self, target) {
+            (DataType::Null | DataType::Unknown, _)
+            | (DataType::String, _)
+            | (_, DataType::String) => true,
+            (DataType::Date | DataType::Timestamp, target) => {
+                matches!(target, DataType::Date | DataType::Timestamp)
+            }
+            (DataType::Interval, target) => target == DataType::Interval,
+            (source, target) => {
+                (source.is_numeric() || *source == DataType::Boolean)
+                    && (target.is_numeric() || target == DataType::Boolean)
+            }
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            DataType::Byte
+                | DataType::Short
+                | DataType::Int
+                | DataType::Long
+                | DataType::Float
+                | DataType::Double
+                | DataType::Decimal
+        )
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            DataType::Byte | DataType::Short | DataType::Int | DataType::Long
+        )
+    }
+
+    // the wider of two numeric types, values of the narrower type can be converted to it
+    pub fn wider_numeric(left: DataType, right: DataType) -> Option<DataType> {
+        if !left.is_numeric() || !right.is_numeric() {
+            return None;
+        }
+        if left.numeric_rank() >= right.numeric_rank() {
+            Some(left)
+        } else {
+            Some(right)
+        }
+    }
+
+    // the type numeric values are computed in, small integers are widened to int
+    pub fn arithmetic_type(&self) -> DataType {
+        match self {
+            DataType::Byte | DataType::Short => DataType::Int,
+            data_type => *data_type,
+        }
+    }
+
+    // the type dates and timestamps are compared as, a date and a timestamp are compared as timestamps
+    pub fn wider_temporal(left: DataType, right: DataType) -> Option<DataType> {
+        match (left, right) {
+            (DataType::Date, DataType::Date) => Some(DataType::Date),
+            (DataType::Timestamp, DataType::Date | DataType::Timestamp)
+            | (DataType::Date, DataType::Timestamp) => Some(DataType::Timestamp),
+            _ => None,
+        }
+    }
+
+    fn numeric_rank(&self) -> u8 {
+        match self {
+            DataType::Byte => 0,
+            DataType::Short => 1,
+            DataType::Int => 2,
+            DataType::Long => 3,
+            // integers are exact decimals but decimals combined with floating point numbers are approximated
+            DataType::Decimal => 4,
+            DataType::Float => 5,
+            _ => 6,
+        }
+    }
+}
+
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            DataType::Boolean => "BOOLEAN",
+            DataType::Byte => "TINYINT",
+            DataType::Short => "SMALLINT",
+            DataType::Int => "INT",
+            DataType::Long => "BIGINT",
+            DataType::Float => "FLOAT",
+            DataType::Double => "DOUBLE",
+            DataType::Decimal => "DECIMAL",
+            DataType::String => "VARCHAR",
+            DataType::Date => "DATE",
+            DataType::Timestamp => "TIMESTAMP",
+            DataType::Interval => "INTERVAL",
+            DataType::Null => "NULL",
+            DataType::Unknown => "UNKNOWN",
+        };
+        f.write_str(name)
+    }
+}
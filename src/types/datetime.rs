@@ -0,0 +1,351 @@
+use std::fmt::{self, Write};
+
+use chrono::{
+    format::{Item, StrftimeItems},
+    Datelike, Duration, Months, NaiveDate, NaiveDateTime, Timelike, Utc,
+};
+
+use super::field::Field;
+
+// dates are stored as days since the unix epoch and timestamps as microseconds since the unix epoch
+pub const MICROS_PER_DAY: i64 = 24 * 60 * 60 * 1_000_000;
+const MICROS_PER_SECOND: i64 = 1_000_000;
+const MICROS_PER_MINUTE: i64 = 60 * MICROS_PER_SECOND;
+const MICROS_PER_HOUR: i64 = 60 * MICROS_PER_MINUTE;
+
+const TIMESTAMP_FORMATS: [&str; 3] = [
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+];
+
+fn epoch() -> NaiveDate {
+    NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()
+}
+
+// parses a date such as 2016-02-03, the time of a timestamp is dropped
+pub fn parse_date(s: &str) -> Option<i32> {
+    let s = s.trim();
+    match NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        Ok(date) => i32::try_from((date - epoch()).num_days()).ok(),
+        Err(_) => parse_timestamp(s).map(timestamp_to_date),
+    }
+}
+
+// parses a timestamp such as 2016-02-03 07:55:29, a date on its own is midnight of that day
+pub fn parse_timestamp(s: &str) -> Option<i64> {
+    let s = s.trim();
+    for format in TIMESTAMP_FORMATS {
+        if let Ok(timestamp) = NaiveDateTime::parse_from_str(s, format) {
+            return Some(timestamp.timestamp_micros());
+        }
+    }
+
+    let date = NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()?;
+    Some((date - epoch()).num_days() * MICROS_PER_DAY)
+}
+
+pub fn date_to_timestamp(days: i32) -> i64 {
+    days as i64 * MICROS_PER_DAY
+}
+
+pub fn timestamp_to_date(micros: i64) -> i32 {
+    micros.div_euclid(MICROS_PER_DAY) as i32
+}
+
+pub fn format_date(days: i32) -> String {
+    (epoch() + Duration::days(days as i64))
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+// the fraction of a second is only shown when it is not zero
+pub fn format_timestamp(micros: i64) -> String {
+    match NaiveDateTime::from_timestamp_micros(micros) {
+        Some(timestamp) => timestamp.format("%Y-%m-%d %H:%M:%S%.f").to_string(),
+        None => micros.to_string(),
+    }
+}
+
+// the current time in utc, timestamps do not have a time zone
+pub fn now() -> i64 {
+    Utc::now().naive_utc().timestamp_micros()
+}
+
+// the timestamp of a date, timestamp or string value
+pub fn to_timestamp(field: &Field) -> Option<i64> {
+    match field {
+        Field::Date(d) => Some(date_to_timestamp(*d)),
+        Field::TimestampMillis(t) => t.checked_mul(1000),
+        Field::TimestampMicros(t) => Some(*t),
+        Field::Str(s) => parse_timestamp(s),
+        _ => None,
+    }
+}
+
+fn date_micros(date: NaiveDate) -> i64 {
+    (date - epoch()).num_days() * MICROS_PER_DAY
+}
+
+// moves the timestamp by the months, then the days and then the time of the interval
+pub fn add_interval(micros: i64, interval: &Interval) -> Option<i64> {
+    let timestamp = NaiveDateTime::from_timestamp_micros(micros)?;
+    let timestamp = if interval.months >= 0 {
+        timestamp.checked_add_months(Months::new(interval.months as u32))?
+    } else {
+        timestamp.checked_sub_months(Months::new(interval.months.unsigned_abs()))?
+    };
+    let timestamp = timestamp
+        .checked_add_signed(Duration::days(interval.days as i64))?
+        .checked_add_signed(Duration::microseconds(interval.micros))?;
+    Some(timestamp.timestamp_micros())
+}
+
+// the difference between two timestamps in days and the time that is left
+pub fn subtract_timestamps(left: i64, right: i64) -> Option<Interval> {
+    let micros = left.checked_sub(right)?;
+    Some(Interval {
+        months: 0,
+        days: i32::try_from(micros / MICROS_PER_DAY).ok()?,
+        micros: micros % MICROS_PER_DAY,
+    })
+}
+
+// the start of the unit the timestamp is in, such as midnight for day or the first of the month for month
+pub fn truncate(micros: i64, unit: &str) -> Option<i64> {
+    let timestamp = NaiveDateTime::from_timestamp_micros(micros)?;
+    let date = timestamp.date();
+    let year = date.year();
+    let first_day =
+        |year: i32, month: u32| NaiveDate::from_ymd_opt(year, month, 1).map(date_micros);
+
+    match unit.to_lowercase().as_str() {
+        "microsecond" | "microseconds" => Some(micros),
+        "millisecond" | "milliseconds" => Some(micros - micros.rem_euclid(1000)),
+        "second" | "seconds" => Some(micros - micros.rem_euclid(MICROS_PER_SECOND)),
+        "minute" | "minutes" => Some(micros - micros.rem_euclid(MICROS_PER_MINUTE)),
+        "hour" | "hours" => Some(micros - micros.rem_euclid(MICROS_PER_HOUR)),
+        "day" | "days" => Some(micros - micros.rem_euclid(MICROS_PER_DAY)),
+        // weeks start on monday
+        "week" | "weeks" => Some(date_micros(
+            date - Duration::days(date.weekday().num_days_from_monday() as i64),
+        )),
+        "month" | "months" => first_day(year, date.month()),
+        "quarter" | "quarters" => first_day(year, (date.month() - 1) / 3 * 3 + 1),
+        "year" | "years" => first_day(year, 1),
+        "decade" | "decades" => first_day(year - year.rem_euclid(10), 1),
+        // the first century and millennium start with the year 1
+        "century" | "centuries" => first_day((year - 1).div_euclid(100) * 100 + 1, 1),
+        "millennium" | "millennia" => first_day((year - 1).div_euclid(1000) * 1000 + 1, 1),
+        _ => None,
+    }
+}
+
+// a field of the timestamp such as the year or the hour
+pub fn date_part(micros: i64, part: &str) -> Option<i64> {
+    let timestamp = NaiveDateTime::from_timestamp_micros(micros)?;
+    let year = timestamp.year() as i64;
+    let micros_of_minute =
+        timestamp.second() as i64 * MICROS_PER_SECOND + micros.rem_euclid(MICROS_PER_SECOND);
+
+    let value = match part.to_lowercase().as_str() {
+        "millennium" | "millennia" | "millenium" => (year - 1).div_euclid(1000) + 1,
+        "century" | "centuries" => (year - 1).div_euclid(100) + 1,
+        "decade" | "decades" => year.div_euclid(10),
+        "year" | "years" => year,
+        "isoyear" => timestamp.iso_week().year() as i64,
+        "quarter" | "quarters" => (timestamp.month() as i64 - 1) / 3 + 1,
+        "month" | "months" => timestamp.month() as i64,
+        "week" | "weeks" => timestamp.iso_week().week() as i64,
+        "day" | "days" => timestamp.day() as i64,
+        // sunday is 0 for dow and 7 for isodow
+        "dow" => timestamp.weekday().num_days_from_sunday() as i64,
+        "isodow" => timestamp.weekday().number_from_monday() as i64,
+        "doy" => timestamp.ordinal() as i64,
+        "hour" | "hours" => timestamp.hour() as i64,
+        "minute" | "minutes" => timestamp.minute() as i64,
+        "second" | "seconds" => timestamp.second() as i64,
+        // milliseconds and microseconds include the seconds
+        "millisecond" | "milliseconds" => micros_of_minute / 1000,
+        "microsecond" | "microseconds" => micros_of_minute,
+        "epoch" => micros.div_euclid(MICROS_PER_SECOND),
+        _ => return None,
+    };
+    Some(value)
+}
+
+// formats the timestamp with the specifiers of strftime such as %Y-%m-%d
+pub fn strftime(micros: i64, format: &str) -> Option<String> {
+    let timestamp = NaiveDateTime::from_timestamp_micros(micros)?;
+    let items = StrftimeItems::new(format);
+    if items.clone().any(|item| matches!(item, Item::Error)) {
+        return None;
+    }
+
+    let mut res = String::new();
+    write!(res, "{}", timestamp.format_with_items(items)).ok()?;
+    Some(res)
+}
+
+// an interval is kept in months, days and microseconds since months and days do not always have the same length
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub micros: i64,
+}
+
+// the units of interval strings such as '1 day 2 hours', the number of months, days or microseconds of one unit
+const INTERVAL_UNITS: [(&[&str], i64, i64, i64); 11] = [
+    (&["microsecond", "microseconds", "us"], 0, 0, 1),
+    (&["millisecond", "milliseconds", "ms"], 0, 0, 1000),
+    (
+        &["second", "seconds", "sec", "secs", "s"],
+        0,
+        0,
+        MICROS_PER_SECOND,
+    ),
+    (
+        &["minute", "minutes", "min", "mins", "m"],
+        0,
+        0,
+        MICROS_PER_MINUTE,
+    ),
+    (&["hour", "hours", "h"], 0, 0, MICROS_PER_HOUR),
+    (&["day", "days", "d"], 0, 1, 0),
+    (&["week", "weeks", "w"], 0, 7, 0),
+    (&["month", "months", "mon", "mons"], 1, 0, 0),
+    (&["year", "years", "y"], 12, 0, 0),
+    (&["decade", "decades"], 120, 0, 0),
+    (&["century", "centuries"], 1200, 0, 0),
+];
+
+impl Interval {
+    // parses intervals such as '1 day', '2 hours 30 minutes', '1 year 2 months' or '1 day 02:03:04'
+    pub fn parse(s: &str) -> Option<Interval> {
+        let mut interval = Interval::default();
+        let mut tokens = s.split_whitespace().peekable();
+        tokens.peek()?;
+
+        while let Some(token) = tokens.next() {
+            if token.contains(':') {
+                interval = interval.checked_add(&Self::parse_time(token)?)?;
+                continue;
+            }
+
+            // the unit can be written together with the number such as 10d
+            let (number, unit) = match token.find(|c: char| c.is_alphabetic()) {
+                Some(i) if i > 0 => (&token[..i], token[i..].to_string()),
+                _ => (token, tokens.next()?.to_lowercase()),
+            };
+            let number: f64 = number.parse().ok()?;
+            let (_, months, days, micros) = INTERVAL_UNITS
+                .iter()
+                .find(|(names, ..)| names.contains(&unit.to_lowercase().as_str()))?;
+
+            // fractions of months and days are not kept
+            if (*months != 0 || *days != 0) && number.fract() != 0.0 {
+                return None;
+            }
+            let part = Interval {
+                months: i32::try_from(number as i64 * months).ok()?,
+                days: i32::try_from(number as i64 * days).ok()?,
+                micros: (number * *micros as f64).round() as i64,
+            };
+            interval = interval.checked_add(&part)?;
+        }
+
+        Some(interval)
+    }
+
+    // a time such as 02:03:04.5 or -01:30
+    fn parse_time(s: &str) -> Option<Interval> {
+        let (sign, s) = match s.strip_prefix('-') {
+            Some(s) => (-1, s),
+            None => (1, s),
+        };
+        let parts: Vec<&str> = s.split(':').collect();
+        if parts.len() > 3 {
+            return None;
+        }
+
+        let hours: i64 = parts[0].parse().ok()?;
+        let minutes: i64 = parts.get(1).map_or(Some(0), |m| m.parse().ok())?;
+        let seconds: f64 = parts.get(2).map_or(Some(0.0), |s| s.parse().ok())?;
+        let micros = hours * MICROS_PER_HOUR
+            + minutes * MICROS_PER_MINUTE
+            + (seconds * MICROS_PER_SECOND as f64).round() as i64;
+        Some(Interval {
+            months: 0,
+            days: 0,
+            micros: sign * micros,
+        })
+    }
+
+    pub fn checked_add(&self, other: &Interval) -> Option<Interval> {
+        Some(Interval {
+            months: self.months.checked_add(other.months)?,
+            days: self.days.checked_add(other.days)?,
+            micros: self.micros.checked_add(other.micros)?,
+        })
+    }
+
+    pub fn checked_mul(&self, factor: i64) -> Option<Interval> {
+        let factor = i32::try_from(factor).ok()?;
+        Some(Interval {
+            months: self.months.checked_mul(factor)?,
+            days: self.days.checked_mul(factor)?,
+            micros: self.micros.checked_mul(factor as i64)?,
+        })
+    }
+
+    pub fn checked_neg(&self) -> Option<Interval> {
+        self.checked_mul(-1)
+    }
+
+    // the approximate length used to order intervals, a month is 30 days
+    pub fn approximate_micros(&self) -> i128 {
+        (self.months as i128 * 30 + self.days as i128) * MICROS_PER_DAY as i128
+            + self.micros as i128
+    }
+}
+
+// formats as 1 year 2 mons 3 days 04:05:06
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = Vec::new();
+        let (years, months) = (self.months / 12, self.months % 12);
+        for (value, singular, plural) in [
+            (years, "year", "years"),
+            (months, "mon", "mons"),
+            (self.days, "day", "days"),
+        ] {
+            if value != 0 {
+                parts.push(format!(
+                    "{} {}",
+                    value,
+                    if value.abs() == 1 { singular } else { plural }
+                ));
+            }
+        }
+
+        if self.micros != 0 || parts.is_empty() {
+            let sign = if self.micros < 0 { "-" } else { "" };
+            let micros = self.micros.unsigned_abs();
+            let mut time = format!(
+                "{}{:02}:{:02}:{:02}",
+                sign,
+                micros / MICROS_PER_HOUR as u64,
+                micros % MICROS_PER_HOUR as u64 / MICROS_PER_MINUTE as u64,
+                micros % MICROS_PER_MINUTE as u64 / MICROS_PER_SECOND as u64
+            );
+            let fraction = micros % MICROS_PER_SECOND as u64;
+            if fraction != 0 {
+                time.push_str(format!(".{:06}", fraction).trim_end_matches('0'));
+            }
+            parts.push(time);
+        }
+
+        f.write_str(&parts.join(" "))
+    }
+}
@@ -0,0 +1,184 @@
+use std::cmp::Ordering;
+
+use parquet::data_type::{ByteArray, Decimal};
+use sqlparser::ast::BinaryOperator;
+
+use super::{coercion::decimal_unscaled, field::Field};
+
+// the unscaled values of decimals are computed as i128 so they have at most 38 digits
+pub const MAX_PRECISION: i32 = 38;
+// the digits a quotient has after the decimal point on top of the digits of its operands
+const DIVISION_EXTRA_SCALE: i32 = 6;
+
+// a decimal field, none if the value has more digits than the precision
+pub fn new(unscaled: i128, precision: i32, scale: i32) -> Option<Field> {
+    if !(0..=precision).contains(&scale) || digits(unscaled) > precision {
+        return None;
+    }
+
+    Some(Field::Decimal(from_unscaled(unscaled, precision, scale)))
+}
+
+pub fn from_unscaled(unscaled: i128, precision: i32, scale: i32) -> Decimal {
+    Decimal::from_bytes(
+        ByteArray::from(unscaled.to_be_bytes().to_vec()),
+        precision,
+        scale,
+    )
+}
+
+// an integer as a decimal without digits after the decimal point
+pub fn from_integer(value: i64, precision: i32) -> Field {
+    new(value as i128, precision.max(digits(value as i128)), 0).unwrap()
+}
+
+fn digits(unscaled: i128) -> i32 {
+    unscaled
+        .unsigned_abs()
+        .checked_ilog10()
+        .map_or(1, |d| d as i32 + 1)
+}
+
+// the unscaled value of a decimal with more digits after the decimal point
+fn rescale(unscaled: i128, from: i32, to: i32) -> Option<i128> {
+    unscaled.checked_mul(10i128.checked_pow((to - from) as u32)?)
+}
+
+// divides and rounds half away from zero
+pub fn divide_rounded(value: i128, divisor: i128) -> i128 {
+    let quotient = value / divisor;
+    let remainder = value % divisor;
+    if remainder.unsigned_abs() * 2 >= divisor.unsigned_abs() {
+        quotient + value.signum() * divisor.signum()
+    } else {
+        quotient
+    }
+}
+
+// parses a number such as -12.345 to its unscaled value with the scale, extra digits are rounded
+pub fn parse(s: &str, scale: i32) -> Option<i128> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (integer, fraction) = s.split_once('.').unwrap_or((s, ""));
+    if integer.is_empty() && fraction.is_empty()
+        || !integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let mut unscaled: i128 = 0;
+    for c in integer.chars() {
+        unscaled = unscaled
+            .checked_mul(10)?
+            .checked_add(c.to_digit(10)? as i128)?;
+    }
+    let mut digits = fraction.chars();
+    for _ in 0..scale {
+        let digit = digits.next().and_then(|c| c.to_digit(10)).unwrap_or(0);
+        unscaled = unscaled.checked_mul(10)?.checked_add(digit as i128)?;
+    }
+    if digits
+        .next()
+        .and_then(|c| c.to_digit(10))
+        .is_some_and(|d| d >= 5)
+    {
+        unscaled = unscaled.checked_add(1)?;
+    }
+
+    Some(if negative { -unscaled } else { unscaled })
+}
+
+// a decimal with the digits of a number such as -12.345
+pub fn from_str(s: &str) -> Option<Field> {
+    let scale = s
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len() as i32);
+    let unscaled = parse(s, scale)?;
+    new(unscaled, digits(unscaled).max(scale), scale)
+}
+
+// the digits of a decimal with as many after the decimal point as its scale and no point when the scale is zero
+pub fn format(decimal: &Decimal) -> String {
+    let unscaled = decimal_unscaled(decimal);
+    let scale = decimal.scale() as usize;
+    let digits = format!("{:0>width$}", unscaled.unsigned_abs(), width = scale + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - scale);
+    let sign = if unscaled < 0 { "-" } else { "" };
+    if fraction.is_empty() {
+        format!("{}{}", sign, integer)
+    } else {
+        format!("{}{}.{}", sign, integer, fraction)
+    }
+}
+
+pub fn is_zero(decimal: &Decimal) -> bool {
+    decimal_unscaled(decimal) == 0
+}
+
+pub fn negate(decimal: &Decimal) -> Option<Field> {
+    new(
+        decimal_unscaled(decimal).checked_neg()?,
+        decimal.precision(),
+        decimal.scale(),
+    )
+}
+
+pub fn compare(left: &Decimal, right: &Decimal) -> Ordering {
+    let (left_unscaled, right_unscaled) = (decimal_unscaled(left), decimal_unscaled(right));
+    let scale = left.scale().max(right.scale());
+
+    match (
+        rescale(left_unscaled, left.scale(), scale),
+        rescale(right_unscaled, right.scale(), scale),
+    ) {
+        (Some(l), Some(r)) => l.cmp(&r),
+        // a value that cannot be aligned has more integer digits than the other so the integer parts decide
+        _ => {
+            let l = left_unscaled as f64 / 10f64.powi(left.scale());
+            let r = right_unscaled as f64 / 10f64.powi(right.scale());
+            l.partial_cmp(&r).unwrap_or(Ordering::Equal)
+        }
+    }
+}
+
+// the exact result of arithmetic with two decimals, none if it has more digits than a decimal can hold
+pub fn arithmetic(left: &Decimal, op: &BinaryOperator, right: &Decimal) -> Option<Field> {
+    let (left_unscaled, right_unscaled) = (decimal_unscaled(left), decimal_unscaled(right));
+    let (left_scale, right_scale) = (left.scale(), right.scale());
+    let scale = left_scale.max(right_scale);
+    // the digits before the decimal point of the wider operand
+    let integer_digits = (left.precision() - left_scale).max(right.precision() - right_scale);
+
+    match op {
+        BinaryOperator::Plus | BinaryOperator::Minus | BinaryOperator::Modulo => {
+            let l = rescale(left_unscaled, left_scale, scale)?;
+            let r = rescale(right_unscaled, right_scale, scale)?;
+            let (unscaled, precision) = match op {
+                BinaryOperator::Plus => (l.checked_add(r)?, integer_digits + scale + 1),
+                BinaryOperator::Minus => (l.checked_sub(r)?, integer_digits + scale + 1),
+                _ => (l.checked_rem(r)?, integer_digits + scale),
+            };
+            new(unscaled, precision.min(MAX_PRECISION), scale)
+        }
+        BinaryOperator::Multiply => new(
+            left_unscaled.checked_mul(right_unscaled)?,
+            (left.precision() + right.precision()).min(MAX_PRECISION),
+            left_scale + right_scale,
+        ),
+        BinaryOperator::Divide => {
+            let scale = (scale + DIVISION_EXTRA_SCALE).min(MAX_PRECISION);
+            let numerator = rescale(left_unscaled, left_scale, scale + right_scale)?;
+            new(
+                divide_rounded(numerator, right_unscaled),
+                MAX_PRECISION,
+                scale,
+            )
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt;
+
+use parquet::{data_type::ByteArray, data_type::Decimal, record::Field as ParquetField};
+
+use super::datetime::Interval;
+
+// a single value of a row, the values read from parquet files plus the values that only come from queries such as intervals
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Field {
+    #[default]
+    Null,
+    Bool(bool),
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    UByte(u8),
+    UShort(u16),
+    UInt(u32),
+    ULong(u64),
+    Float(f32),
+    Double(f64),
+    Decimal(Decimal),
+    Str(String),
+    Bytes(ByteArray),
+    // days since the epoch
+    Date(i32),
+    TimestampMillis(i64),
+    TimestampMicros(i64),
+    Interval(Interval),
+}
+
+impl From<ParquetField> for Field {
+    fn from(field: ParquetField) -> Field {
+        match field {
+            ParquetField::Null => Field::Null,
+            ParquetField::Bool(b) => Field::Bool(b),
+            ParquetField::Byte(v) => Field::Byte(v),
+            ParquetField::Short(v) => Field::Short(v),
+            ParquetField::Int(v) => Field::Int(v),
+            ParquetField::Long(v) => Field::Long(v),
+            ParquetField::UByte(v) => Field::UByte(v),
+            ParquetField::UShort(v) => Field::UShort(v),
+            ParquetField::UInt(v) => Field::UInt(v),
+            ParquetField::ULong(v) => Field::ULong(v),
+            ParquetField::Float(v) => Field::Float(v),
+            ParquetField::Double(v) => Field::Double(v),
+            ParquetField::Decimal(d) => Field::Decimal(d),
+            ParquetField::Str(s) => Field::Str(s),
+            ParquetField::Bytes(b) => Field::Bytes(b),
+            ParquetField::Date(d) => Field::Date(d),
+            ParquetField::TimestampMillis(t) => Field::TimestampMillis(t),
+            ParquetField::TimestampMicros(t) => Field::TimestampMicros(t),
+            // only the flat columns of a file are read so there are no nested fields
+            ParquetField::Group(_)
+            | ParquetField::ListInternal(_)
+            | ParquetField::MapInternal(_) => Field::Null,
+        }
+    }
+}
+
+// values are written the way parquet writes its fields, strings are quoted
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Field::Null => write!(f, "null"),
+            Field::Bool(value) => write!(f, "{value}"),
+            Field::Byte(value) => write!(f, "{value}"),
+            Field::Short(value) => write!(f, "{value}"),
+            Field::Int(value) => write!(f, "{value}"),
+            Field::Long(value) => write!(f, "{value}"),
+            Field::UByte(value) => write!(f, "{value}"),
+            Field::UShort(value) => write!(f, "{value}"),
+            Field::UInt(value) => write!(f, "{value}"),
+            Field::ULong(value) => write!(f, "{value}"),
+            Field::Float(value) => ParquetField::Float(*value).fmt(f),
+            Field::Double(value) => ParquetField::Double(*value).fmt(f),
+            Field::Decimal(value) => ParquetField::Decimal(value.clone()).fmt(f),
+            Field::Str(value) => write!(f, "\"{value}\""),
+            Field::Bytes(value) => write!(f, "{:?}", value.data()),
+            Field::Date(value) => ParquetField::Date(*value).fmt(f),
+            Field::TimestampMillis(value) => ParquetField::TimestampMillis(*value).fmt(f),
+            Field::TimestampMicros(value) => ParquetField::TimestampMicros(*value).fmt(f),
+            Field::Interval(value) => write!(f, "{value}"),
+        }
+    }
+}
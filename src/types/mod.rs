@@ -1,15 +1,20 @@
 use std::fmt;
 
-use parquet::record::Field;
 use regex::Regex;
 use sqlparser::ast::SelectItem;
 use tabled::{builder::Builder, settings::Style};
 
 use crate::planner::OutputSchema;
 
-use self::error::Error;
+use self::{data_type::DataType, error::Error, field::Field, vector::Vector};
 
+pub mod coercion;
+pub mod data_type;
+pub mod datetime;
+pub mod decimal;
 pub mod error;
+pub mod field;
+pub mod vector;
 
 #[derive(Debug, Clone)]
 pub struct TupleValue {
@@ -18,7 +23,14 @@ pub struct TupleValue {
 
 impl fmt::Display for TupleValue {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        fmt.write_str(self.value.to_string().as_str())?;
+        match &self.value {
+            Field::Date(_)
+            | Field::TimestampMillis(_)
+            | Field::TimestampMicros(_)
+            | Field::Decimal(_)
+            | Field::Interval(_) => fmt.write_str(String::from(self).as_str())?,
+            value => fmt.write_str(value.to_string().as_str())?,
+        }
         Ok(())
     }
 }
@@ -27,16 +39,88 @@ impl From<&TupleValue> for std::string::String {
     fn from(val: &TupleValue) -> Self {
         match &val.value {
             Field::Bool(b) => b.to_string(),
+            Field::Byte(b) => b.to_string(),
+            Field::Short(s) => s.to_string(),
             Field::Int(i) => i.to_string(),
+            Field::Long(l) => l.to_string(),
             Field::Float(f) => f.to_string(),
             Field::Double(d) => d.to_string(),
+            Field::Decimal(d) => decimal::format(d),
             Field::Str(s) => s.to_string(),
+            Field::Date(d) => datetime::format_date(*d),
+            Field::TimestampMillis(t) => datetime::format_timestamp(t * 1000),
+            Field::TimestampMicros(t) => datetime::format_timestamp(*t),
+            Field::Interval(interval) => interval.to_string(),
             Field::Null => String::from("NULL"),
             _ => String::from("-"),
         }
     }
 }
 
+// a value that can be hashed and compared for equality, used as the key of hash tables
+// integers of any width are the same key and so are timestamps in millis and micros
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum KeyValue {
+    Null,
+    Bool(bool),
+    Integer(i128),
+    // the bits of the double, with a single zero and a single NaN
+    Double(u64),
+    // the unscaled value and the scale without trailing zeros
+    Decimal(i128, i32),
+    Str(String),
+    Date(i32),
+    Timestamp(i64),
+    Bytes(Vec<u8>),
+    // the approximate length intervals are compared by
+    Interval(i128),
+}
+
+impl From<&Field> for KeyValue {
+    fn from(field: &Field) -> KeyValue {
+        match field {
+            Field::Null => KeyValue::Null,
+            Field::Bool(b) => KeyValue::Bool(*b),
+            Field::Byte(v) => KeyValue::Integer(*v as i128),
+            Field::Short(v) => KeyValue::Integer(*v as i128),
+            Field::Int(v) => KeyValue::Integer(*v as i128),
+            Field::Long(v) => KeyValue::Integer(*v as i128),
+            Field::UByte(v) => KeyValue::Integer(*v as i128),
+            Field::UShort(v) => KeyValue::Integer(*v as i128),
+            Field::UInt(v) => KeyValue::Integer(*v as i128),
+            Field::ULong(v) => KeyValue::Integer(*v as i128),
+            Field::Float(v) => KeyValue::from_double(*v as f64),
+            Field::Double(v) => KeyValue::from_double(*v),
+            Field::Decimal(d) => {
+                let (mut unscaled, mut scale) = (coercion::decimal_unscaled(d), d.scale());
+                while scale > 0 && unscaled % 10 == 0 {
+                    unscaled /= 10;
+                    scale -= 1;
+                }
+                KeyValue::Decimal(unscaled, scale)
+            }
+            Field::Str(s) => KeyValue::Str(s.clone()),
+            Field::Date(d) => KeyValue::Date(*d),
+            Field::TimestampMillis(t) => KeyValue::Timestamp(t.saturating_mul(1000)),
+            Field::TimestampMicros(t) => KeyValue::Timestamp(*t),
+            Field::Bytes(b) => KeyValue::Bytes(b.data().to_vec()),
+            Field::Interval(interval) => KeyValue::Interval(interval.approximate_micros()),
+        }
+    }
+}
+
+impl KeyValue {
+    fn from_double(value: f64) -> KeyValue {
+        if value == 0.0 {
+            KeyValue::Double(0f64.to_bits())
+        } else if value.is_nan() {
+            KeyValue::Double(f64::NAN.to_bits())
+        } else {
+            KeyValue::Double(value.to_bits())
+        }
+    }
+}
+
 pub type Row = Vec<TupleValue>;
 
 #[derive(Debug, Clone)]
@@ -44,37 +128,63 @@ pub struct Column {
     pub label: Option<String>,
     pub table: Option<String>,
     pub column_name: String,
+    pub data_type: DataType,
+    pub nullable: bool,
+    // hidden columns are left out of wildcards and can only be referenced by their qualified name
+    // this is used for the columns that are merged into a single column by JOIN ... USING
+    pub hidden: bool,
 }
 
 impl Column {
-    pub fn new(label: Option<String>, name: String) -> Result<Column, Error> {
+    pub fn new(
+        label: Option<String>,
+        name: String,
+        data_type: DataType,
+        nullable: bool,
+    ) -> Result<Column, Error> {
         let (column_name, table) = parse_identifer(&name)?;
 
         Ok(Column {
             label,
             table,
             column_name,
+            data_type,
+            nullable,
+            hidden: false,
         })
     }
 
-    #[allow(dead_code)]
-    pub fn as_select_item(&self) -> SelectItem {
+    // the name the column is referenced by outside of its query, the alias or the expression when it is not a plain column
+    pub fn output_name(&self) -> String {
+        let qualified_name = match &self.table {
+            Some(table) => format!("{}.{}", table, self.column_name),
+            None => self.column_name.clone(),
+        };
+        match &self.label {
+            Some(label) if *label != qualified_name => label.clone(),
+            _ => self.column_name.clone(),
+        }
+    }
+
+    pub fn as_expr(&self) -> sqlparser::ast::Expr {
         let mut ident_name = self.column_name.clone();
         if let Some(table) = &self.table {
             ident_name = format!("{}.{}", table, self.column_name);
         }
 
-        let ident = sqlparser::ast::Ident::new(ident_name);
+        sqlparser::ast::Expr::Identifier(sqlparser::ast::Ident::new(ident_name))
+    }
 
+    pub fn as_select_item(&self) -> SelectItem {
         if let Some(label) = &self.label {
             let alias = sqlparser::ast::Ident::new(label);
 
             SelectItem::ExprWithAlias {
-                expr: sqlparser::ast::Expr::Identifier(ident),
+                expr: self.as_expr(),
                 alias,
             }
         } else {
-            SelectItem::UnnamedExpr(sqlparser::ast::Expr::Identifier(ident))
+            SelectItem::UnnamedExpr(self.as_expr())
         }
     }
 }
@@ -88,8 +198,8 @@ pub fn parse_identifer(name: &str) -> Result<(String, Option<String>), Error> {
             let re = Regex::new(r"(?<table>'.+')\.(?<column>.+)").unwrap();
 
             let Some(caps) = re.captures(name) else {
-                    return Err(Error::Planner(format!("Invalid field name: {}", name)));
-                };
+                return Err(Error::Planner(format!("Invalid field name: {}", name)));
+            };
 
             if caps.name("table").is_none() || caps.name("column").is_none() {
                 return Err(Error::Planner(format!("Invalid field name: {}", name)));
@@ -117,28 +227,103 @@ pub fn parse_identifer(name: &str) -> Result<(String, Option<String>), Error> {
     ))
 }
 
+// a set of rows stored as one vector per column
 #[derive(Default, Clone)]
 pub struct Chunk {
-    data_chunks: Vec<Row>,
+    columns: Vec<Vector>,
+    // the number of rows is kept separately since a chunk can have rows without any columns
+    len: usize,
 }
 
 impl Chunk {
     pub fn new() -> Chunk {
         Chunk {
-            data_chunks: Vec::new(),
+            columns: Vec::new(),
+            len: 0,
         }
     }
+    pub fn from_columns(columns: Vec<Vector>, len: usize) -> Chunk {
+        assert!(columns.iter().all(|column| column.len() == len));
+        Chunk { columns, len }
+    }
     pub fn add_row(&mut self, row: Row) {
-        self.data_chunks.push(row);
+        if self.len == 0 && self.columns.is_empty() {
+            self.columns = vec![Vector::default(); row.len()];
+        }
+        for (column, value) in self.columns.iter_mut().zip(row) {
+            column.push(value.value);
+        }
+        self.len += 1;
+    }
+    pub fn append(&mut self, chunk: &Chunk) {
+        if self.len == 0 && self.columns.is_empty() {
+            *self = chunk.clone();
+            return;
+        }
+        for (column, other) in self.columns.iter_mut().zip(chunk.columns.iter()) {
+            column.append(other);
+        }
+        self.len += chunk.len;
     }
     pub fn size(&self) -> usize {
-        self.data_chunks.len()
+        self.len
     }
     pub fn is_empty(&self) -> bool {
-        self.data_chunks.is_empty()
+        self.len == 0
+    }
+    pub fn columns(&self) -> &[Vector] {
+        &self.columns
     }
-    pub fn get_rows(&self) -> &Vec<Row> {
-        &self.data_chunks
+    pub fn column(&self, i: usize) -> &Vector {
+        &self.columns[i]
+    }
+    pub fn get_row(&self, i: usize) -> Row {
+        self.columns
+            .iter()
+            .map(|column| TupleValue {
+                value: column.get(i),
+            })
+            .collect()
+    }
+    pub fn get_rows(&self) -> Vec<Row> {
+        (0..self.len).map(|i| self.get_row(i)).collect()
+    }
+    // keeps the rows where the mask is true
+    pub fn filter(&self, mask: &[bool]) -> Chunk {
+        Chunk {
+            columns: self.columns.iter().map(|c| c.filter(mask)).collect(),
+            len: mask.iter().filter(|keep| **keep).count(),
+        }
+    }
+    // builds a chunk with the rows at the indexes
+    pub fn take(&self, indexes: &[usize]) -> Chunk {
+        Chunk {
+            columns: self.columns.iter().map(|c| c.take(indexes)).collect(),
+            len: indexes.len(),
+        }
+    }
+    // builds a chunk with the rows at the indexes and rows of nulls where there is no index
+    pub fn take_optional(&self, indexes: &[Option<usize>]) -> Chunk {
+        Chunk {
+            columns: self
+                .columns
+                .iter()
+                .map(|c| c.take_optional(indexes))
+                .collect(),
+            len: indexes.len(),
+        }
+    }
+    // places the columns of the other chunk after the columns of this chunk
+    pub fn concat_columns(mut self, other: Chunk) -> Chunk {
+        assert!(self.len == other.len);
+        self.columns.extend(other.columns);
+        self
+    }
+    pub fn slice(&self, start: usize, end: usize) -> Chunk {
+        Chunk {
+            columns: self.columns.iter().map(|c| c.slice(start, end)).collect(),
+            len: end - start,
+        }
     }
 }
 
@@ -165,7 +350,7 @@ impl fmt::Display for ResultSet {
         builder.set_header(headers);
 
         for chunk in self.data_chunks.iter() {
-            for row in chunk.data_chunks.iter() {
+            for row in chunk.get_rows().iter() {
                 builder.push_record(row);
             }
         }
@@ -0,0 +1,522 @@
+use super::{
+    coercion::decimal_unscaled, data_type::DataType, datetime::Interval, decimal, field::Field,
+};
+
+// a bitmap with one bit per value, used to mark which values of a vector are not null
+#[derive(Debug, Clone, Default)]
+pub struct Bitmap {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Bitmap {
+    pub fn new(len: usize, value: bool) -> Bitmap {
+        let word = if value { u64::MAX } else { 0 };
+        let mut bitmap = Bitmap {
+            words: vec![word; len.div_ceil(64)],
+            len,
+        };
+        bitmap.clear_unused_bits();
+        bitmap
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        self.words[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    pub fn set(&mut self, i: usize, value: bool) {
+        if value {
+            self.words[i / 64] |= 1 << (i % 64);
+        } else {
+            self.words[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    pub fn push(&mut self, value: bool) {
+        if self.len.is_multiple_of(64) {
+            self.words.push(0);
+        }
+        self.len += 1;
+        self.set(self.len - 1, value);
+    }
+
+    pub fn count_set(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn all_set(&self) -> bool {
+        self.count_set() == self.len
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|i| self.get(i))
+    }
+
+    // the bits that are set in both bitmaps
+    pub fn and(&self, other: &Bitmap) -> Bitmap {
+        assert!(self.len == other.len);
+        Bitmap {
+            words: self
+                .words
+                .iter()
+                .zip(other.words.iter())
+                .map(|(l, r)| l & r)
+                .collect(),
+            len: self.len,
+        }
+    }
+
+    // the bits past the length are kept unset so counting the set bits stays correct
+    fn clear_unused_bits(&mut self) {
+        if !self.len.is_multiple_of(64) {
+            let last = self.words.len() - 1;
+            self.words[last] &= (1 << (self.len % 64)) - 1;
+        }
+    }
+}
+
+impl FromIterator<bool> for Bitmap {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Bitmap {
+        let mut bitmap = Bitmap::default();
+        for value in iter {
+            bitmap.push(value);
+        }
+        bitmap
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum VectorData {
+    Bool(Vec<bool>),
+    Byte(Vec<i8>),
+    Short(Vec<i16>),
+    Int(Vec<i32>),
+    Long(Vec<i64>),
+    Float(Vec<f32>),
+    Double(Vec<f64>),
+    // the unscaled values of decimals that all have the precision and scale after them
+    Decimal(Vec<i128>, i32, i32),
+    Str(Vec<String>),
+    // days since the epoch
+    Date(Vec<i32>),
+    // microseconds since the epoch, timestamps in milliseconds are converted when they are added
+    Timestamp(Vec<i64>),
+    Interval(Vec<Interval>),
+    // values of the types that do not have their own vector or vectors that mix types
+    Other(Vec<Field>),
+}
+
+// applies the same expression to the values of any kind of vector data and wraps the result in the same kind
+macro_rules! map_data {
+    ($data:expr, $values:ident => $body:expr) => {
+        match $data {
+            VectorData::Bool($values) => VectorData::Bool($body),
+            VectorData::Byte($values) => VectorData::Byte($body),
+            VectorData::Short($values) => VectorData::Short($body),
+            VectorData::Int($values) => VectorData::Int($body),
+            VectorData::Long($values) => VectorData::Long($body),
+            VectorData::Float($values) => VectorData::Float($body),
+            VectorData::Double($values) => VectorData::Double($body),
+            VectorData::Decimal($values, precision, scale) => {
+                VectorData::Decimal($body, *precision, *scale)
+            }
+            VectorData::Str($values) => VectorData::Str($body),
+            VectorData::Date($values) => VectorData::Date($body),
+            VectorData::Timestamp($values) => VectorData::Timestamp($body),
+            VectorData::Interval($values) => VectorData::Interval($body),
+            VectorData::Other($values) => VectorData::Other($body),
+        }
+    };
+}
+
+impl VectorData {
+    pub fn len(&self) -> usize {
+        match self {
+            VectorData::Bool(values) => values.len(),
+            VectorData::Byte(values) => values.len(),
+            VectorData::Short(values) => values.len(),
+            VectorData::Int(values) => values.len(),
+            VectorData::Long(values) => values.len(),
+            VectorData::Float(values) => values.len(),
+            VectorData::Double(values) => values.len(),
+            VectorData::Decimal(values, _, _) => values.len(),
+            VectorData::Str(values) => values.len(),
+            VectorData::Date(values) => values.len(),
+            VectorData::Timestamp(values) => values.len(),
+            VectorData::Interval(values) => values.len(),
+            VectorData::Other(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn has_typed_vector(field: &Field) -> bool {
+        matches!(
+            field,
+            Field::Bool(_)
+                | Field::Byte(_)
+                | Field::Short(_)
+                | Field::Int(_)
+                | Field::Long(_)
+                | Field::Float(_)
+                | Field::Double(_)
+                | Field::Decimal(_)
+                | Field::Str(_)
+                | Field::Date(_)
+                | Field::TimestampMillis(_)
+                | Field::TimestampMicros(_)
+                | Field::Interval(_)
+        )
+    }
+
+    // vector data of the type that stores the field with len default values
+    fn for_field(field: &Field, len: usize) -> VectorData {
+        match field {
+            Field::Decimal(d) => VectorData::Decimal(vec![0; len], d.precision(), d.scale()),
+            field => VectorData::for_type(DataType::of_field(field)).with_defaults(len),
+        }
+    }
+
+    // empty vector data for values of the type, decimals are stored as fields until the precision and scale are known
+    pub fn for_type(data_type: DataType) -> VectorData {
+        match data_type {
+            DataType::Boolean => VectorData::Bool(Vec::new()),
+            DataType::Byte => VectorData::Byte(Vec::new()),
+            DataType::Short => VectorData::Short(Vec::new()),
+            DataType::Int => VectorData::Int(Vec::new()),
+            DataType::Long => VectorData::Long(Vec::new()),
+            DataType::Float => VectorData::Float(Vec::new()),
+            DataType::Double => VectorData::Double(Vec::new()),
+            DataType::String => VectorData::Str(Vec::new()),
+            DataType::Date => VectorData::Date(Vec::new()),
+            DataType::Timestamp => VectorData::Timestamp(Vec::new()),
+            DataType::Interval => VectorData::Interval(Vec::new()),
+            _ => VectorData::Other(Vec::new()),
+        }
+    }
+
+    // vector data of the same kind as this one with len default values
+    fn with_defaults(&self, len: usize) -> VectorData {
+        let mut data = map_data!(self, _values => Vec::with_capacity(len));
+        for _ in 0..len {
+            data.push_default();
+        }
+        data
+    }
+
+    fn same_kind(&self, other: &VectorData) -> bool {
+        match (self, other) {
+            (VectorData::Decimal(_, lp, ls), VectorData::Decimal(_, rp, rs)) => {
+                lp == rp && ls == rs
+            }
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+
+    fn push_default(&mut self) {
+        match self {
+            VectorData::Bool(values) => values.push(false),
+            VectorData::Byte(values) => values.push(0),
+            VectorData::Short(values) => values.push(0),
+            VectorData::Int(values) => values.push(0),
+            VectorData::Long(values) => values.push(0),
+            VectorData::Float(values) => values.push(0.0),
+            VectorData::Double(values) => values.push(0.0),
+            VectorData::Decimal(values, _, _) => values.push(0),
+            VectorData::Str(values) => values.push(String::new()),
+            VectorData::Date(values) => values.push(0),
+            VectorData::Timestamp(values) => values.push(0),
+            VectorData::Interval(values) => values.push(Interval::default()),
+            VectorData::Other(values) => values.push(Field::Null),
+        }
+    }
+}
+
+// a column of values stored in a typed vector along with a bitmap of the values that are not null
+#[derive(Debug, Clone)]
+pub struct Vector {
+    data: VectorData,
+    validity: Bitmap,
+    // the number of values that are not null, kept so pushing a value does not count the bitmap again
+    valid_count: usize,
+}
+
+impl Default for Vector {
+    fn default() -> Vector {
+        Vector::new(VectorData::Other(Vec::new()), Bitmap::default())
+    }
+}
+
+impl Vector {
+    pub fn new(data: VectorData, validity: Bitmap) -> Vector {
+        assert!(data.len() == validity.len());
+        Vector {
+            data,
+            valid_count: validity.count_set(),
+            validity,
+        }
+    }
+
+    // a vector with the same value repeated len times
+    pub fn from_field(field: &Field, len: usize) -> Vector {
+        let data = match field {
+            Field::Bool(b) => VectorData::Bool(vec![*b; len]),
+            Field::Byte(b) => VectorData::Byte(vec![*b; len]),
+            Field::Short(s) => VectorData::Short(vec![*s; len]),
+            Field::Int(i) => VectorData::Int(vec![*i; len]),
+            Field::Long(l) => VectorData::Long(vec![*l; len]),
+            Field::Float(f) => VectorData::Float(vec![*f; len]),
+            Field::Double(d) => VectorData::Double(vec![*d; len]),
+            Field::Decimal(d) => {
+                VectorData::Decimal(vec![decimal_unscaled(d); len], d.precision(), d.scale())
+            }
+            Field::Str(s) => VectorData::Str(vec![s.clone(); len]),
+            Field::Date(d) => VectorData::Date(vec![*d; len]),
+            Field::TimestampMillis(t) => VectorData::Timestamp(vec![t.saturating_mul(1000); len]),
+            Field::TimestampMicros(t) => VectorData::Timestamp(vec![*t; len]),
+            Field::Interval(i) => VectorData::Interval(vec![*i; len]),
+            _ => VectorData::Other(vec![field.clone(); len]),
+        };
+
+        Vector::new(data, Bitmap::new(len, *field != Field::Null))
+    }
+
+    pub fn from_fields(fields: Vec<Field>) -> Vector {
+        let mut vector = Vector::default();
+        for field in fields {
+            vector.push(field);
+        }
+        vector
+    }
+
+    // a vector of the values that are expected to have the type, a value of another type is stored as a field
+    pub fn from_fields_of_type(fields: Vec<Field>, data_type: DataType) -> Vector {
+        let mut vector = Vector::new(VectorData::for_type(data_type), Bitmap::default());
+        for field in fields {
+            vector.push(field);
+        }
+        vector
+    }
+
+    // the same values with another bitmap of the values that are not null
+    pub fn with_validity(self, validity: Bitmap) -> Vector {
+        Vector::new(self.data, validity)
+    }
+
+    pub fn data(&self) -> &VectorData {
+        &self.data
+    }
+
+    pub fn validity(&self) -> &Bitmap {
+        &self.validity
+    }
+
+    pub fn len(&self) -> usize {
+        self.validity.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.validity.is_empty()
+    }
+
+    pub fn is_valid(&self, i: usize) -> bool {
+        self.validity.get(i)
+    }
+
+    pub fn get(&self, i: usize) -> Field {
+        if !self.is_valid(i) {
+            return Field::Null;
+        }
+
+        match &self.data {
+            VectorData::Bool(values) => Field::Bool(values[i]),
+            VectorData::Byte(values) => Field::Byte(values[i]),
+            VectorData::Short(values) => Field::Short(values[i]),
+            VectorData::Int(values) => Field::Int(values[i]),
+            VectorData::Long(values) => Field::Long(values[i]),
+            VectorData::Float(values) => Field::Float(values[i]),
+            VectorData::Double(values) => Field::Double(values[i]),
+            VectorData::Decimal(values, precision, scale) => {
+                Field::Decimal(decimal::from_unscaled(values[i], *precision, *scale))
+            }
+            VectorData::Str(values) => Field::Str(values[i].clone()),
+            VectorData::Date(values) => Field::Date(values[i]),
+            VectorData::Timestamp(values) => Field::TimestampMicros(values[i]),
+            VectorData::Interval(values) => Field::Interval(values[i]),
+            VectorData::Other(values) => values[i].clone(),
+        }
+    }
+
+    // the string at the index, none when the value is null or not a string
+    pub fn get_str(&self, i: usize) -> Option<&str> {
+        if !self.is_valid(i) {
+            return None;
+        }
+
+        match &self.data {
+            VectorData::Str(values) => Some(&values[i]),
+            VectorData::Other(values) => match &values[i] {
+                Field::Str(s) => Some(s),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn to_fields(&self) -> Vec<Field> {
+        (0..self.len()).map(|i| self.get(i)).collect()
+    }
+
+    pub fn push(&mut self, field: Field) {
+        match (&mut self.data, field) {
+            (data, Field::Null) => {
+                data.push_default();
+                self.validity.push(false);
+                return;
+            }
+            (VectorData::Bool(values), Field::Bool(b)) => values.push(b),
+            (VectorData::Byte(values), Field::Byte(b)) => values.push(b),
+            (VectorData::Short(values), Field::Short(s)) => values.push(s),
+            (VectorData::Int(values), Field::Int(i)) => values.push(i),
+            (VectorData::Long(values), Field::Long(l)) => values.push(l),
+            (VectorData::Float(values), Field::Float(f)) => values.push(f),
+            (VectorData::Double(values), Field::Double(d)) => values.push(d),
+            (VectorData::Decimal(values, precision, scale), Field::Decimal(d))
+                if d.precision() == *precision && d.scale() == *scale =>
+            {
+                values.push(decimal_unscaled(&d))
+            }
+            (VectorData::Str(values), Field::Str(s)) => values.push(s),
+            (VectorData::Date(values), Field::Date(d)) => values.push(d),
+            (VectorData::Timestamp(values), Field::TimestampMillis(t)) => {
+                values.push(t.saturating_mul(1000))
+            }
+            (VectorData::Timestamp(values), Field::TimestampMicros(t)) => values.push(t),
+            (VectorData::Interval(values), Field::Interval(i)) => values.push(i),
+            (VectorData::Other(values), field)
+                if self.valid_count > 0 || !VectorData::has_typed_vector(&field) =>
+            {
+                values.push(field)
+            }
+            (_, field) => {
+                if self.valid_count == 0 {
+                    // a vector with only nulls takes the type of the first value
+                    self.data = VectorData::for_field(&field, self.len());
+                } else {
+                    // the values have different types so we fall back to storing fields
+                    self.data = VectorData::Other(self.to_fields());
+                }
+                self.push(field);
+                return;
+            }
+        }
+        self.validity.push(true);
+        self.valid_count += 1;
+    }
+
+    pub fn append(&mut self, other: &Vector) {
+        if !self.data.same_kind(&other.data) {
+            if other.valid_count == 0 {
+                // the nulls are stored as default values of the type of this vector
+                let nulls = self.data.with_defaults(other.len());
+                self.extend_data(&nulls);
+            } else if self.valid_count == 0 {
+                // a vector with only nulls takes the type of the values appended to it
+                self.data = other.data.with_defaults(self.len());
+                self.extend_data(&other.data);
+            } else {
+                // the values have different types so we fall back to storing fields
+                let mut fields = self.to_fields();
+                fields.extend(other.to_fields());
+                self.data = VectorData::Other(fields);
+            }
+        } else {
+            self.extend_data(&other.data);
+        }
+
+        for valid in other.validity.iter() {
+            self.validity.push(valid);
+        }
+        self.valid_count += other.valid_count;
+    }
+
+    // adds the values of data of the same kind to the end of this vector without changing the validity
+    fn extend_data(&mut self, other: &VectorData) {
+        match (&mut self.data, other) {
+            (VectorData::Bool(l), VectorData::Bool(r)) => l.extend_from_slice(r),
+            (VectorData::Byte(l), VectorData::Byte(r)) => l.extend_from_slice(r),
+            (VectorData::Short(l), VectorData::Short(r)) => l.extend_from_slice(r),
+            (VectorData::Int(l), VectorData::Int(r)) => l.extend_from_slice(r),
+            (VectorData::Long(l), VectorData::Long(r)) => l.extend_from_slice(r),
+            (VectorData::Float(l), VectorData::Float(r)) => l.extend_from_slice(r),
+            (VectorData::Double(l), VectorData::Double(r)) => l.extend_from_slice(r),
+            (VectorData::Decimal(l, _, _), VectorData::Decimal(r, _, _)) => l.extend_from_slice(r),
+            (VectorData::Str(l), VectorData::Str(r)) => l.extend_from_slice(r),
+            (VectorData::Date(l), VectorData::Date(r)) => l.extend_from_slice(r),
+            (VectorData::Timestamp(l), VectorData::Timestamp(r)) => l.extend_from_slice(r),
+            (VectorData::Interval(l), VectorData::Interval(r)) => l.extend_from_slice(r),
+            (VectorData::Other(l), VectorData::Other(r)) => l.extend_from_slice(r),
+            _ => unreachable!(),
+        }
+    }
+
+    // keeps the values where the mask is true
+    pub fn filter(&self, mask: &[bool]) -> Vector {
+        let data = map_data!(&self.data, values => values
+            .iter()
+            .zip(mask)
+            .filter(|(_, keep)| **keep)
+            .map(|(value, _)| value)
+            .cloned()
+            .collect());
+        let validity = self
+            .validity
+            .iter()
+            .zip(mask)
+            .filter(|(_, keep)| **keep)
+            .map(|(valid, _)| valid)
+            .collect();
+
+        Vector::new(data, validity)
+    }
+
+    // builds a vector with the values at the indexes
+    pub fn take(&self, indexes: &[usize]) -> Vector {
+        let data =
+            map_data!(&self.data, values => indexes.iter().map(|i| &values[*i]).cloned().collect());
+        let validity = indexes.iter().map(|i| self.validity.get(*i)).collect();
+
+        Vector::new(data, validity)
+    }
+
+    // builds a vector with the values at the indexes and nulls where there is no index
+    pub fn take_optional(&self, indexes: &[Option<usize>]) -> Vector {
+        let data = map_data!(&self.data, values => indexes
+            .iter()
+            .map(|i| i.map(|i| &values[i]).cloned().unwrap_or_default())
+            .collect());
+        let validity = indexes
+            .iter()
+            .map(|i| i.is_some_and(|i| self.validity.get(i)))
+            .collect();
+
+        Vector::new(data, validity)
+    }
+
+    pub fn slice(&self, start: usize, end: usize) -> Vector {
+        let data = map_data!(&self.data, values => values[start..end].to_vec());
+        let validity = (start..end).map(|i| self.validity.get(i)).collect();
+
+        Vector::new(data, validity)
+    }
+}
@@ -1,4 +1,7 @@
-use sqlengine::{database::Database, types::error::Error};
+use sqlengine::{
+    database::Database,
+    types::{data_type::DataType, error::Error},
+};
 use sqllogictest::{self, DBOutput, DefaultColumnType};
 
 pub struct DatabaseTestHelper(pub Database);
@@ -8,14 +11,28 @@ impl sqllogictest::DB for DatabaseTestHelper {
     type ColumnType = DefaultColumnType;
     fn run(&mut self, sql: &str) -> Result<sqllogictest::DBOutput<Self::ColumnType>, Self::Error> {
         let result_set = self.0.execute(sql)?;
-        let types = vec![DefaultColumnType::Any; result_set.output_schema.columns.len()];
+        let types = result_set
+            .output_schema
+            .columns
+            .iter()
+            .map(|column| match column.data_type {
+                DataType::Byte | DataType::Short | DataType::Int | DataType::Long => {
+                    DefaultColumnType::Integer
+                }
+                DataType::Float | DataType::Double | DataType::Decimal => {
+                    DefaultColumnType::FloatingPoint
+                }
+                DataType::String => DefaultColumnType::Text,
+                _ => DefaultColumnType::Any,
+            })
+            .collect();
         let rows = result_set
             .data_chunks
             .iter()
             .flat_map(|chunk| {
                 chunk
                     .get_rows()
-                    .iter()
+                    .into_iter()
                     .map(|row| row.iter().map(|cell| cell.to_string()).collect())
             })
             .collect();
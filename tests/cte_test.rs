@@ -0,0 +1,21 @@
+use common::DatabaseTestHelper;
+use sqlengine::database::Database;
+
+mod common;
+
+#[test]
+fn test_ctes() {
+    let db = Database::new().unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester.run_file("tests/resources/sql/ctes.slt").unwrap();
+}
+
+#[test]
+fn test_ctes_materialized() {
+    let mut db = Database::new().unwrap();
+    db.set_materialize_ctes(true);
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester.run_file("tests/resources/sql/ctes.slt").unwrap();
+}
@@ -0,0 +1,14 @@
+use common::DatabaseTestHelper;
+use sqlengine::database::Database;
+
+mod common;
+
+#[test]
+fn test_derived_tables() {
+    let db = Database::new().unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester
+        .run_file("tests/resources/sql/derived_tables.slt")
+        .unwrap();
+}
@@ -32,3 +32,65 @@ fn test_binary_operators() {
         .run_file("tests/resources/sql/binary_operators.slt")
         .unwrap();
 }
+
+#[test]
+fn test_binder() {
+    let db = Database::new().unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester.run_file("tests/resources/sql/binder.slt").unwrap();
+}
+
+#[test]
+fn test_null_logic() {
+    let db = Database::new().unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester
+        .run_file("tests/resources/sql/null_logic.slt")
+        .unwrap();
+}
+
+#[test]
+fn test_cast() {
+    let db = Database::new().unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester.run_file("tests/resources/sql/cast.slt").unwrap();
+}
+
+#[test]
+fn test_conditional() {
+    let db = Database::new().unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester
+        .run_file("tests/resources/sql/conditional.slt")
+        .unwrap();
+}
+
+#[test]
+fn test_pattern() {
+    let db = Database::new().unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester.run_file("tests/resources/sql/pattern.slt").unwrap();
+}
+
+#[test]
+fn test_functions() {
+    let db = Database::new().unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester
+        .run_file("tests/resources/sql/functions.slt")
+        .unwrap();
+}
+
+#[test]
+fn test_datetime() {
+    let db = Database::new().unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester.run_file("tests/resources/sql/datetime.slt").unwrap();
+}
@@ -22,3 +22,33 @@ fn test_inner_join() {
         .run_file("tests/resources/sql/inner_join.slt")
         .unwrap();
 }
+
+#[test]
+fn test_hash_join() {
+    let db = Database::new().unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester
+        .run_file("tests/resources/sql/hash_join.slt")
+        .unwrap();
+}
+
+#[test]
+fn test_outer_join() {
+    let db = Database::new().unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester
+        .run_file("tests/resources/sql/outer_join.slt")
+        .unwrap();
+}
+
+#[test]
+fn test_using_join() {
+    let db = Database::new().unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester
+        .run_file("tests/resources/sql/using_join.slt")
+        .unwrap();
+}
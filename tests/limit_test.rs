@@ -1,5 +1,6 @@
 use common::DatabaseTestHelper;
 use sqlengine::database::Database;
+use sqlengine::types::field::Field;
 
 mod common;
 
@@ -10,3 +11,20 @@ fn test_limit() {
     let mut tester = sqllogictest::Runner::new(db_helper);
     tester.run_file("tests/resources/sql/limit.slt").unwrap();
 }
+
+#[test]
+fn test_limit_with_params() {
+    let db = Database::new().unwrap();
+    let result_set = db
+        .execute_with_params(
+            "select age from 'tests/resources/data/animals1.parquet' limit $1 offset $2 - 1",
+            &[Field::Int(2), Field::Int(3)],
+        )
+        .unwrap();
+    let rows = result_set
+        .data_chunks
+        .iter()
+        .flat_map(|chunk| chunk.get_rows().into_iter().map(|row| row[0].to_string()))
+        .collect::<Vec<String>>();
+    assert_eq!(rows, vec!["3", "4"]);
+}
@@ -0,0 +1,165 @@
+use common::DatabaseTestHelper;
+use sqlengine::{database::Database, types::ResultSet};
+
+mod common;
+
+fn get_rows(result_set: &ResultSet) -> Vec<Vec<String>> {
+    result_set
+        .data_chunks
+        .iter()
+        .flat_map(|chunk| {
+            chunk
+                .get_rows()
+                .into_iter()
+                .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+        })
+        .collect()
+}
+
+#[test]
+fn test_optimizer_trace() {
+    let db = Database::new().unwrap();
+
+    let fired_rules = db
+        .optimizer_trace("select a1.animal, a2.age from 'tests/resources/data/animals1.parquet' as a1 join 'tests/resources/data/animals2.parquet' as a2 on a1.animal = a2.animal")
+        .unwrap();
+    assert_eq!(
+        fired_rules,
+        vec!["projection_pushdown", "hash_join_selection"]
+    );
+
+    let fired_rules = db
+        .optimizer_trace("select a1.animal, a2.age from 'tests/resources/data/animals1.parquet' as a1, 'tests/resources/data/animals2.parquet' as a2 where a1.animal = a2.animal")
+        .unwrap();
+    assert_eq!(
+        fired_rules,
+        vec![
+            "predicate_pushdown",
+            "projection_pushdown",
+            "hash_join_selection"
+        ]
+    );
+
+    let fired_rules = db
+        .optimizer_trace("select animal from 'tests/resources/data/animals1.parquet' where age > 2")
+        .unwrap();
+    assert_eq!(
+        fired_rules,
+        vec!["predicate_pushdown", "projection_pushdown"]
+    );
+
+    let fired_rules = db
+        .optimizer_trace("select * from 'tests/resources/data/animals1.parquet'")
+        .unwrap();
+    assert!(fired_rules.is_empty());
+}
+
+#[test]
+fn test_optimizer_disable_rule() {
+    let sql = "select a1.animal, a2.age from 'tests/resources/data/animals1.parquet' as a1, 'tests/resources/data/animals2.parquet' as a2 where a1.animal = a2.animal order by a1.animal, a2.age";
+
+    let mut db = Database::new().unwrap();
+    let expected = get_rows(&db.execute(sql).unwrap());
+
+    db.set_optimizer_rule_enabled("projection_pushdown", false)
+        .unwrap();
+    db.set_optimizer_rule_enabled("hash_join_selection", false)
+        .unwrap();
+    assert_eq!(db.optimizer_trace(sql).unwrap(), vec!["predicate_pushdown"]);
+    assert_eq!(get_rows(&db.execute(sql).unwrap()), expected);
+
+    db.set_optimizer_rule_enabled("predicate_pushdown", false)
+        .unwrap();
+    assert!(db.optimizer_trace(sql).unwrap().is_empty());
+    assert_eq!(get_rows(&db.execute(sql).unwrap()), expected);
+
+    // without merging the filter the join has no predicate for a hash join to use
+    db.set_optimizer_rule_enabled("hash_join_selection", true)
+        .unwrap();
+    assert!(db.optimizer_trace(sql).unwrap().is_empty());
+}
+
+#[test]
+fn test_optimizer_unknown_rule() {
+    let mut db = Database::new().unwrap();
+    assert_eq!(
+        db.set_optimizer_rule_enabled("not_a_rule", false)
+            .unwrap_err()
+            .to_string(),
+        "Planner Error: Unknown optimizer rule: not_a_rule"
+    );
+    assert!(db.optimizer_rules().contains(&"hash_join_selection"));
+}
+
+#[test]
+fn test_predicate_pushdown() {
+    let db = Database::new().unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester
+        .run_file("tests/resources/sql/predicate_pushdown.slt")
+        .unwrap();
+}
+
+#[test]
+fn test_predicate_pushdown_disabled() {
+    let mut db = Database::new().unwrap();
+    db.set_optimizer_rule_enabled("predicate_pushdown", false)
+        .unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester
+        .run_file("tests/resources/sql/predicate_pushdown.slt")
+        .unwrap();
+}
+
+#[test]
+fn test_projection_pushdown() {
+    let db = Database::new().unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester
+        .run_file("tests/resources/sql/projection_pushdown.slt")
+        .unwrap();
+}
+
+#[test]
+fn test_projection_pushdown_disabled() {
+    let mut db = Database::new().unwrap();
+    db.set_optimizer_rule_enabled("projection_pushdown", false)
+        .unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester
+        .run_file("tests/resources/sql/projection_pushdown.slt")
+        .unwrap();
+}
+
+#[test]
+fn test_subquery_decorrelation() {
+    let db = Database::new().unwrap();
+
+    let fired_rules = db
+        .optimizer_trace("select animal from 'tests/resources/data/animals1.parquet' as a where exists (select * from 'tests/resources/data/animals2.parquet' as b where b.animal = a.animal)")
+        .unwrap();
+    assert_eq!(
+        fired_rules,
+        vec![
+            "subquery_decorrelation",
+            "projection_pushdown",
+            "hash_join_selection"
+        ]
+    );
+
+    let fired_rules = db
+        .optimizer_trace("select animal, (select count(*) from 'tests/resources/data/animals2.parquet' as b where b.animal = a.animal) from 'tests/resources/data/animals1.parquet' as a")
+        .unwrap();
+    assert_eq!(fired_rules[0], "subquery_decorrelation");
+    assert!(fired_rules.contains(&"hash_join_selection".to_string()));
+
+    // the ages can be null so NOT IN cannot become an anti join
+    let fired_rules = db
+        .optimizer_trace("select animal from 'tests/resources/data/animals1.parquet' as a where age not in (select age from 'tests/resources/data/animals2.parquet' as b where b.animal = a.animal)")
+        .unwrap();
+    assert!(!fired_rules.contains(&"subquery_decorrelation".to_string()));
+}
@@ -0,0 +1,80 @@
+use sqlengine::database::Database;
+
+fn count_rows(db: &Database, sql: &str) -> usize {
+    db.execute(sql)
+        .unwrap()
+        .data_chunks
+        .iter()
+        .map(|chunk| chunk.size())
+        .sum()
+}
+
+#[test]
+fn test_row_group_pruning() {
+    let db = Database::new().unwrap();
+
+    assert_eq!(
+        count_rows(
+            &db,
+            "select movie from 'tests/resources/data/movies1.parquet' where year > 2012"
+        ),
+        0
+    );
+    assert_eq!(db.row_groups_pruned(), 1);
+
+    assert_eq!(
+        count_rows(
+            &db,
+            "select movie from 'tests/resources/data/movies1.parquet' where movie = 'zzz' or 1976 > year"
+        ),
+        0
+    );
+    assert_eq!(db.row_groups_pruned(), 2);
+
+    assert_eq!(
+        count_rows(
+            &db,
+            "select id from 'tests/resources/data/userdata1.parquet' where id > 10 and salary > 1000000.0"
+        ),
+        0
+    );
+    assert_eq!(db.row_groups_pruned(), 3);
+
+    // the filter is pushed into the scan below the join before the row groups are pruned
+    assert_eq!(
+        count_rows(
+            &db,
+            "select m1.movie from 'tests/resources/data/movies1.parquet' as m1 join 'tests/resources/data/movies2.parquet' as m2 on m1.movie = m2.movie where m1.year = 1900"
+        ),
+        0
+    );
+    assert_eq!(db.row_groups_pruned(), 4);
+}
+
+#[test]
+fn test_row_group_not_pruned() {
+    let db = Database::new().unwrap();
+
+    assert_eq!(
+        count_rows(
+            &db,
+            "select movie from 'tests/resources/data/movies1.parquet' where movie = 'drive'"
+        ),
+        1
+    );
+    assert_eq!(
+        count_rows(
+            &db,
+            "select movie from 'tests/resources/data/movies1.parquet' where year >= 2012 or movie = 'zzz'"
+        ),
+        2
+    );
+    assert_eq!(
+        count_rows(
+            &db,
+            "select movie from 'tests/resources/data/movies1.parquet' where year <= 1976"
+        ),
+        3
+    );
+    assert_eq!(db.row_groups_pruned(), 0);
+}
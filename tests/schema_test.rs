@@ -0,0 +1,108 @@
+use sqlengine::{database::Database, types::data_type::DataType};
+
+fn get_types(db: &Database, sql: &str) -> Vec<(DataType, bool)> {
+    db.execute(sql)
+        .unwrap()
+        .output_schema
+        .columns
+        .iter()
+        .map(|column| (column.data_type, column.nullable))
+        .collect()
+}
+
+#[test]
+fn test_scan_types() {
+    let db = Database::new().unwrap();
+
+    assert_eq!(
+        get_types(&db, "select * from 'tests/resources/data/animals1.parquet'"),
+        vec![
+            (DataType::String, true),
+            (DataType::String, true),
+            (DataType::String, true),
+            (DataType::Int, true),
+        ]
+    );
+    assert_eq!(
+        get_types(
+            &db,
+            "select id, salary, registration_dttm from 'tests/resources/data/userdata1.parquet'"
+        ),
+        vec![
+            (DataType::Int, true),
+            (DataType::Double, true),
+            (DataType::Timestamp, true),
+        ]
+    );
+}
+
+#[test]
+fn test_projection_types() {
+    let db = Database::new().unwrap();
+
+    assert_eq!(
+        get_types(&db, "select 1, 'a', 1.5, true, null"),
+        vec![
+            (DataType::Int, false),
+            (DataType::String, false),
+            (DataType::Float, false),
+            (DataType::Boolean, false),
+            (DataType::Null, true),
+        ]
+    );
+    assert_eq!(
+        get_types(
+            &db,
+            "select age + 1, -age, animal + colour, age > 2 as a from 'tests/resources/data/animals1.parquet'"
+        ),
+        vec![
+            (DataType::Int, true),
+            (DataType::Int, true),
+            (DataType::String, true),
+            (DataType::Boolean, true),
+        ]
+    );
+}
+
+#[test]
+fn test_aggregate_types() {
+    let db = Database::new().unwrap();
+
+    assert_eq!(
+        get_types(
+            &db,
+            "select count(*), sum(id), min(first_name), max(salary), avg(id), gender from 'tests/resources/data/userdata1.parquet' group by gender"
+        ),
+        vec![
+            (DataType::Int, false),
+            (DataType::Long, true),
+            (DataType::String, true),
+            (DataType::Double, true),
+            (DataType::Float, true),
+            (DataType::String, true),
+        ]
+    );
+}
+
+#[test]
+fn test_join_types() {
+    let db = Database::new().unwrap();
+
+    assert_eq!(
+        get_types(
+            &db,
+            "select m1.year, m2.movie from 'tests/resources/data/movies1.parquet' as m1 left join 'tests/resources/data/movies2.parquet' as m2 on m1.movie = m2.movie"
+        ),
+        vec![
+            (DataType::Int, true),
+            (DataType::String, true),
+        ]
+    );
+    assert_eq!(
+        get_types(
+            &db,
+            "select * from 'tests/resources/data/movies1.parquet' join 'tests/resources/data/movies2.parquet' using (movie)"
+        )[0],
+        (DataType::String, true)
+    );
+}
@@ -0,0 +1,26 @@
+use common::DatabaseTestHelper;
+use sqlengine::database::Database;
+
+mod common;
+
+#[test]
+fn test_subqueries() {
+    let db = Database::new().unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester
+        .run_file("tests/resources/sql/subqueries.slt")
+        .unwrap();
+}
+
+#[test]
+fn test_subqueries_not_decorrelated() {
+    let mut db = Database::new().unwrap();
+    db.set_optimizer_rule_enabled("subquery_decorrelation", false)
+        .unwrap();
+    let db_helper = DatabaseTestHelper(db);
+    let mut tester = sqllogictest::Runner::new(db_helper);
+    tester
+        .run_file("tests/resources/sql/subqueries.slt")
+        .unwrap();
+}